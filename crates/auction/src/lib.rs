@@ -1,39 +1,155 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
+use async_lock::Mutex;
 use async_trait::async_trait;
-use ethereum::Transaction;
-use oe4_runtime::buffers;
+use ethereum::{Address, Transaction, U256};
+use event_listener::Event;
+use oe4_runtime::{buffers, MessageId, Target};
+use std::{
+  collections::{BTreeMap, HashMap},
+  mem,
+};
 
 pub type BlockProposal = Vec<Transaction>;
 
+/// The transactions of one account that wait to be proposed.
+#[derive(Default)]
+struct Account {
+  /// ordered by nonce, the first one is proposed next
+  txs: BTreeMap<U256, Transaction>,
+  /// the turn of this account in the auction
+  turn: Option<MessageId>,
+}
+
+#[derive(Default)]
+struct Pool {
+  accounts: HashMap<Address, Account>,
+  /// the block proposal being put together, kept here rather than by
+  /// the consumer so a cancelled consumer doesn't lose its transactions
+  proposal: BlockProposal,
+  /// no more transactions are accepted once set
+  completed: bool,
+}
+
 /// This type is responsible for selecting the most appropriate set of transactions
 /// to be included in the next block.
 ///
 /// In this first iteration it has a basic mock logic, that just porposes a
-/// new block for every set of 3 transactions.
+/// new block for every set of 3 transactions. A transaction can't be included
+/// before the lower nonces of its sender, so accounts take turns ranked by the
+/// effective tip of their lowest-nonce transaction.
 pub struct TransactionsAuction {
-  incoming_tx: buffers::UnboundedBuffer<Transaction>,
+  base_fee: U256,
+  /// one turn per account with pending transactions, only touched
+  /// while the pool is locked
+  turns: buffers::PriorityBuffer<(Address, U256), U256>,
+  pending: Mutex<Pool>,
+  /// signals consumers that a turn was taken or the auction completed
+  ready: Event,
+}
+
+/// The fee per gas that goes to the block producer when the transaction
+/// is included in a block with the given base fee.
+fn effective_tip(tx: &Transaction, base_fee: U256) -> U256 {
+  tx.max_priority_fee_per_gas()
+    .min(tx.max_fee_per_gas().saturating_sub(base_fee))
 }
 
 impl TransactionsAuction {
   pub fn new() -> Self {
+    Self::with_base_fee(U256::zero())
+  }
+
+  /// Creates an auction that ranks transactions by what they would pay
+  /// on top of the given base fee.
+  pub fn with_base_fee(base_fee: U256) -> Self {
     TransactionsAuction {
-      incoming_tx: buffers::UnboundedBuffer::new(),
+      base_fee,
+      turns: buffers::PriorityBuffer::new(|&(_, tip)| tip),
+      pending: Mutex::new(Pool::default()),
+      ready: Event::new(),
     }
   }
 
   /// adds a transaction to the auction as a candidate for the next
   /// block that will be proposed
   pub async fn include_transaction(&self, tx: Transaction) {
-    buffers::send(&self.incoming_tx, tx).await;
+    buffers::send(self, tx).await;
+  }
+
+  /// Queues the next turn of an account, ranked by its lowest nonce.
+  async fn take_turn(&self, sender: Address, account: &mut Account) {
+    if let Some(tx) = account.txs.values().next() {
+      let turn = oe4_runtime::Message::new((sender, effective_tip(tx, self.base_fee)));
+      account.turn = Some(turn.id());
+      self.turns.accept(turn).await;
+      self.ready.notify_additional(1);
+    }
+  }
+
+  /// Moves the lowest-nonce transaction of the account whose turn it is
+  /// into the proposal and queues the account's next turn.
+  async fn propose(&self, pool: &mut Pool, sender: Address) {
+    let account = pool
+      .accounts
+      .get_mut(&sender)
+      .expect("accounts with a turn have pending transactions");
+    let (_, tx) = account
+      .txs
+      .pop_first()
+      .expect("accounts with a turn have pending transactions");
+    pool.proposal.push(tx);
+
+    account.turn = None;
+    self.take_turn(sender, account).await;
+    if account.txs.is_empty() {
+      pool.accounts.remove(&sender);
+    }
   }
 }
 
 #[async_trait]
 impl buffers::Target<Transaction> for TransactionsAuction {
+  /// Declines unsigned transactions and a second transaction for a nonce
+  /// that is already pending, the first one stays in the auction.
   async fn accept(&self, message: oe4_runtime::Message<Transaction>) -> oe4_runtime::MessageStatus {
-    self.incoming_tx.accept(message).await
+    let tx = message.release();
+    let sender = match tx.sender() {
+      Ok(sender) => sender,
+      Err(_) => return oe4_runtime::MessageStatus::Declined,
+    };
+
+    let mut pending = self.pending.lock().await;
+    if pending.completed {
+      return oe4_runtime::MessageStatus::Declined;
+    }
+    let account = pending.accounts.entry(sender).or_default();
+    if account.txs.contains_key(&tx.nonce) {
+      return oe4_runtime::MessageStatus::Declined;
+    }
+
+    let lowest = account
+      .txs
+      .keys()
+      .next()
+      .is_none_or(|nonce| tx.nonce < *nonce);
+    let tip = effective_tip(&tx, self.base_fee);
+    account.txs.insert(tx.nonce, tx);
+    match account.turn {
+      None => self.take_turn(sender, account).await,
+      // the turn now stands for the new lowest nonce
+      Some(turn) if lowest => {
+        self.turns.reprioritize(turn, tip).await;
+      }
+      Some(_) => {}
+    }
+    oe4_runtime::MessageStatus::Accepted
+  }
+
+  async fn complete(&self) {
+    self.pending.lock().await.completed = true;
+    self.ready.notify(usize::MAX);
   }
 }
 
@@ -43,21 +159,181 @@ impl buffers::Source<BlockProposal> for TransactionsAuction {
     None
   }
 
-  /// Aggregate transactions until 3 txs are available and then return them as one proposal
+  /// Aggregate transactions until 3 txs are available and then return them as one
+  /// proposal, in the order the accounts took their turns. Once the auction is
+  /// completed, the remaining transactions form one last proposal.
+  ///
+  /// Turns are only taken while the pool is locked, and nothing else uses
+  /// the turns buffer then, so the only points where a dropped consumer can
+  /// stop are waiting for the pool and waiting for turns. The proposal it
+  /// was putting together stays in the pool for the next consumer.
   async fn consume(&self) -> buffers::Result<oe4_runtime::Message<BlockProposal>> {
-    let mut txs = Vec::with_capacity(3);
-    while txs.len() != 3 {
-      txs.push(self.incoming_tx.consume().await?.release());
+    loop {
+      let mut pending = self.pending.lock().await;
+      while pending.proposal.len() != 3 {
+        match self.turns.try_consume() {
+          Some(turn) => self.propose(&mut pending, turn.release().0).await,
+          None => break,
+        }
+      }
+
+      let drained = pending.completed && pending.accounts.is_empty();
+      if pending.proposal.len() == 3 || (drained && !pending.proposal.is_empty()) {
+        return Ok(buffers::Message::new(mem::take(&mut pending.proposal)));
+      }
+      if drained {
+        return Err(buffers::Error::Completed);
+      }
+
+      let listener = self.ready.listen();
+      drop(pending);
+      listener.await;
     }
-    txs.sort_by(|a, b| a.nonce.cmp(&b.nonce));
-    Ok(buffers::Message::new(txs))
   }
 }
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+  use ethereum::{SecretKey, TransactionKind};
+  use futures_await_test::async_test;
+  use futures_lite::future;
+  use oe4_runtime::{receive, send, CancellationToken, MessageStatus, Source};
+
+  fn signed(key: &SecretKey, nonce: u64, kind: TransactionKind) -> Transaction {
+    let mut tx = Transaction {
+      kind,
+      chain_id: Some(1),
+      nonce: U256::from(nonce),
+      ..Transaction::default()
+    };
+    tx.sign(key).unwrap();
+    tx
+  }
+
+  fn legacy(gas_price: u64) -> TransactionKind {
+    TransactionKind::Legacy {
+      gas_price: gas_price.into(),
+    }
+  }
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  #[async_test]
+  async fn effective_tip_order() {
+    let auction = TransactionsAuction::with_base_fee(U256::from(10));
+    let dynamic_fee =
+      |max_priority_fee_per_gas: u64, max_fee_per_gas: u64| TransactionKind::DynamicFee {
+        max_priority_fee_per_gas: max_priority_fee_per_gas.into(),
+        max_fee_per_gas: max_fee_per_gas.into(),
+        access_list: vec![],
+      };
+
+    let kinds = vec![
+      // legacy transactions tip everything above the base fee
+      legacy(15),
+      legacy(12),
+      // capped by the max fee
+      dynamic_fee(8, 13),
+      // capped by the priority fee
+      dynamic_fee(4, 100),
+    ];
+    // every transaction comes from another account
+    for (nonce, kind) in kinds.into_iter().enumerate() {
+      let tx = signed(&SecretKey::random(), nonce as u64 + 1, kind);
+      send(&auction, tx).await;
+    }
+
+    let proposal = receive(&auction).await.unwrap();
+    let nonces: Vec<_> = proposal.iter().map(|tx| tx.nonce.as_u64()).collect();
+    assert_eq!(nonces, [1, 4, 3]);
+  }
+
+  #[async_test]
+  async fn nonce_order() {
+    let auction = TransactionsAuction::new();
+    let (alice, bob) = (SecretKey::random(), SecretKey::random());
+
+    // the higher tip of alice's second transaction doesn't let it
+    // skip her first one, even when it arrives before it
+    send(&auction, signed(&alice, 2, legacy(10))).await;
+    send(&auction, signed(&bob, 7, legacy(5))).await;
+    send(&auction, signed(&alice, 1, legacy(1))).await;
+
+    let proposal = receive(&auction).await.unwrap();
+    let order: Vec<_> = proposal
+      .iter()
+      .map(|tx| (tx.sender().unwrap(), tx.nonce.as_u64()))
+      .collect();
+    assert_eq!(
+      order,
+      [
+        (bob.address(), 7),
+        (alice.address(), 1),
+        (alice.address(), 2)
+      ]
+    );
+
+    // unsigned transactions have no sender to order them by
+    assert_eq!(
+      send(&auction, Transaction::default()).await,
+      MessageStatus::Declined
+    );
+    assert_eq!(
+      send(&auction, signed(&bob, 8, legacy(5))).await,
+      MessageStatus::Accepted
+    );
+    assert_eq!(
+      send(&auction, signed(&bob, 8, legacy(6))).await,
+      MessageStatus::Declined
+    );
+  }
+
+  #[async_test]
+  async fn drain_on_complete() {
+    let auction = TransactionsAuction::new();
+    let key = SecretKey::random();
+    for nonce in 1..=4u64 {
+      send(&auction, signed(&key, nonce, legacy(1))).await;
+    }
+    auction.complete().await;
+
+    let proposal = receive(&auction).await.unwrap();
+    let nonces: Vec<_> = proposal.iter().map(|tx| tx.nonce.as_u64()).collect();
+    assert_eq!(nonces, [1, 2, 3]);
+    assert_eq!(receive(&auction).await.unwrap().len(), 1);
+    assert!(matches!(
+      receive(&auction).await,
+      Err(buffers::Error::Completed)
+    ));
+  }
+
+  #[async_test]
+  async fn cancelled_consume() {
+    let auction = TransactionsAuction::new();
+    let key = SecretKey::random();
+    send(&auction, signed(&key, 1, legacy(1))).await;
+    send(&auction, signed(&key, 2, legacy(1))).await;
+
+    // the consumer takes both transactions into the proposal before it
+    // starts waiting for a third one and gets cancelled
+    let token = CancellationToken::new();
+    let cancel = async {
+      token.cancel();
+      future::pending().await
+    };
+    assert!(matches!(
+      future::or(auction.consume_cancellable(&token), cancel).await,
+      Err(buffers::Error::Cancelled)
+    ));
+
+    send(&auction, signed(&key, 3, legacy(1))).await;
+    auction.complete().await;
+    let proposal = receive(&auction).await.unwrap();
+    let nonces: Vec<_> = proposal.iter().map(|tx| tx.nonce.as_u64()).collect();
+    assert_eq!(nonces, [1, 2, 3]);
+  }
 }
@@ -0,0 +1,84 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Compares decoding blocks from memory, from a reader
+//! and lazily through views.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ethereum::{Block, RlpReader, RlpView, Transaction};
+
+fn fixture(hex: &str) -> Vec<u8> {
+  hex::decode(hex.trim()).unwrap()
+}
+
+/// A post-Shanghai block holding 200 legacy and typed transactions
+fn block() -> Vec<u8> {
+  let transactions: Vec<Transaction> = [
+    include_str!("../res/mainnet/tx_0ec0b6a2.hex"),
+    include_str!("../res/mainnet/tx_280cde7c.hex"),
+    include_str!("../res/mainnet/tx_bb3a336e.hex"),
+    include_str!("../res/mainnet/tx_ce4dc6d7.hex"),
+  ]
+  .iter()
+  .map(|hex| Transaction::decode(&fixture(hex)).unwrap())
+  .collect();
+
+  let block = Block {
+    header: ethereum::rlp_deserialize(&fixture(include_str!("../res/mainnet/header_19449567.hex")))
+      .unwrap(),
+    transactions: transactions.iter().cycle().take(200).cloned().collect(),
+    ommers: vec![],
+    withdrawals: Some(vec![]),
+  };
+  ethereum::rlp_serialize(&block).unwrap()
+}
+
+fn bench_block(c: &mut Criterion) {
+  let block = block();
+
+  c.bench_function("decode_block", |b| {
+    b.iter(|| ethereum::rlp_deserialize::<Block>(black_box(&block)).unwrap())
+  });
+  c.bench_function("decode_block_from_reader", |b| {
+    b.iter(|| ethereum::rlp_deserialize_from::<_, Block>(black_box(&block[..])).unwrap())
+  });
+  c.bench_function("decode_block_last_transaction", |b| {
+    b.iter(|| {
+      let block = ethereum::rlp_deserialize::<Block>(black_box(&block)).unwrap();
+      block.transactions.last().cloned().unwrap()
+    })
+  });
+  c.bench_function("view_block_last_transaction", |b| {
+    b.iter(|| {
+      let transactions = RlpView::new(black_box(&block)).unwrap().at(1).unwrap();
+      let last = transactions.iter().last().unwrap();
+      last.decode::<Transaction>().unwrap()
+    })
+  });
+}
+
+fn bench_stream(c: &mut Criterion) {
+  let block = block();
+  let file = block.repeat(100);
+
+  c.bench_function("stream_100_blocks", |b| {
+    b.iter(|| {
+      RlpReader::new(black_box(&file[..]))
+        .items::<Block>()
+        .map(|block| block.unwrap().transactions.len())
+        .sum::<usize>()
+    })
+  });
+  c.bench_function("decode_100_blocks_in_memory", |b| {
+    b.iter(|| {
+      black_box(&file)
+        .chunks(block.len())
+        .map(|chunk| ethereum::rlp_deserialize::<Block>(chunk).unwrap())
+        .map(|block| block.transactions.len())
+        .sum::<usize>()
+    })
+  });
+}
+
+criterion_group!(benches, bench_block, bench_stream);
+criterion_main!(benches);
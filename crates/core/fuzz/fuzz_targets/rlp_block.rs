@@ -0,0 +1,16 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+#![no_main]
+
+use ethereum::{Block, RlpView};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  if let Ok(block) = ethereum::rlp_deserialize::<Block>(data) {
+    // blocks are only accepted in their canonical encoding
+    assert!(RlpView::new(data).is_ok());
+    let encoded = ethereum::rlp_serialize(&block).unwrap();
+    assert_eq!(ethereum::rlp_deserialize::<Block>(&encoded).unwrap(), block);
+  }
+});
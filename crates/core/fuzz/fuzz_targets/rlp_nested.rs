@@ -0,0 +1,32 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Arbitrarily nested lists, compared against the rlp crate
+
+#![no_main]
+
+use ethereum::RlpView;
+use libfuzzer_sys::fuzz_target;
+use rlp::Rlp;
+
+fn check(view: RlpView, rlp: &Rlp) {
+  assert_eq!(view.as_raw(), rlp.as_raw());
+  match view.is_list() {
+    true => {
+      assert_eq!(view.item_count().unwrap(), rlp.item_count().unwrap());
+      for (i, item) in view.iter().enumerate() {
+        check(item, &rlp.at(i).unwrap());
+      }
+      // lists are encoded from their items
+      let items: Vec<RlpView> = view.iter().collect();
+      assert_eq!(ethereum::rlp_serialize(&items).unwrap(), view.as_raw());
+    }
+    false => assert_eq!(view.data().unwrap(), rlp.data().unwrap()),
+  }
+}
+
+fuzz_target!(|data: &[u8]| {
+  if let Ok(view) = ethereum::rlp_deserialize::<RlpView>(data) {
+    check(view, &Rlp::new(data));
+  }
+});
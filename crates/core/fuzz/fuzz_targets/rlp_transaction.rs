@@ -0,0 +1,24 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+#![no_main]
+
+use ethereum::Transaction;
+use libfuzzer_sys::fuzz_target;
+
+// only canonical envelopes decode, so a decoded transaction encodes back
+// to exactly the bytes it came from.
+fuzz_target!(|data: &[u8]| {
+  if let Ok(tx) = Transaction::decode(data) {
+    assert_eq!(tx.encode(), data);
+  }
+
+  if let Ok(tx) = ethereum::rlp_deserialize::<Transaction>(data) {
+    let encoded = ethereum::rlp_serialize(&tx).unwrap();
+    assert_eq!(
+      ethereum::rlp_deserialize::<Transaction>(&encoded).unwrap(),
+      tx
+    );
+    assert_eq!(Transaction::decode(&tx.encode()).unwrap(), tx);
+  }
+});
@@ -2,13 +2,44 @@
 // Licensed under the Apache License, Version 2.0.
 
 use crate::{Keccak, U256};
-use serde::{Deserialize, Serialize};
+use oe4_rlp::{RlpDecodable, RlpEncodable};
 
 /// https://ethereum.stackexchange.com/questions/268/ethereum-block-architecture
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, RlpEncodable, RlpDecodable)]
 pub struct Account {
+  #[rlp(encodable)]
   pub nonce: U256,
+  #[rlp(encodable)]
   pub balance: U256,
+  #[rlp(encodable)]
   pub storage_root: Keccak,
+  #[rlp(encodable)]
   pub code_hash: Keccak,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hex_literal::hex;
+
+  #[test]
+  fn empty_account() -> Result<(), Box<dyn std::error::Error>> {
+    let account = Account {
+      nonce: U256::zero(),
+      balance: U256::zero(),
+      storage_root: hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421").into(),
+      code_hash: hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470").into(),
+    };
+    let encoded = crate::rlp_serialize(&account)?;
+    assert_eq!(
+      encoded,
+      hex!(
+        "f8448080a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+        "a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+      )
+    );
+    assert_eq!(crate::rlp_deserialize::<Account>(&encoded)?, account);
+
+    Ok(())
+  }
+}
@@ -0,0 +1,77 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Address, Keccak, H256, H64, U256};
+use keccak_hash::keccak;
+use keccak_hasher::KeccakHasher;
+use rlp::RlpStream;
+use std::collections::BTreeMap;
+
+/// Contents of the first block of a chain, everything that is
+/// needed to reconstruct its header except the fork-dependent fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Genesis {
+  pub parent_hash: Keccak,
+  pub beneficiary: Address,
+  pub difficulty: U256,
+  pub number: u64,
+  pub gas_limit: U256,
+  pub gas_used: U256,
+  pub timestamp: u64,
+  pub extra_data: Vec<u8>,
+  pub mix_hash: Keccak,
+  pub nonce: H64,
+
+  /// Overrides the initial EIP-1559 base fee when London is active at genesis.
+  pub base_fee_per_gas: Option<U256>,
+
+  /// Override the EIP-4844 header fields when Cancun is active at genesis.
+  pub blob_gas_used: Option<u64>,
+  pub excess_blob_gas: Option<u64>,
+
+  /// Accounts that exist before any transaction is executed.
+  pub alloc: BTreeMap<Address, GenesisAccount>,
+}
+
+/// An account preallocated in the genesis state.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GenesisAccount {
+  pub balance: U256,
+  pub nonce: u64,
+  pub code: Vec<u8>,
+  pub storage: BTreeMap<H256, H256>,
+}
+
+impl GenesisAccount {
+  /// Root of the secure trie holding this account's storage.
+  /// Zero-valued slots are not part of the trie.
+  pub fn storage_root(&self) -> Keccak {
+    let slots = self
+      .storage
+      .iter()
+      .filter(|(_, value)| !value.is_zero())
+      .map(|(key, value)| (key, rlp::encode(&U256::from(value.as_bytes()))));
+    triehash::sec_trie_root::<KeccakHasher, _, _, _>(slots).into()
+  }
+
+  /// The state trie leaf of this account: [nonce, balance, storage_root, code_hash].
+  fn encode(&self) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&self.nonce);
+    stream.append(&self.balance);
+    stream.append(&self.storage_root());
+    stream.append(&keccak(&self.code));
+    stream.as_raw().to_vec()
+  }
+}
+
+impl Genesis {
+  /// Root of the world state trie after applying the genesis allocations.
+  pub fn state_root(&self) -> Keccak {
+    let accounts = self
+      .alloc
+      .iter()
+      .map(|(address, account)| (address, account.encode()));
+    triehash::sec_trie_root::<KeccakHasher, _, _, _>(accounts).into()
+  }
+}
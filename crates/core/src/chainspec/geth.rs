@@ -0,0 +1,160 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! geth genesis.json format, as produced by `geth dumpgenesis`,
+//! https://geth.ethereum.org/docs/fundamentals/private-network
+
+use super::json::{Addr, Bytes, Hash, Quantity, Uint};
+use super::{ChainSpec, ChainSpecError, Engine, Forks, Genesis, GenesisAccount};
+use crate::{H64, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GethGenesis {
+  config: Config,
+  nonce: Option<Uint>,
+  timestamp: Option<Uint>,
+  extra_data: Option<Bytes>,
+  gas_limit: Quantity,
+  difficulty: Quantity,
+  mix_hash: Option<Hash>,
+  coinbase: Option<Addr>,
+  number: Option<Uint>,
+  gas_used: Option<Quantity>,
+  parent_hash: Option<Hash>,
+  base_fee_per_gas: Option<Quantity>,
+  excess_blob_gas: Option<Uint>,
+  blob_gas_used: Option<Uint>,
+  alloc: BTreeMap<Addr, Account>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+  chain_id: Uint,
+  homestead_block: Option<Uint>,
+  dao_fork_block: Option<Uint>,
+  #[serde(default)]
+  dao_fork_support: bool,
+  eip150_block: Option<Uint>,
+  eip155_block: Option<Uint>,
+  eip158_block: Option<Uint>,
+  byzantium_block: Option<Uint>,
+  constantinople_block: Option<Uint>,
+  petersburg_block: Option<Uint>,
+  istanbul_block: Option<Uint>,
+  muir_glacier_block: Option<Uint>,
+  berlin_block: Option<Uint>,
+  london_block: Option<Uint>,
+  arrow_glacier_block: Option<Uint>,
+  gray_glacier_block: Option<Uint>,
+  merge_netsplit_block: Option<Uint>,
+  shanghai_time: Option<Uint>,
+  cancun_time: Option<Uint>,
+  prague_time: Option<Uint>,
+  terminal_total_difficulty: Option<Quantity>,
+  clique: Option<Clique>,
+}
+
+#[derive(Deserialize)]
+struct Clique {
+  period: Uint,
+  epoch: Uint,
+}
+
+#[derive(Deserialize)]
+struct Account {
+  balance: Quantity,
+  nonce: Option<Uint>,
+  code: Option<Bytes>,
+  storage: Option<BTreeMap<Hash, Hash>>,
+}
+
+fn get(value: Option<Uint>) -> Option<u64> {
+  value.map(|Uint(v)| v)
+}
+
+pub(super) fn parse(json: &str) -> Result<ChainSpec, ChainSpecError> {
+  let spec: GethGenesis = serde_json::from_str(json)?;
+  let config = spec.config;
+  let chain_id = config.chain_id.0;
+  let dao_fork_support = config.dao_fork_support;
+
+  // geth falls back to ethash when no other engine is configured
+  let engine = match config.clique {
+    Some(Clique { period, epoch }) => Engine::Clique {
+      period: period.0,
+      epoch: epoch.0,
+    },
+    None => Engine::Ethash,
+  };
+
+  let forks = Forks {
+    homestead_block: get(config.homestead_block),
+    dao_fork_block: get(config.dao_fork_block).filter(|_| dao_fork_support),
+    eip150_block: get(config.eip150_block),
+    eip155_block: get(config.eip155_block),
+    eip158_block: get(config.eip158_block),
+    byzantium_block: get(config.byzantium_block),
+    constantinople_block: get(config.constantinople_block),
+    petersburg_block: get(config.petersburg_block),
+    istanbul_block: get(config.istanbul_block),
+    muir_glacier_block: get(config.muir_glacier_block),
+    berlin_block: get(config.berlin_block),
+    london_block: get(config.london_block),
+    arrow_glacier_block: get(config.arrow_glacier_block),
+    gray_glacier_block: get(config.gray_glacier_block),
+    merge_netsplit_block: get(config.merge_netsplit_block),
+    shanghai_time: get(config.shanghai_time),
+    cancun_time: get(config.cancun_time),
+    prague_time: get(config.prague_time),
+  };
+
+  let alloc = spec
+    .alloc
+    .into_iter()
+    .map(|(Addr(address), account)| {
+      let account = GenesisAccount {
+        balance: account.balance.0,
+        nonce: get(account.nonce).unwrap_or_default(),
+        code: account.code.map(|Bytes(b)| b).unwrap_or_default(),
+        storage: account
+          .storage
+          .unwrap_or_default()
+          .into_iter()
+          .map(|(Hash(k), Hash(v))| (k, v))
+          .collect(),
+      };
+      (address, account)
+    })
+    .collect();
+
+  let genesis = Genesis {
+    parent_hash: spec.parent_hash.map(|Hash(h)| h).unwrap_or_default(),
+    beneficiary: spec.coinbase.map(|Addr(a)| a).unwrap_or_default(),
+    difficulty: spec.difficulty.0,
+    number: get(spec.number).unwrap_or_default(),
+    gas_limit: spec.gas_limit.0,
+    gas_used: spec.gas_used.map_or(U256::zero(), |Quantity(q)| q),
+    timestamp: get(spec.timestamp).unwrap_or_default(),
+    extra_data: spec.extra_data.map(|Bytes(b)| b).unwrap_or_default(),
+    mix_hash: spec.mix_hash.map(|Hash(h)| h).unwrap_or_default(),
+    nonce: H64::from_low_u64_be(get(spec.nonce).unwrap_or_default()),
+    base_fee_per_gas: spec.base_fee_per_gas.map(|Quantity(q)| q),
+    blob_gas_used: get(spec.blob_gas_used),
+    excess_blob_gas: get(spec.excess_blob_gas),
+    alloc,
+  };
+
+  Ok(ChainSpec {
+    name: String::new(),
+    chain_id,
+    network_id: chain_id,
+    engine,
+    forks,
+    terminal_total_difficulty: config.terminal_total_difficulty.map(|Quantity(q)| q),
+    genesis,
+  })
+}
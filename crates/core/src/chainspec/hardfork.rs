@@ -0,0 +1,409 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::ChainSpec;
+use crate::{Keccak, U256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+/// Protocol upgrades of Ethereum, in activation order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Hardfork {
+  Frontier,
+  Homestead,
+  Dao,
+  TangerineWhistle,
+  SpuriousDragon,
+  Byzantium,
+  Constantinople,
+  Petersburg,
+  Istanbul,
+  MuirGlacier,
+  Berlin,
+  London,
+  ArrowGlacier,
+  GrayGlacier,
+  Paris,
+  Shanghai,
+  Cancun,
+  Prague,
+}
+
+/// What activates a fork on a given chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkCondition {
+  /// Active from this block number onwards
+  Block(u64),
+  /// Active from the first block with a timestamp at or after this one
+  Timestamp(u64),
+  /// The merge, activated once the chain reaches the terminal total
+  /// difficulty. `fork_block` is the first block after the transition,
+  /// when the chain spec declares it.
+  TotalDifficulty {
+    total_difficulty: U256,
+    fork_block: Option<u64>,
+  },
+}
+
+/// EIP-2124 fork identifier, exchanged in the eth protocol Status
+/// message so that peers on incompatible chains can disconnect early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForkId {
+  /// CRC32 checksum of the genesis hash and all passed fork activations
+  pub hash: u32,
+  /// Activation point of the next upcoming fork, or 0 if none is known
+  pub next: u64,
+}
+
+impl Encodable for ForkId {
+  fn rlp_append(&self, s: &mut RlpStream) {
+    s.begin_list(2);
+    s.append(&&self.hash.to_be_bytes()[..]);
+    s.append(&self.next);
+  }
+}
+
+impl Decodable for ForkId {
+  fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+    let hash: Vec<u8> = rlp.val_at(0)?;
+    if hash.len() != 4 {
+      return Err(DecoderError::RlpInvalidLength);
+    }
+    Ok(ForkId {
+      hash: u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]),
+      next: rlp.val_at(1)?,
+    })
+  }
+}
+
+/// The forks a chain goes through and the points at which they activate.
+/// Answers which rules apply to a block and what fork id to advertise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkSchedule {
+  genesis_hash: Keccak,
+  forks: Vec<(Hardfork, ForkCondition)>,
+  /// Every distinct activation block and timestamp, including forks that
+  /// have no [Hardfork] of their own (EIP-155 when it is not on the same
+  /// block as EIP-158, the merge netsplit block).
+  fork_blocks: Vec<u64>,
+  fork_timestamps: Vec<u64>,
+}
+
+impl ForkSchedule {
+  pub fn new(spec: &ChainSpec) -> Self {
+    use ForkCondition::{Block, Timestamp};
+    let forks = &spec.forks;
+
+    let paris =
+      spec
+        .terminal_total_difficulty
+        .map(|total_difficulty| ForkCondition::TotalDifficulty {
+          total_difficulty,
+          fork_block: forks.merge_netsplit_block,
+        });
+
+    let schedule = vec![
+      (Hardfork::Frontier, Some(Block(0))),
+      (Hardfork::Homestead, forks.homestead_block.map(Block)),
+      (Hardfork::Dao, forks.dao_fork_block.map(Block)),
+      (Hardfork::TangerineWhistle, forks.eip150_block.map(Block)),
+      (Hardfork::SpuriousDragon, forks.eip158_block.map(Block)),
+      (Hardfork::Byzantium, forks.byzantium_block.map(Block)),
+      (
+        Hardfork::Constantinople,
+        forks.constantinople_block.map(Block),
+      ),
+      (Hardfork::Petersburg, forks.petersburg_block.map(Block)),
+      (Hardfork::Istanbul, forks.istanbul_block.map(Block)),
+      (Hardfork::MuirGlacier, forks.muir_glacier_block.map(Block)),
+      (Hardfork::Berlin, forks.berlin_block.map(Block)),
+      (Hardfork::London, forks.london_block.map(Block)),
+      (Hardfork::ArrowGlacier, forks.arrow_glacier_block.map(Block)),
+      (Hardfork::GrayGlacier, forks.gray_glacier_block.map(Block)),
+      (Hardfork::Paris, paris),
+      (Hardfork::Shanghai, forks.shanghai_time.map(Timestamp)),
+      (Hardfork::Cancun, forks.cancun_time.map(Timestamp)),
+      (Hardfork::Prague, forks.prague_time.map(Timestamp)),
+    ];
+
+    let mut fork_blocks: Vec<u64> = [
+      forks.homestead_block,
+      forks.dao_fork_block,
+      forks.eip150_block,
+      forks.eip155_block,
+      forks.eip158_block,
+      forks.byzantium_block,
+      forks.constantinople_block,
+      forks.petersburg_block,
+      forks.istanbul_block,
+      forks.muir_glacier_block,
+      forks.berlin_block,
+      forks.london_block,
+      forks.arrow_glacier_block,
+      forks.gray_glacier_block,
+      forks.merge_netsplit_block,
+    ]
+    .iter()
+    .flatten()
+    .copied()
+    .filter(|&block| block != 0)
+    .collect();
+    fork_blocks.sort_unstable();
+    fork_blocks.dedup();
+
+    // forks that activate with the genesis block are not counted
+    let genesis_timestamp = spec.genesis.timestamp;
+    let mut fork_timestamps: Vec<u64> = [forks.shanghai_time, forks.cancun_time, forks.prague_time]
+      .iter()
+      .flatten()
+      .copied()
+      .filter(|&timestamp| timestamp > genesis_timestamp)
+      .collect();
+    fork_timestamps.sort_unstable();
+    fork_timestamps.dedup();
+
+    ForkSchedule {
+      genesis_hash: spec.genesis_hash(),
+      forks: schedule
+        .into_iter()
+        .filter_map(|(fork, condition)| condition.map(|c| (fork, c)))
+        .collect(),
+      fork_blocks,
+      fork_timestamps,
+    }
+  }
+
+  /// The activation condition of a fork, `None` if
+  /// the chain is never going to activate it.
+  pub fn activation(&self, fork: Hardfork) -> Option<ForkCondition> {
+    self
+      .forks
+      .iter()
+      .find(|(f, _)| *f == fork)
+      .map(|(_, condition)| *condition)
+  }
+
+  /// Whether the rules of a fork apply to the block with the given
+  /// number and timestamp.
+  ///
+  /// The merge is driven by total difficulty which is not known from
+  /// the block alone. Unless the chain spec declares the block that
+  /// follows the transition, Paris is considered active once any of
+  /// the later forks is.
+  pub fn is_active(&self, fork: Hardfork, number: u64, timestamp: u64) -> bool {
+    match self.activation(fork) {
+      Some(ForkCondition::Block(block)) => block <= number,
+      Some(ForkCondition::Timestamp(time)) => time <= timestamp,
+      Some(ForkCondition::TotalDifficulty {
+        total_difficulty,
+        fork_block,
+      }) => {
+        total_difficulty.is_zero()
+          || matches!(fork_block, Some(block) if block <= number)
+          || self
+            .forks
+            .iter()
+            .filter(|(f, _)| *f > Hardfork::Paris)
+            .any(|(f, _)| self.is_active(*f, number, timestamp))
+      }
+      None => false,
+    }
+  }
+
+  /// The latest fork whose rules apply to the given block.
+  pub fn active_fork(&self, number: u64, timestamp: u64) -> Hardfork {
+    self
+      .forks
+      .iter()
+      .rev()
+      .map(|(fork, _)| *fork)
+      .find(|fork| self.is_active(*fork, number, timestamp))
+      .unwrap_or(Hardfork::Frontier)
+  }
+
+  /// EIP-2124 fork identifier of a node whose head is at the given
+  /// block number and timestamp. Timestamp based forks follow
+  /// EIP-6122 and are checksummed after all block based forks.
+  pub fn fork_id(&self, number: u64, timestamp: u64) -> ForkId {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(self.genesis_hash.as_bytes());
+
+    let blocks = self
+      .fork_blocks
+      .iter()
+      .map(|&block| (block, block <= number));
+    let timestamps = self
+      .fork_timestamps
+      .iter()
+      .map(|&time| (time, time <= timestamp));
+
+    for (activation, passed) in blocks.chain(timestamps) {
+      if !passed {
+        return ForkId {
+          hash: hasher.finalize(),
+          next: activation,
+        };
+      }
+      hasher.update(&activation.to_be_bytes());
+    }
+
+    ForkId {
+      hash: hasher.finalize(),
+      next: 0,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hex_literal::hex;
+
+  fn assert_fork_ids(schedule: &ForkSchedule, cases: &[(u64, u64, u32, u64)]) {
+    for &(number, timestamp, hash, next) in cases {
+      assert_eq!(
+        schedule.fork_id(number, timestamp),
+        ForkId { hash, next },
+        "head at block {} and timestamp {}",
+        number,
+        timestamp
+      );
+    }
+  }
+
+  /// https://eips.ethereum.org/EIPS/eip-2124#test-cases, extended
+  /// with the forks that happened after the EIP was written.
+  #[test]
+  fn mainnet_fork_ids() {
+    let schedule = ChainSpec::mainnet().fork_schedule();
+    assert_fork_ids(
+      &schedule,
+      &[
+        (0, 0, 0xfc64ec04, 1150000),                    // unsynced
+        (1149999, 0, 0xfc64ec04, 1150000),              // last Frontier block
+        (1150000, 0, 0x97c2c34c, 1920000),              // first Homestead block
+        (1919999, 0, 0x97c2c34c, 1920000),              // last Homestead block
+        (1920000, 0, 0x91d1f948, 2463000),              // first DAO block
+        (2462999, 0, 0x91d1f948, 2463000),              // last DAO block
+        (2463000, 0, 0x7a64da13, 2675000),              // first Tangerine block
+        (2674999, 0, 0x7a64da13, 2675000),              // last Tangerine block
+        (2675000, 0, 0x3edd5b10, 4370000),              // first Spurious block
+        (4369999, 0, 0x3edd5b10, 4370000),              // last Spurious block
+        (4370000, 0, 0xa00bc324, 7280000),              // first Byzantium block
+        (7279999, 0, 0xa00bc324, 7280000),              // last Byzantium block
+        (7280000, 0, 0x668db0af, 9069000),              // first Petersburg block
+        (9068999, 0, 0x668db0af, 9069000),              // last Petersburg block
+        (9069000, 0, 0x879d6e30, 9200000),              // first Istanbul block
+        (9199999, 0, 0x879d6e30, 9200000),              // last Istanbul block
+        (9200000, 0, 0xe029e991, 12244000),             // first Muir Glacier block
+        (12243999, 0, 0xe029e991, 12244000),            // last Muir Glacier block
+        (12244000, 0, 0x0eb440f6, 12965000),            // first Berlin block
+        (12964999, 0, 0x0eb440f6, 12965000),            // last Berlin block
+        (12965000, 0, 0xb715077d, 13773000),            // first London block
+        (13772999, 0, 0xb715077d, 13773000),            // last London block
+        (13773000, 0, 0x20c327fc, 15050000),            // first Arrow Glacier block
+        (15049999, 0, 0x20c327fc, 15050000),            // last Arrow Glacier block
+        (15050000, 0, 0xf0afd0e3, 1681338455),          // first Gray Glacier block
+        (20000000, 1681338454, 0xf0afd0e3, 1681338455), // last Gray Glacier block
+        (20000000, 1681338455, 0xdce96c2d, 1710338135), // first Shanghai block
+        (30000000, 1710338134, 0xdce96c2d, 1710338135), // last Shanghai block
+        (40000000, 1710338135, 0x9f3d2254, 1746612311), // first Cancun block
+        (50000000, 1746612310, 0x9f3d2254, 1746612311), // last Cancun block
+        (50000000, 1746612311, 0xc376cf8b, 0),          // first Prague block
+        (60000000, 2000000000, 0xc376cf8b, 0),          // future Prague block
+      ],
+    );
+  }
+
+  #[test]
+  fn testnet_fork_ids() {
+    let sepolia = ChainSpec::sepolia().fork_schedule();
+    assert_fork_ids(
+      &sepolia,
+      &[
+        (0, 0, 0xfe3366e7, 1735371),
+        (1735370, 0, 0xfe3366e7, 1735371),
+        (1735371, 0, 0xb96cbd13, 1677557088),
+        (1735372, 1677557087, 0xb96cbd13, 1677557088),
+        (1735372, 1677557088, 0xf7f9bc08, 1706655072),
+        (1735372, 1706655072, 0x88cf81d9, 1741159776),
+        (1735372, 1741159776, 0xed88b5fd, 0),
+      ],
+    );
+
+    let holesky = ChainSpec::holesky().fork_schedule();
+    assert_fork_ids(
+      &holesky,
+      &[
+        (0, 0, 0xc61a6098, 1696000704),
+        (123, 1696000703, 0xc61a6098, 1696000704),
+        (123, 1696000704, 0xfd4f016b, 1707305664),
+        (123, 1707305664, 0x9b192ad0, 1740434112),
+        (123, 1740434112, 0xdfbd9bed, 0),
+      ],
+    );
+  }
+
+  #[test]
+  fn fork_id_rlp() {
+    let cases = [
+      (ForkId { hash: 0, next: 0 }, &hex!("c6840000000080")[..]),
+      (
+        ForkId {
+          hash: 0xdeadbeef,
+          next: 0xbaddcafe,
+        },
+        &hex!("ca84deadbeef84baddcafe")[..],
+      ),
+      (
+        ForkId {
+          hash: u32::MAX,
+          next: u64::MAX,
+        },
+        &hex!("ce84ffffffff88ffffffffffffffff")[..],
+      ),
+    ];
+
+    for (id, encoded) in cases.iter() {
+      assert_eq!(&rlp::encode(id)[..], *encoded);
+      assert_eq!(rlp::decode::<ForkId>(encoded).unwrap(), *id);
+    }
+
+    assert!(rlp::decode::<ForkId>(&hex!("c58300000080")).is_err());
+  }
+
+  #[test]
+  fn active_rules() {
+    let mainnet = ChainSpec::mainnet().fork_schedule();
+    assert_eq!(mainnet.active_fork(0, 0), Hardfork::Frontier);
+    assert_eq!(mainnet.active_fork(1920000, 0), Hardfork::Dao);
+    assert_eq!(mainnet.active_fork(7280000, 0), Hardfork::Petersburg);
+    assert_eq!(
+      mainnet.active_fork(15050000, 1655000000),
+      Hardfork::GrayGlacier
+    );
+    assert_eq!(
+      mainnet.active_fork(17034870, 1681338455),
+      Hardfork::Shanghai
+    );
+    assert_eq!(mainnet.active_fork(19426587, 1710338135), Hardfork::Cancun);
+
+    assert!(mainnet.is_active(Hardfork::London, 12965000, 0));
+    assert!(!mainnet.is_active(Hardfork::London, 12964999, 0));
+    assert!(mainnet.is_active(Hardfork::Paris, 17034870, 1681338455));
+    assert!(!mainnet.is_active(Hardfork::Cancun, 19426586, 1710338123));
+    assert_eq!(
+      mainnet.activation(Hardfork::Shanghai),
+      Some(ForkCondition::Timestamp(1681338455))
+    );
+
+    // sepolia declares the merge netsplit block, and has no DAO fork
+    let sepolia = ChainSpec::sepolia().fork_schedule();
+    assert!(!sepolia.is_active(Hardfork::Paris, 1735370, 0));
+    assert!(sepolia.is_active(Hardfork::Paris, 1735371, 0));
+    assert_eq!(sepolia.activation(Hardfork::Dao), None);
+
+    // holesky launched after the merge
+    let holesky = ChainSpec::holesky().fork_schedule();
+    assert_eq!(holesky.active_fork(0, 0), Hardfork::Paris);
+  }
+}
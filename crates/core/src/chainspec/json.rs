@@ -0,0 +1,124 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Scalar types shared by the geth and OpenEthereum chain spec formats.
+//! Both formats are liberal in how they spell numbers and hashes, so
+//! these wrappers accept every variant found in the wild and normalize
+//! it to the corresponding ethereum type.
+
+use crate::{Address, H256, U256};
+use serde::{de::Error, Deserialize, Deserializer};
+use serde_json::Value;
+
+fn strip_hex_prefix(s: &str) -> Option<&str> {
+  s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))
+}
+
+/// A number given either as a JSON number, a decimal
+/// string or a 0x-prefixed hexadecimal string.
+pub(super) struct Quantity(pub U256);
+
+impl<'de> Deserialize<'de> for Quantity {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let text = match Value::deserialize(deserializer)? {
+      Value::Number(n) => n.to_string(),
+      Value::String(s) => s,
+      other => {
+        return Err(D::Error::custom(format!(
+          "expected a quantity, found {}",
+          other
+        )))
+      }
+    };
+
+    let parsed = match strip_hex_prefix(&text) {
+      Some("") => Ok(U256::zero()),
+      Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| format!("{:?}", e)),
+      None => U256::from_dec_str(&text).map_err(|e| format!("{:?}", e)),
+    };
+
+    parsed
+      .map(Quantity)
+      .map_err(|e| D::Error::custom(format!("invalid quantity {}: {}", text, e)))
+  }
+}
+
+/// A [Quantity] that must fit in 64 bits.
+pub(super) struct Uint(pub u64);
+
+impl<'de> Deserialize<'de> for Uint {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let Quantity(value) = Quantity::deserialize(deserializer)?;
+    if value > U256::from(u64::MAX) {
+      return Err(D::Error::custom(format!(
+        "{} does not fit in 64 bits",
+        value
+      )));
+    }
+    Ok(Uint(value.as_u64()))
+  }
+}
+
+/// Hex encoded byte string, the 0x prefix is optional.
+pub(super) struct Bytes(pub Vec<u8>);
+
+impl<'de> Deserialize<'de> for Bytes {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let text = String::deserialize(deserializer)?;
+    let hex = strip_hex_prefix(&text).unwrap_or(&text);
+    hex::decode(hex).map(Bytes).map_err(D::Error::custom)
+  }
+}
+
+/// A 32 byte word. Shorter values (common in storage entries of
+/// genesis allocations) are left-padded with zeros, so are odd
+/// numbers of hex digits.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct Hash(pub H256);
+
+impl<'de> Deserialize<'de> for Hash {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let text = String::deserialize(deserializer)?;
+    let hex = strip_hex_prefix(&text).unwrap_or(&text);
+    let bytes = match hex.len() % 2 {
+      0 => hex::decode(hex),
+      _ => hex::decode(format!("0{}", hex)),
+    }
+    .map_err(D::Error::custom)?;
+    if bytes.len() > 32 {
+      return Err(D::Error::invalid_length(bytes.len(), &"at most 32 bytes"));
+    }
+    let mut word = H256::zero();
+    word.as_bytes_mut()[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(Hash(word))
+  }
+}
+
+/// An account address, exactly 20 bytes.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct Addr(pub Address);
+
+impl<'de> Deserialize<'de> for Addr {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let Bytes(bytes) = Bytes::deserialize(deserializer)?;
+    if bytes.len() != 20 {
+      return Err(D::Error::invalid_length(bytes.len(), &"20 bytes"));
+    }
+    Ok(Addr(Address::from_slice(&bytes)))
+  }
+}
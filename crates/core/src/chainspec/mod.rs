@@ -0,0 +1,451 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Chain specifications: the parameters that distinguish one network
+//! from another. Specs can be loaded from geth-style genesis.json files
+//! or from OpenEthereum chain spec files.
+
+mod genesis;
+mod geth;
+mod hardfork;
+mod json;
+mod openethereum;
+
+use crate::{Address, BlockHeader, Keccak, U256};
+use keccak_hash::{KECCAK_EMPTY_LIST_RLP, KECCAK_NULL_RLP};
+
+pub use genesis::{Genesis, GenesisAccount};
+pub use hardfork::{ForkCondition, ForkId, ForkSchedule, Hardfork};
+
+/// Initial base fee of chains that activate London at genesis,
+/// https://eips.ethereum.org/EIPS/eip-1559
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+#[derive(Debug)]
+pub enum ChainSpecError {
+  /// The input is not valid JSON or a field has an unexpected shape or value
+  Json(serde_json::Error),
+  /// The input is neither a geth genesis file nor an OpenEthereum chain spec
+  UnknownFormat,
+  /// The spec uses a feature we can not represent, for example OpenEthereum
+  /// genesis accounts that are initialized by running constructor code.
+  Unsupported(&'static str),
+}
+
+impl std::fmt::Display for ChainSpecError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self, f)
+  }
+}
+
+impl std::error::Error for ChainSpecError {}
+
+impl From<serde_json::Error> for ChainSpecError {
+  fn from(e: serde_json::Error) -> Self {
+    ChainSpecError::Json(e)
+  }
+}
+
+/// Activation points of the protocol upgrades. Forks up to the merge
+/// are activated at a block number, later forks at a block timestamp.
+/// `None` means the fork is never activated on this chain.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Forks {
+  pub homestead_block: Option<u64>,
+  pub dao_fork_block: Option<u64>,
+  pub eip150_block: Option<u64>,
+  pub eip155_block: Option<u64>,
+  pub eip158_block: Option<u64>,
+  pub byzantium_block: Option<u64>,
+  pub constantinople_block: Option<u64>,
+  pub petersburg_block: Option<u64>,
+  pub istanbul_block: Option<u64>,
+  pub muir_glacier_block: Option<u64>,
+  pub berlin_block: Option<u64>,
+  pub london_block: Option<u64>,
+  pub arrow_glacier_block: Option<u64>,
+  pub gray_glacier_block: Option<u64>,
+  pub merge_netsplit_block: Option<u64>,
+
+  pub shanghai_time: Option<u64>,
+  pub cancun_time: Option<u64>,
+  pub prague_time: Option<u64>,
+}
+
+/// The consensus engine sealing blocks before the merge
+/// (or for the whole lifetime of chains that never merge).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Engine {
+  /// Proof of work
+  Ethash,
+  /// Proof of authority, https://eips.ethereum.org/EIPS/eip-225
+  Clique { period: u64, epoch: u64 },
+  /// Proof of authority with round-robin step based sealing
+  AuthorityRound {
+    step_duration: u64,
+    validators: ValidatorSet,
+  },
+  /// Seals a block as soon as a transaction arrives, for development chains
+  InstantSeal,
+}
+
+/// Source of the validators of an AuthorityRound chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidatorSet {
+  /// Fixed list of validators
+  List(Vec<Address>),
+  /// Validators are read from a contract that does not report misbehaviour
+  SafeContract(Address),
+  /// Validators are read from a contract that reports misbehaviour
+  Contract(Address),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainSpec {
+  /// Human readable name of the chain. Geth genesis files
+  /// don't carry a name so it is empty for specs loaded from them.
+  pub name: String,
+  /// EIP-155 chain id used for transaction replay protection
+  pub chain_id: u64,
+  /// Network id advertised in the devp2p handshake
+  pub network_id: u64,
+  pub engine: Engine,
+  pub forks: Forks,
+  /// Total difficulty at which the chain switches to proof of stake
+  pub terminal_total_difficulty: Option<U256>,
+  pub genesis: Genesis,
+}
+
+impl ChainSpec {
+  /// Parses either a geth genesis file or an OpenEthereum chain spec,
+  /// depending on the shape of the document.
+  pub fn from_json(json: &str) -> Result<Self, ChainSpecError> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    if value.get("config").is_some() && value.get("alloc").is_some() {
+      geth::parse(json)
+    } else if value.get("engine").is_some() && value.get("params").is_some() {
+      openethereum::parse(json)
+    } else {
+      Err(ChainSpecError::UnknownFormat)
+    }
+  }
+
+  /// Parses a geth-style genesis.json file.
+  pub fn from_geth_json(json: &str) -> Result<Self, ChainSpecError> {
+    geth::parse(json)
+  }
+
+  /// Parses an OpenEthereum chain spec file.
+  pub fn from_openethereum_json(json: &str) -> Result<Self, ChainSpecError> {
+    openethereum::parse(json)
+  }
+
+  /// Ethereum mainnet
+  pub fn mainnet() -> Self {
+    Self::builtin("mainnet", include_str!("../../res/chainspec/mainnet.json"))
+  }
+
+  /// Sepolia proof of stake testnet
+  pub fn sepolia() -> Self {
+    Self::builtin("sepolia", include_str!("../../res/chainspec/sepolia.json"))
+  }
+
+  /// Holesky proof of stake testnet
+  pub fn holesky() -> Self {
+    Self::builtin("holesky", include_str!("../../res/chainspec/holesky.json"))
+  }
+
+  /// Single node development chain with all forks up to Cancun
+  /// active from genesis and one prefunded account.
+  pub fn dev() -> Self {
+    Self::from_openethereum_json(include_str!("../../res/chainspec/dev.json"))
+      .expect("built-in dev chain spec is valid")
+  }
+
+  fn builtin(name: &str, json: &str) -> Self {
+    let mut spec = Self::from_geth_json(json).expect("built-in chain specs are valid");
+    spec.name = name.to_owned();
+    spec
+  }
+
+  /// Constructs the header of the genesis block. Fields introduced by
+  /// forks that are active at genesis get their initial values.
+  ///
+  /// Prague (EIP-7685 requests hash) at genesis is not supported yet,
+  /// and neither are AuthorityRound seal fields.
+  pub fn genesis_header(&self) -> BlockHeader {
+    let genesis = &self.genesis;
+    let at_block = |fork: Option<u64>| matches!(fork, Some(b) if b <= genesis.number);
+    let at_time = |fork: Option<u64>| matches!(fork, Some(t) if t <= genesis.timestamp);
+    let cancun = at_time(self.forks.cancun_time);
+
+    BlockHeader {
+      parent_hash: genesis.parent_hash,
+      ommers_hash: KECCAK_EMPTY_LIST_RLP,
+      beneficiary: genesis.beneficiary,
+      state_root: genesis.state_root(),
+      transactions_root: KECCAK_NULL_RLP,
+      receipts_root: KECCAK_NULL_RLP,
+      logs_bloom: Default::default(),
+      difficulty: genesis.difficulty,
+      number: genesis.number,
+      gas_limit: genesis.gas_limit,
+      gas_used: genesis.gas_used,
+      timestamp: genesis.timestamp,
+      extra_data: genesis.extra_data.clone(),
+      mix_hash: genesis.mix_hash,
+      nonce: genesis.nonce,
+      base_fee_per_gas: at_block(self.forks.london_block).then(|| {
+        genesis
+          .base_fee_per_gas
+          .unwrap_or_else(|| INITIAL_BASE_FEE.into())
+      }),
+      withdrawals_root: at_time(self.forks.shanghai_time).then_some(KECCAK_NULL_RLP),
+      blob_gas_used: cancun.then(|| genesis.blob_gas_used.unwrap_or_default()),
+      excess_blob_gas: cancun.then(|| genesis.excess_blob_gas.unwrap_or_default()),
+      parent_beacon_block_root: cancun.then(Keccak::zero),
+    }
+  }
+
+  /// Hash of the genesis block header
+  pub fn genesis_hash(&self) -> Keccak {
+    self.genesis_header().hash()
+  }
+
+  /// Activation schedule of the forks of this chain
+  pub fn fork_schedule(&self) -> ForkSchedule {
+    ForkSchedule::new(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{H256, H64};
+  use hex_literal::hex;
+
+  #[test]
+  fn mainnet_genesis() {
+    let spec = ChainSpec::mainnet();
+    assert_eq!(spec.chain_id, 1);
+    assert_eq!(spec.engine, Engine::Ethash);
+    assert_eq!(spec.forks.dao_fork_block, Some(1_920_000));
+    assert_eq!(spec.forks.cancun_time, Some(1_710_338_135));
+    assert_eq!(
+      spec.terminal_total_difficulty,
+      Some(U256::from_dec_str("58750000000000000000000").unwrap())
+    );
+    assert_eq!(spec.genesis.alloc.len(), 8893);
+    assert_eq!(spec.genesis.nonce, H64::from_low_u64_be(0x42));
+
+    let header = spec.genesis_header();
+    assert_eq!(
+      header.state_root,
+      hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544").into()
+    );
+    assert_eq!(header.base_fee_per_gas, None);
+    assert_eq!(
+      spec.genesis_hash(),
+      hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3").into()
+    );
+
+    // the genesis header agrees with the one stored on chain
+    let block: crate::Block = crate::rlp_deserialize(
+      &hex::decode(include_str!("../../res/mainnet/block_0.hex").trim()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(block.header, header);
+  }
+
+  #[test]
+  fn sepolia_genesis() {
+    let spec = ChainSpec::sepolia();
+    assert_eq!(spec.chain_id, 11_155_111);
+    assert_eq!(spec.forks.merge_netsplit_block, Some(1_735_371));
+    assert_eq!(spec.forks.dao_fork_block, None);
+    assert_eq!(
+      spec.genesis_header().base_fee_per_gas,
+      Some(INITIAL_BASE_FEE.into())
+    );
+    assert_eq!(
+      spec.genesis_hash(),
+      hex!("25a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9").into()
+    );
+  }
+
+  #[test]
+  fn holesky_genesis() {
+    let spec = ChainSpec::holesky();
+    assert_eq!(spec.chain_id, 17_000);
+    assert_eq!(spec.terminal_total_difficulty, Some(U256::zero()));
+
+    // holesky allocates contracts with code and storage at genesis
+    let deposit_contract =
+      spec.genesis.alloc[&hex!("4242424242424242424242424242424242424242").into()].clone();
+    assert!(!deposit_contract.code.is_empty());
+    assert!(!deposit_contract.storage.is_empty());
+
+    let header = spec.genesis_header();
+    assert_eq!(
+      header.state_root,
+      hex!("69d8c9d72f6fa4ad42d4702b433707212f90db395eb54dc20bc85de253788783").into()
+    );
+    assert_eq!(header.withdrawals_root, None);
+    assert_eq!(
+      spec.genesis_hash(),
+      hex!("b5f7f912443c940f21fd611f12828d75b534364ed9e95ca4e307729a4661bde4").into()
+    );
+
+    let onchain: BlockHeader = crate::rlp_deserialize(
+      &hex::decode(include_str!("../../res/holesky/header_0.hex").trim()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(header, onchain);
+  }
+
+  #[test]
+  fn dev_genesis() {
+    let spec = ChainSpec::dev();
+    assert_eq!(spec.name, "DevelopmentChain");
+    assert_eq!(spec.engine, Engine::InstantSeal);
+    assert_eq!(spec.chain_id, 0x11);
+    assert_eq!(spec.forks.petersburg_block, Some(0));
+    assert_eq!(spec.forks.muir_glacier_block, None);
+
+    // builtins only declaring pricing are not part of the state
+    assert_eq!(spec.genesis.alloc.len(), 5);
+
+    let header = spec.genesis_header();
+    assert_eq!(header.withdrawals_root, Some(KECCAK_NULL_RLP));
+    assert_eq!(header.blob_gas_used, Some(0));
+    assert_eq!(header.excess_blob_gas, Some(0));
+    assert_eq!(header.parent_beacon_block_root, Some(H256::zero()));
+
+    // no other client ships this exact spec, the expected hash was
+    // computed with an independent trie and header implementation
+    assert_eq!(
+      spec.genesis_hash(),
+      hex!("6e70c0a3d669838770a758c8199aef707f58cd09c5e222cd96507e254a7355b1").into()
+    );
+  }
+
+  #[test]
+  fn geth_and_openethereum_formats_agree() -> Result<(), ChainSpecError> {
+    let geth = ChainSpec::from_json(
+      r#"{
+        "config": {
+          "chainId": 1337,
+          "homesteadBlock": 0,
+          "eip150Block": 0,
+          "eip155Block": 0,
+          "eip158Block": 0,
+          "byzantiumBlock": 0,
+          "constantinopleBlock": 0,
+          "petersburgBlock": 0,
+          "istanbulBlock": 0,
+          "berlinBlock": 0,
+          "londonBlock": 5,
+          "clique": { "period": 5, "epoch": 30000 }
+        },
+        "difficulty": "1",
+        "gasLimit": "8000000",
+        "extraData": "0x00",
+        "alloc": {
+          "0x00a329c0648769a73afac7f9381e08fb43dbea72": {
+            "balance": "1000000000000000000",
+            "nonce": "0x1",
+            "code": "0x6000",
+            "storage": { "0x01": "0x2a", "0x02": "0x00" }
+          }
+        }
+      }"#,
+    )?;
+
+    let openethereum = ChainSpec::from_json(
+      r#"{
+        "name": "",
+        "engine": { "clique": { "params": { "period": 5, "epoch": 30000 } } },
+        "params": {
+          "networkID": "1337",
+          "eip150Transition": 0,
+          "eip155Transition": 0,
+          "eip161abcTransition": 0,
+          "eip140Transition": 0,
+          "eip145Transition": 0,
+          "eip1344Transition": 0,
+          "eip2929Transition": 0,
+          "eip1559Transition": 5
+        },
+        "genesis": {
+          "difficulty": "0x1",
+          "gasLimit": "0x7a1200",
+          "extraData": "00"
+        },
+        "accounts": {
+          "00a329c0648769a73afac7f9381e08fb43dbea72": {
+            "balance": "0xde0b6b3a7640000",
+            "nonce": 1,
+            "code": "6000",
+            "storage": {
+              "0x0000000000000000000000000000000000000000000000000000000000000001": "0x2a",
+              "0x0000000000000000000000000000000000000000000000000000000000000002": "0x0"
+            }
+          },
+          "0x0000000000000000000000000000000000000001": { "builtin": { "name": "ecrecover" } }
+        }
+      }"#,
+    )?;
+
+    assert_eq!(geth, openethereum);
+    assert_eq!(
+      geth.engine,
+      Engine::Clique {
+        period: 5,
+        epoch: 30000
+      }
+    );
+    assert_eq!(geth.genesis_header().base_fee_per_gas, None);
+    assert_eq!(geth.genesis_hash(), openethereum.genesis_hash());
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_specs() {
+    assert!(matches!(
+      ChainSpec::from_json(r#"{ "alloc": {} }"#),
+      Err(ChainSpecError::UnknownFormat)
+    ));
+    assert!(matches!(
+      ChainSpec::from_json("[1, 2"),
+      Err(ChainSpecError::Json(_))
+    ));
+
+    // chain id overflowing 64 bits
+    assert!(matches!(
+      ChainSpec::from_geth_json(
+        r#"{ "config": { "chainId": 18446744073709551616 }, "difficulty": "0x1", "gasLimit": "0x1", "alloc": {} }"#
+      ),
+      Err(ChainSpecError::Json(_))
+    ));
+
+    // addresses must be exactly 20 bytes
+    assert!(matches!(
+      ChainSpec::from_geth_json(
+        r#"{ "config": { "chainId": 1 }, "difficulty": "0x1", "gasLimit": "0x1", "alloc": { "0x01": { "balance": "1" } } }"#
+      ),
+      Err(ChainSpecError::Json(_))
+    ));
+
+    let with_constructor = r#"{
+      "name": "test",
+      "engine": { "instantSeal": null },
+      "params": { "networkID": "0x1" },
+      "genesis": { "difficulty": "0x1", "gasLimit": "0x1" },
+      "accounts": { "0x0000000000000000000000000000000000000001": { "balance": "1", "constructor": "0x00" } }
+    }"#;
+    assert!(matches!(
+      ChainSpec::from_openethereum_json(with_constructor),
+      Err(ChainSpecError::Unsupported(_))
+    ));
+  }
+}
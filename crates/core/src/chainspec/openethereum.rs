@@ -0,0 +1,254 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! OpenEthereum chain spec format. Instead of named forks it lists
+//! the activation point of every individual EIP, the named forks are
+//! derived from the EIP that first shipped with each of them.
+//! Forks that only delay the difficulty bomb (Muir, Arrow and Gray
+//! Glacier) can't be told apart in this format and are left unset.
+
+use super::json::{Addr, Bytes, Hash, Quantity, Uint};
+use super::{ChainSpec, ChainSpecError, Engine, Forks, Genesis, GenesisAccount, ValidatorSet};
+use crate::{H64, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Spec {
+  name: String,
+  engine: EngineSpec,
+  params: Params,
+  genesis: GenesisSpec,
+  #[serde(default)]
+  accounts: BTreeMap<Addr, Account>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum EngineSpec {
+  #[serde(alias = "Ethash")]
+  Ethash {
+    params: EthashParams,
+  },
+  Clique {
+    params: CliqueParams,
+  },
+  AuthorityRound {
+    params: AuthorityRoundParams,
+  },
+  InstantSeal(#[allow(dead_code)] serde::de::IgnoredAny),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthashParams {
+  homestead_transition: Option<Uint>,
+  dao_hardfork_transition: Option<Uint>,
+}
+
+#[derive(Deserialize)]
+struct CliqueParams {
+  period: Uint,
+  epoch: Uint,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorityRoundParams {
+  step_duration: Uint,
+  validators: Validators,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Validators {
+  List(Vec<Addr>),
+  SafeContract(Addr),
+  Contract(Addr),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Params {
+  #[serde(rename = "networkID")]
+  network_id: Uint,
+  #[serde(rename = "chainID")]
+  chain_id: Option<Uint>,
+  eip150_transition: Option<Uint>,
+  eip155_transition: Option<Uint>,
+  eip161abc_transition: Option<Uint>,
+  eip140_transition: Option<Uint>,
+  eip145_transition: Option<Uint>,
+  eip1283_transition: Option<Uint>,
+  eip1283_disable_transition: Option<Uint>,
+  eip1344_transition: Option<Uint>,
+  eip2929_transition: Option<Uint>,
+  eip1559_transition: Option<Uint>,
+  eip3855_transition_timestamp: Option<Uint>,
+  eip4844_transition_timestamp: Option<Uint>,
+  eip7702_transition_timestamp: Option<Uint>,
+  terminal_total_difficulty: Option<Quantity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenesisSpec {
+  seal: Option<Seal>,
+  difficulty: Quantity,
+  author: Option<Addr>,
+  timestamp: Option<Uint>,
+  parent_hash: Option<Hash>,
+  extra_data: Option<Bytes>,
+  gas_limit: Quantity,
+  gas_used: Option<Quantity>,
+  number: Option<Uint>,
+  base_fee_per_gas: Option<Quantity>,
+}
+
+/// Only the proof of work seal maps onto header fields,
+/// other seals are ignored.
+#[derive(Deserialize)]
+struct Seal {
+  ethereum: Option<EthereumSeal>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthereumSeal {
+  nonce: Uint,
+  mix_hash: Hash,
+}
+
+#[derive(Deserialize)]
+struct Account {
+  balance: Option<Quantity>,
+  nonce: Option<Uint>,
+  code: Option<Bytes>,
+  storage: Option<BTreeMap<Hash, Hash>>,
+  constructor: Option<Bytes>,
+}
+
+impl Account {
+  /// Accounts that only declare a builtin are not part of the state.
+  fn is_empty(&self) -> bool {
+    self.balance.is_none() && self.nonce.is_none() && self.code.is_none() && self.storage.is_none()
+  }
+}
+
+fn get(value: Option<Uint>) -> Option<u64> {
+  value.map(|Uint(v)| v)
+}
+
+pub(super) fn parse(json: &str) -> Result<ChainSpec, ChainSpecError> {
+  let spec: Spec = serde_json::from_str(json)?;
+  let params = spec.params;
+
+  let (engine, homestead_block, dao_fork_block) = match spec.engine {
+    EngineSpec::Ethash { params } => (
+      Engine::Ethash,
+      get(params.homestead_transition),
+      get(params.dao_hardfork_transition),
+    ),
+    EngineSpec::Clique { params } => (
+      Engine::Clique {
+        period: params.period.0,
+        epoch: params.epoch.0,
+      },
+      Some(0),
+      None,
+    ),
+    EngineSpec::AuthorityRound { params } => (
+      Engine::AuthorityRound {
+        step_duration: params.step_duration.0,
+        validators: match params.validators {
+          Validators::List(list) => ValidatorSet::List(list.into_iter().map(|Addr(a)| a).collect()),
+          Validators::SafeContract(Addr(a)) => ValidatorSet::SafeContract(a),
+          Validators::Contract(Addr(a)) => ValidatorSet::Contract(a),
+        },
+      },
+      Some(0),
+      None,
+    ),
+    EngineSpec::InstantSeal(_) => (Engine::InstantSeal, Some(0), None),
+  };
+
+  let constantinople_block = get(params.eip145_transition);
+  let forks = Forks {
+    homestead_block,
+    dao_fork_block,
+    eip150_block: get(params.eip150_transition),
+    eip155_block: get(params.eip155_transition),
+    eip158_block: get(params.eip161abc_transition),
+    byzantium_block: get(params.eip140_transition),
+    constantinople_block,
+    // Petersburg is Constantinople without EIP-1283. If 1283 was
+    // never enabled both forks activate at the same block.
+    petersburg_block: match params.eip1283_transition {
+      Some(_) => get(params.eip1283_disable_transition),
+      None => constantinople_block,
+    },
+    istanbul_block: get(params.eip1344_transition),
+    muir_glacier_block: None,
+    berlin_block: get(params.eip2929_transition),
+    london_block: get(params.eip1559_transition),
+    arrow_glacier_block: None,
+    gray_glacier_block: None,
+    merge_netsplit_block: None,
+    shanghai_time: get(params.eip3855_transition_timestamp),
+    cancun_time: get(params.eip4844_transition_timestamp),
+    prague_time: get(params.eip7702_transition_timestamp),
+  };
+
+  let mut alloc = BTreeMap::new();
+  for (Addr(address), account) in spec.accounts {
+    if account.constructor.is_some() {
+      return Err(ChainSpecError::Unsupported("genesis account constructors"));
+    }
+    if account.is_empty() {
+      continue;
+    }
+    let account = GenesisAccount {
+      balance: account.balance.map_or(U256::zero(), |Quantity(q)| q),
+      nonce: get(account.nonce).unwrap_or_default(),
+      code: account.code.map(|Bytes(b)| b).unwrap_or_default(),
+      storage: account
+        .storage
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(Hash(k), Hash(v))| (k, v))
+        .collect(),
+    };
+    alloc.insert(address, account);
+  }
+
+  let genesis = spec.genesis;
+  let seal = genesis.seal.and_then(|s| s.ethereum);
+  let genesis = Genesis {
+    parent_hash: genesis.parent_hash.map(|Hash(h)| h).unwrap_or_default(),
+    beneficiary: genesis.author.map(|Addr(a)| a).unwrap_or_default(),
+    difficulty: genesis.difficulty.0,
+    number: get(genesis.number).unwrap_or_default(),
+    gas_limit: genesis.gas_limit.0,
+    gas_used: genesis.gas_used.map_or(U256::zero(), |Quantity(q)| q),
+    timestamp: get(genesis.timestamp).unwrap_or_default(),
+    extra_data: genesis.extra_data.map(|Bytes(b)| b).unwrap_or_default(),
+    mix_hash: seal.as_ref().map(|s| s.mix_hash.0).unwrap_or_default(),
+    nonce: H64::from_low_u64_be(seal.map(|s| s.nonce.0).unwrap_or_default()),
+    base_fee_per_gas: genesis.base_fee_per_gas.map(|Quantity(q)| q),
+    blob_gas_used: None,
+    excess_blob_gas: None,
+    alloc,
+  };
+
+  let network_id = params.network_id.0;
+  Ok(ChainSpec {
+    name: spec.name,
+    chain_id: get(params.chain_id).unwrap_or(network_id),
+    network_id,
+    engine,
+    forks,
+    terminal_total_difficulty: params.terminal_total_difficulty.map(|Quantity(q)| q),
+    genesis,
+  })
+}
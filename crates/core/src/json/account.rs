@@ -0,0 +1,42 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::json_repr;
+use crate::{Account, Keccak, U256};
+use serde::{Deserialize, Serialize};
+
+/// The account members of an `eth_getProof` response
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountJson {
+  nonce: U256,
+  balance: U256,
+  storage_hash: Keccak,
+  code_hash: Keccak,
+}
+
+impl From<&Account> for AccountJson {
+  fn from(account: &Account) -> Self {
+    AccountJson {
+      nonce: account.nonce,
+      balance: account.balance,
+      storage_hash: account.storage_root,
+      code_hash: account.code_hash,
+    }
+  }
+}
+
+impl std::convert::TryFrom<AccountJson> for Account {
+  type Error = String;
+
+  fn try_from(json: AccountJson) -> Result<Self, Self::Error> {
+    Ok(Account {
+      nonce: json.nonce,
+      balance: json.balance,
+      storage_root: json.storage_hash,
+      code_hash: json.code_hash,
+    })
+  }
+}
+
+json_repr!(Account, AccountJson);
@@ -0,0 +1,472 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::{
+  data, json_repr, optional_quantity, quantity,
+  transaction::{Inclusion, TransactionJson},
+  Json,
+};
+use crate::{Address, Block, BlockHeader, Bloom, Keccak, Transaction, Withdrawal, H64, U256};
+use keccak_hash::keccak;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::convert::TryInto;
+
+/// The header members of an `eth_getBlockByHash` response
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeaderJson {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  hash: Option<Keccak>,
+  parent_hash: Keccak,
+  sha3_uncles: Keccak,
+  miner: Address,
+  state_root: Keccak,
+  transactions_root: Keccak,
+  receipts_root: Keccak,
+  logs_bloom: Bloom,
+  difficulty: U256,
+  #[serde(with = "quantity")]
+  number: u64,
+  gas_limit: U256,
+  gas_used: U256,
+  #[serde(with = "quantity")]
+  timestamp: u64,
+  #[serde(with = "data")]
+  extra_data: Vec<u8>,
+  mix_hash: Keccak,
+  nonce: H64,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  base_fee_per_gas: Option<U256>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  withdrawals_root: Option<Keccak>,
+  #[serde(
+    default,
+    with = "optional_quantity",
+    skip_serializing_if = "Option::is_none"
+  )]
+  blob_gas_used: Option<u64>,
+  #[serde(
+    default,
+    with = "optional_quantity",
+    skip_serializing_if = "Option::is_none"
+  )]
+  excess_blob_gas: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  parent_beacon_block_root: Option<Keccak>,
+}
+
+impl From<&BlockHeader> for HeaderJson {
+  fn from(header: &BlockHeader) -> Self {
+    HeaderJson {
+      hash: Some(header.hash()),
+      parent_hash: header.parent_hash,
+      sha3_uncles: header.ommers_hash,
+      miner: header.beneficiary,
+      state_root: header.state_root,
+      transactions_root: header.transactions_root,
+      receipts_root: header.receipts_root,
+      logs_bloom: header.logs_bloom,
+      difficulty: header.difficulty,
+      number: header.number,
+      gas_limit: header.gas_limit,
+      gas_used: header.gas_used,
+      timestamp: header.timestamp,
+      extra_data: header.extra_data.clone(),
+      mix_hash: header.mix_hash,
+      nonce: header.nonce,
+      base_fee_per_gas: header.base_fee_per_gas,
+      withdrawals_root: header.withdrawals_root,
+      blob_gas_used: header.blob_gas_used,
+      excess_blob_gas: header.excess_blob_gas,
+      parent_beacon_block_root: header.parent_beacon_block_root,
+    }
+  }
+}
+
+impl std::convert::TryFrom<HeaderJson> for BlockHeader {
+  type Error = String;
+
+  fn try_from(json: HeaderJson) -> Result<Self, Self::Error> {
+    let header = BlockHeader {
+      parent_hash: json.parent_hash,
+      ommers_hash: json.sha3_uncles,
+      beneficiary: json.miner,
+      state_root: json.state_root,
+      transactions_root: json.transactions_root,
+      receipts_root: json.receipts_root,
+      logs_bloom: json.logs_bloom,
+      difficulty: json.difficulty,
+      number: json.number,
+      gas_limit: json.gas_limit,
+      gas_used: json.gas_used,
+      timestamp: json.timestamp,
+      extra_data: json.extra_data,
+      mix_hash: json.mix_hash,
+      nonce: json.nonce,
+      base_fee_per_gas: json.base_fee_per_gas,
+      withdrawals_root: json.withdrawals_root,
+      blob_gas_used: json.blob_gas_used,
+      excess_blob_gas: json.excess_blob_gas,
+      parent_beacon_block_root: json.parent_beacon_block_root,
+    };
+    match json.hash {
+      Some(hash) if hash != header.hash() => Err(format!(
+        "block hash {:?} does not match its header {:?}",
+        hash,
+        header.hash()
+      )),
+      _ => Ok(header),
+    }
+  }
+}
+
+json_repr!(BlockHeader, HeaderJson);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WithdrawalJson {
+  #[serde(with = "quantity")]
+  index: u64,
+  #[serde(with = "quantity")]
+  validator_index: u64,
+  address: Address,
+  #[serde(with = "quantity")]
+  amount: u64,
+}
+
+impl From<&Withdrawal> for WithdrawalJson {
+  fn from(withdrawal: &Withdrawal) -> Self {
+    WithdrawalJson {
+      index: withdrawal.index,
+      validator_index: withdrawal.validator_index,
+      address: withdrawal.address,
+      amount: withdrawal.amount,
+    }
+  }
+}
+
+impl std::convert::TryFrom<WithdrawalJson> for Withdrawal {
+  type Error = String;
+
+  fn try_from(json: WithdrawalJson) -> Result<Self, Self::Error> {
+    Ok(Withdrawal {
+      index: json.index,
+      validator_index: json.validator_index,
+      address: json.address,
+      amount: json.amount,
+    })
+  }
+}
+
+json_repr!(Withdrawal, WithdrawalJson);
+
+/// Blocks are their header members plus `size`, the full transactions, the
+/// hashes of the ommers as `uncles` and withdrawals since Shanghai.
+///
+/// When parsing, full headers in `uncles` are checked against `sha3Uncles`.
+/// Hashes, as the API reports them, don't carry the content of the ommers, so
+/// a block with ommers is only rebuilt from full headers. `totalDifficulty` is
+/// not known to a block on its own, it is ignored when parsing.
+impl Serialize for Json<&Block> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let block = self.0;
+    let hash = block.header.hash();
+    let transactions: Vec<_> = block
+      .transactions
+      .iter()
+      .enumerate()
+      .map(|(index, tx)| {
+        let inclusion = Inclusion {
+          block_hash: hash,
+          block_number: block.header.number,
+          index: index as u64,
+          base_fee_per_gas: block.header.base_fee_per_gas,
+        };
+        TransactionJson::new(tx, Some(inclusion))
+      })
+      .collect();
+    let uncles: Vec<_> = block.ommers.iter().map(BlockHeader::hash).collect();
+    let size = crate::rlp_serialize(block)
+      .map_err(serde::ser::Error::custom)?
+      .len() as u64;
+
+    let mut object = match serde_json::to_value(Json(&block.header)) {
+      Ok(Value::Object(object)) => object,
+      _ => return Err(serde::ser::Error::custom("headers serialize to objects")),
+    };
+    let mut insert = |key: &str, value: Result<Value, serde_json::Error>| {
+      value
+        .map(|value| object.insert(key.to_owned(), value))
+        .map_err(serde::ser::Error::custom)
+    };
+    insert("size", serde_json::to_value(format!("{:#x}", size)))?;
+    insert("transactions", serde_json::to_value(transactions))?;
+    insert("uncles", serde_json::to_value(uncles))?;
+    if let Some(ref withdrawals) = block.withdrawals {
+      let withdrawals: Vec<_> = withdrawals.iter().map(Json).collect();
+      insert("withdrawals", serde_json::to_value(withdrawals))?;
+    }
+    object.serialize(serializer)
+  }
+}
+
+impl Serialize for Json<Block> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    Json(&self.0).serialize(serializer)
+  }
+}
+
+/// Removes a member of a block object and parses it as a list of `T`.
+fn take_list<T, E>(object: &mut Map<String, Value>, key: &str) -> Result<Option<Vec<T>>, E>
+where
+  Json<T>: serde::de::DeserializeOwned,
+  E: Error,
+{
+  let items = match object.remove(key) {
+    Some(Value::Array(items)) => items,
+    Some(Value::Null) | None => return Ok(None),
+    Some(other) => return Err(E::custom(format!("{} is not a list: {}", key, other))),
+  };
+  items
+    .into_iter()
+    .map(|item| match item {
+      Value::String(hash) => Err(E::custom(format!(
+        "{} must be full objects, found the hash {}",
+        key, hash
+      ))),
+      item => serde_json::from_value::<Json<T>>(item)
+        .map(Json::into_inner)
+        .map_err(|e| E::custom(format!("invalid {}: {}", key, e))),
+    })
+    .collect::<Result<_, _>>()
+    .map(Some)
+}
+
+/// Parses the full headers in the `uncles` of a block and checks them against
+/// the `sha3Uncles` of its header.
+fn parse_ommers<E: Error>(
+  uncles: Option<Value>,
+  header: &BlockHeader,
+) -> Result<Vec<BlockHeader>, E> {
+  let items = match uncles {
+    Some(Value::Array(items)) => items,
+    Some(Value::Null) | None => vec![],
+    Some(other) => return Err(E::custom(format!("uncles is not a list: {}", other))),
+  };
+  let mut hashes = Vec::with_capacity(items.len());
+  let mut ommers = Vec::with_capacity(items.len());
+  for item in items {
+    if item.is_string() {
+      hashes.push(serde_json::from_value::<Keccak>(item).map_err(E::custom)?);
+    } else {
+      let ommer = serde_json::from_value::<Json<BlockHeader>>(item)
+        .map_err(|e| E::custom(format!("invalid uncles: {}", e)))?
+        .into_inner();
+      hashes.push(ommer.hash());
+      ommers.push(ommer);
+    }
+  }
+
+  if ommers.len() != hashes.len() {
+    return Err(E::custom(format!(
+      "uncles of block {} must be full headers, their hashes {:?} can't be \
+       expanded",
+      header.number, hashes
+    )));
+  }
+  let ommers_hash = keccak(crate::rlp_serialize(&ommers).map_err(E::custom)?);
+  if ommers_hash != header.ommers_hash {
+    return Err(E::custom(format!(
+      "uncles {:?} do not match sha3Uncles {:?}",
+      hashes, header.ommers_hash
+    )));
+  }
+  Ok(ommers)
+}
+
+impl<'de> Deserialize<'de> for Json<Block> {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let mut object = Map::deserialize(deserializer)?;
+    let transactions: Vec<Transaction> =
+      take_list(&mut object, "transactions")?.unwrap_or_default();
+    let uncles = object.remove("uncles");
+    let withdrawals = take_list(&mut object, "withdrawals")?;
+    let header: BlockHeader = serde_json::from_value::<HeaderJson>(Value::Object(object))
+      .map_err(D::Error::custom)?
+      .try_into()
+      .map_err(D::Error::custom)?;
+    let ommers = parse_ommers(uncles, &header)?;
+
+    Ok(Json(Block {
+      header,
+      transactions,
+      ommers,
+      withdrawals,
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hex_literal::hex;
+
+  fn fixture(hex: &str) -> Vec<u8> {
+    hex::decode(hex.trim()).unwrap()
+  }
+
+  #[test]
+  fn mainnet_genesis() -> Result<(), Box<dyn std::error::Error>> {
+    let expected: Value = serde_json::from_str(include_str!("../../res/mainnet/block_0.json"))?;
+    let block: Block =
+      crate::rlp_deserialize(&fixture(include_str!("../../res/mainnet/block_0.hex")))?;
+
+    // everything but the total difficulty, which depends on the chain
+    let mut json = serde_json::to_value(Json(&block))?;
+    json["totalDifficulty"] = expected["totalDifficulty"].clone();
+    assert_eq!(json, expected);
+
+    let parsed: Json<Block> = serde_json::from_value(expected)?;
+    assert_eq!(parsed.0, block);
+    Ok(())
+  }
+
+  #[test]
+  fn cancun_header() -> Result<(), Box<dyn std::error::Error>> {
+    let header: BlockHeader = crate::rlp_deserialize(&fixture(include_str!(
+      "../../res/mainnet/header_19449567.hex"
+    )))?;
+    let json = serde_json::to_value(Json(&header))?;
+    assert_eq!(
+      json["hash"],
+      "0x85cdcbe36217fd57bf2c33731d8460657a7ce512401f49c9f6392c82a7ccf7ac"
+    );
+    assert_eq!(json["number"], "0x128c6df");
+    assert_eq!(json["baseFeePerGas"], "0x886b221ad");
+    assert_eq!(json["blobGasUsed"], "0x0");
+    assert_eq!(json["excessBlobGas"], "0x0");
+    assert_eq!(json["miner"], format!("{:?}", header.beneficiary));
+    assert_eq!(json["sha3Uncles"], format!("{:?}", header.ommers_hash));
+    assert!(json.get("ommersHash").is_none());
+
+    let parsed: Json<BlockHeader> = serde_json::from_value(json.clone())?;
+    assert_eq!(parsed.0, header);
+
+    // pre-london headers have none of the optional members
+    let old: BlockHeader = crate::rlp_deserialize(&fixture(include_str!(
+      "../../res/mainnet/header_11117104.hex"
+    )))?;
+    let old_json = serde_json::to_value(Json(&old))?;
+    assert!(old_json.get("baseFeePerGas").is_none());
+    assert!(old_json.get("parentBeaconBlockRoot").is_none());
+
+    // the hash is checked against the header content
+    let mut tampered = json;
+    tampered["gasUsed"] = "0x1".into();
+    assert!(serde_json::from_value::<Json<BlockHeader>>(tampered).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn block_with_transactions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut block = Block {
+      header: crate::rlp_deserialize(&fixture(include_str!(
+        "../../res/mainnet/header_19449567.hex"
+      )))?,
+      transactions: vec![],
+      ommers: vec![],
+      withdrawals: Some(vec![Withdrawal {
+        index: 38633702,
+        validator_index: 570038,
+        address: hex!("7cd1122e8e118b12ece8d25480dfeef230da17ff").into(),
+        amount: 18226960,
+      }]),
+    };
+    for tx in [
+      include_str!("../../res/mainnet/tx_bb3a336e.hex"),
+      include_str!("../../res/mainnet/tx_ce4dc6d7.hex"),
+    ]
+    .iter()
+    {
+      block.transactions.push(Transaction::decode(&fixture(tx))?);
+    }
+
+    let json = serde_json::to_value(Json(&block))?;
+    let hash = format!("{:?}", block.header.hash());
+    assert_eq!(json["transactions"][1]["blockHash"], hash);
+    assert_eq!(json["transactions"][1]["blockNumber"], json["number"]);
+    assert_eq!(json["transactions"][1]["transactionIndex"], "0x1");
+    assert_eq!(json["withdrawals"][0]["validatorIndex"], "0x8b2b6");
+    assert_eq!(json["withdrawals"][0]["amount"], "0x1161f10");
+    assert_eq!(
+      json["size"],
+      format!("{:#x}", crate::rlp_serialize(&block)?.len())
+    );
+
+    // dynamic fee transactions report the price they paid
+    let tx = &block.transactions[1];
+    if let crate::TransactionKind::DynamicFee { .. } = tx.kind {
+      let base_fee = block.header.base_fee_per_gas.unwrap();
+      let paid = tx
+        .max_fee_per_gas()
+        .min(base_fee + tx.max_priority_fee_per_gas());
+      assert_eq!(json["transactions"][1]["gasPrice"], format!("{:#x}", paid));
+    }
+
+    let parsed: Json<Block> = serde_json::from_value(json.clone())?;
+    assert_eq!(parsed.0, block);
+
+    // blocks requested without full transactions can't be parsed
+    let mut hashes_only = json;
+    hashes_only["transactions"] = serde_json::json!([hash]);
+    assert!(serde_json::from_value::<Json<Block>>(hashes_only).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn block_with_ommers() -> Result<(), Box<dyn std::error::Error>> {
+    let mut block: Block =
+      crate::rlp_deserialize(&fixture(include_str!("../../res/mainnet/block_436.hex")))?;
+    let ommer: Block =
+      crate::rlp_deserialize(&fixture(include_str!("../../res/mainnet/block_1.hex")))?;
+    block.ommers.push(ommer.header);
+    block.header.ommers_hash = keccak(crate::rlp_serialize(&block.ommers)?);
+
+    // the api reports the hashes of ommers, which can't be expanded again
+    let json = serde_json::to_value(Json(&block))?;
+    assert_eq!(
+      json["uncles"],
+      serde_json::json!([format!("{:?}", block.ommers[0].hash())])
+    );
+    assert!(serde_json::from_value::<Json<Block>>(json.clone()).is_err());
+
+    // full headers are checked against sha3Uncles
+    let mut full = json;
+    full["uncles"] = serde_json::to_value(vec![Json(&block.ommers[0])])?;
+    let parsed: Json<Block> = serde_json::from_value(full.clone())?;
+    assert_eq!(parsed.0, block);
+
+    let mut missing = full.clone();
+    missing["uncles"] = serde_json::json!([]);
+    assert!(serde_json::from_value::<Json<Block>>(missing).is_err());
+
+    let mut tampered = full;
+    tampered["uncles"][0]["gasUsed"] = "0x1".into();
+    tampered["uncles"][0]
+      .as_object_mut()
+      .unwrap()
+      .remove("hash");
+    assert!(serde_json::from_value::<Json<Block>>(tampered).is_err());
+    Ok(())
+  }
+}
@@ -0,0 +1,182 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! JSON representation of the core types in the shape used by the
+//! execution layer JSON-RPC API: camelCase member names as named by
+//! the API (`sha3Uncles`, `miner`, `input`), numbers as 0x-prefixed
+//! quantities without leading zeros and byte strings as 0x-prefixed data.
+//!
+//! The plain `Serialize` and `Deserialize` impls of the core types
+//! produce their RLP encoding, so the JSON form is reached by wrapping
+//! a value in [Json], e.g. `serde_json::to_string(&Json(&block))`.
+
+mod account;
+mod block;
+mod transaction;
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+/// Wraps a core type or a reference to one for de/serialization
+/// in its JSON-RPC shape. Deserialization verifies the `hash` members
+/// of blocks and transactions against the hash of the parsed value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+/// Implements the [Json] wrapper for a core type through its
+/// JSON-RPC representation, a plain serde struct that converts
+/// from a reference to the type and fallibly back into it.
+macro_rules! json_repr {
+  ($name: ty, $repr: ty) => {
+    impl serde::Serialize for $crate::json::Json<&$name> {
+      fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+      where
+        S: serde::Serializer,
+      {
+        <$repr>::from(self.0).serialize(serializer)
+      }
+    }
+
+    impl serde::Serialize for $crate::json::Json<$name> {
+      fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+      where
+        S: serde::Serializer,
+      {
+        $crate::json::Json(&self.0).serialize(serializer)
+      }
+    }
+
+    impl<'de> serde::Deserialize<'de> for $crate::json::Json<$name> {
+      fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+      where
+        D: serde::Deserializer<'de>,
+      {
+        use std::convert::TryInto;
+        let repr = <$repr>::deserialize(deserializer)?;
+        repr
+          .try_into()
+          .map($crate::json::Json)
+          .map_err(<D::Error as serde::de::Error>::custom)
+      }
+    }
+  };
+}
+
+pub(crate) use json_repr;
+
+/// Parses a quantity, hex digits after 0x without leading zeros.
+fn parse_quantity(text: &str) -> Result<u64, String> {
+  let digits = text
+    .strip_prefix("0x")
+    .ok_or_else(|| format!("quantity {} is missing the 0x prefix", text))?;
+  if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) {
+    return Err(format!("invalid quantity {}", text));
+  }
+  u64::from_str_radix(digits, 16).map_err(|e| format!("invalid quantity {}: {}", text, e))
+}
+
+/// 64 bit numbers as quantities, for use with `#[serde(with)]`.
+pub(crate) mod quantity {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#x}", value))
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    parse_quantity(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+  }
+}
+
+/// [quantity] for members that are null or missing when absent.
+pub(crate) mod optional_quantity {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+      Some(value) => quantity::serialize(value, serializer),
+      None => serializer.serialize_none(),
+    }
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+      Some(text) => parse_quantity(&text).map(Some).map_err(D::Error::custom),
+      None => Ok(None),
+    }
+  }
+}
+
+/// Byte strings as 0x-prefixed hex data, for use with `#[serde(with)]`.
+pub(crate) mod data {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", hex::encode(value)))
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    let digits = text
+      .strip_prefix("0x")
+      .ok_or_else(|| D::Error::custom(format!("data {} is missing the 0x prefix", text)))?;
+    hex::decode(digits).map_err(D::Error::custom)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde::Serialize;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Sample {
+    #[serde(with = "quantity")]
+    number: u64,
+    #[serde(with = "data")]
+    bytes: Vec<u8>,
+  }
+
+  #[test]
+  fn quantities_and_data() -> Result<(), serde_json::Error> {
+    let sample = Sample {
+      number: 0,
+      bytes: vec![],
+    };
+    let json = serde_json::to_string(&sample)?;
+    assert_eq!(json, r#"{"number":"0x0","bytes":"0x"}"#);
+    assert_eq!(serde_json::from_str::<Sample>(&json)?, sample);
+
+    let sample = Sample {
+      number: 1024,
+      bytes: vec![0, 0x41],
+    };
+    let json = serde_json::to_string(&sample)?;
+    assert_eq!(json, r#"{"number":"0x400","bytes":"0x0041"}"#);
+    assert_eq!(serde_json::from_str::<Sample>(&json)?, sample);
+
+    for invalid in [
+      r#"{"number":"0x","bytes":"0x"}"#,
+      r#"{"number":"0x0400","bytes":"0x"}"#,
+      r#"{"number":"400","bytes":"0x"}"#,
+      r#"{"number":1024,"bytes":"0x"}"#,
+      r#"{"number":"0x10000000000000000","bytes":"0x"}"#,
+      r#"{"number":"0x0","bytes":"0x041"}"#,
+      r#"{"number":"0x0","bytes":"41"}"#,
+    ]
+    .iter()
+    {
+      assert!(
+        serde_json::from_str::<Sample>(invalid).is_err(),
+        "{}",
+        invalid
+      );
+    }
+    Ok(())
+  }
+}
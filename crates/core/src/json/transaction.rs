@@ -0,0 +1,376 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::{data, json_repr, optional_quantity, quantity};
+use crate::{AccessListItem, Address, Keccak, Signature, Transaction, TransactionKind, H256, U256};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListItemJson {
+  address: Address,
+  storage_keys: Vec<H256>,
+}
+
+/// Where a transaction was included, reported along
+/// with the transactions of a block.
+pub(super) struct Inclusion {
+  pub block_hash: Keccak,
+  pub block_number: u64,
+  pub index: u64,
+  pub base_fee_per_gas: Option<U256>,
+}
+
+/// `eth_getTransactionByHash` and the full transactions of `eth_getBlockByHash`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct TransactionJson {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  block_hash: Option<Keccak>,
+  #[serde(
+    default,
+    with = "optional_quantity",
+    skip_serializing_if = "Option::is_none"
+  )]
+  block_number: Option<u64>,
+  #[serde(
+    default,
+    with = "optional_quantity",
+    skip_serializing_if = "Option::is_none"
+  )]
+  transaction_index: Option<u64>,
+
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  hash: Option<Keccak>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  from: Option<Address>,
+
+  // nodes that predate EIP-2718 omit the type of legacy transactions
+  #[serde(rename = "type", default, with = "quantity")]
+  type_id: u64,
+  #[serde(
+    default,
+    with = "optional_quantity",
+    skip_serializing_if = "Option::is_none"
+  )]
+  chain_id: Option<u64>,
+  nonce: U256,
+  gas: U256,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  gas_price: Option<U256>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  max_fee_per_gas: Option<U256>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  max_priority_fee_per_gas: Option<U256>,
+  to: Option<Address>,
+  value: U256,
+  #[serde(with = "data")]
+  input: Vec<u8>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  access_list: Option<Vec<AccessListItemJson>>,
+
+  #[serde(with = "quantity")]
+  v: u64,
+  r: U256,
+  s: U256,
+  #[serde(
+    default,
+    with = "optional_quantity",
+    skip_serializing_if = "Option::is_none"
+  )]
+  y_parity: Option<u64>,
+}
+
+fn access_list_json(access_list: &[AccessListItem]) -> Option<Vec<AccessListItemJson>> {
+  Some(
+    access_list
+      .iter()
+      .map(|item| AccessListItemJson {
+        address: item.address,
+        storage_keys: item.storage_keys.clone(),
+      })
+      .collect(),
+  )
+}
+
+impl TransactionJson {
+  pub(super) fn new(tx: &Transaction, inclusion: Option<Inclusion>) -> Self {
+    let (gas_price, max_fee_per_gas, max_priority_fee_per_gas, access_list) = match tx.kind {
+      TransactionKind::Legacy { gas_price } => (Some(gas_price), None, None, None),
+      TransactionKind::AccessList {
+        gas_price,
+        ref access_list,
+      } => (Some(gas_price), None, None, access_list_json(access_list)),
+      TransactionKind::DynamicFee {
+        max_priority_fee_per_gas,
+        max_fee_per_gas,
+        ref access_list,
+      } => {
+        // once included the price actually paid is known
+        let effective_gas_price = inclusion
+          .as_ref()
+          .and_then(|inclusion| inclusion.base_fee_per_gas)
+          .map(|base_fee| max_fee_per_gas.min(base_fee.saturating_add(max_priority_fee_per_gas)));
+        (
+          effective_gas_price,
+          Some(max_fee_per_gas),
+          Some(max_priority_fee_per_gas),
+          access_list_json(access_list),
+        )
+      }
+    };
+
+    TransactionJson {
+      block_hash: inclusion.as_ref().map(|i| i.block_hash),
+      block_number: inclusion.as_ref().map(|i| i.block_number),
+      transaction_index: inclusion.as_ref().map(|i| i.index),
+      hash: Some(tx.hash()),
+      from: tx.sender().ok(),
+      type_id: tx.type_id() as u64,
+      chain_id: tx.chain_id,
+      nonce: tx.nonce,
+      gas: tx.gas_limit,
+      gas_price,
+      max_fee_per_gas,
+      max_priority_fee_per_gas,
+      to: tx.recipient,
+      value: tx.value,
+      input: tx.data.clone(),
+      access_list,
+      v: tx.signature.v,
+      r: tx.signature.r,
+      s: tx.signature.s,
+      y_parity: match tx.kind {
+        TransactionKind::Legacy { .. } => None,
+        _ => Some(tx.signature.v),
+      },
+    }
+  }
+}
+
+impl From<&Transaction> for TransactionJson {
+  fn from(tx: &Transaction) -> Self {
+    TransactionJson::new(tx, None)
+  }
+}
+
+impl std::convert::TryFrom<TransactionJson> for Transaction {
+  type Error = String;
+
+  fn try_from(json: TransactionJson) -> Result<Self, Self::Error> {
+    let missing = |member: &str| format!("type {} transaction without {}", json.type_id, member);
+    let access_list = || -> Result<Vec<AccessListItem>, String> {
+      Ok(
+        json
+          .access_list
+          .as_ref()
+          .ok_or_else(|| missing("accessList"))?
+          .iter()
+          .map(|item| AccessListItem {
+            address: item.address,
+            storage_keys: item.storage_keys.clone(),
+          })
+          .collect(),
+      )
+    };
+
+    let kind = match json.type_id {
+      0x00 => TransactionKind::Legacy {
+        gas_price: json.gas_price.ok_or_else(|| missing("gasPrice"))?,
+      },
+      0x01 => TransactionKind::AccessList {
+        gas_price: json.gas_price.ok_or_else(|| missing("gasPrice"))?,
+        access_list: access_list()?,
+      },
+      0x02 => TransactionKind::DynamicFee {
+        max_priority_fee_per_gas: json
+          .max_priority_fee_per_gas
+          .ok_or_else(|| missing("maxPriorityFeePerGas"))?,
+        max_fee_per_gas: json
+          .max_fee_per_gas
+          .ok_or_else(|| missing("maxFeePerGas"))?,
+        access_list: access_list()?,
+      },
+      other => return Err(format!("unknown transaction type {}", other)),
+    };
+
+    let signature = Signature {
+      v: json.v,
+      r: json.r,
+      s: json.s,
+    };
+    let chain_id = match kind {
+      // the chain id of legacy transactions is part of V
+      TransactionKind::Legacy { .. } => signature.chain_id(),
+      _ => {
+        if json.y_parity.unwrap_or(json.v) != json.v {
+          return Err(format!("yParity {:?} differs from v", json.y_parity));
+        }
+        Some(json.chain_id.ok_or_else(|| missing("chainId"))?)
+      }
+    };
+    if json.chain_id.is_some() && json.chain_id != chain_id {
+      return Err(format!("chainId {:?} differs from v", json.chain_id));
+    }
+
+    let tx = Transaction {
+      kind,
+      chain_id,
+      nonce: json.nonce,
+      gas_limit: json.gas,
+      recipient: json.to,
+      value: json.value,
+      data: json.input,
+      signature,
+    };
+    if let Some(hash) = json.hash.filter(|hash| *hash != tx.hash()) {
+      return Err(format!(
+        "transaction hash {:?} does not match its content {:?}",
+        hash,
+        tx.hash()
+      ));
+    }
+    match (json.from, tx.sender()) {
+      (Some(from), Ok(sender)) if from != sender => Err(format!(
+        "from {:?} does not match the signer {:?}",
+        from, sender
+      )),
+      (Some(from), Err(e)) => Err(format!("from {:?} without a valid signature: {}", from, e)),
+      _ => Ok(tx),
+    }
+  }
+}
+
+json_repr!(Transaction, TransactionJson);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Json;
+  use serde_json::json;
+
+  fn mainnet(fixture: &str) -> Transaction {
+    Transaction::decode(&hex::decode(fixture.trim()).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn legacy_transaction() -> Result<(), serde_json::Error> {
+    let tx = mainnet(include_str!("../../res/mainnet/tx_bb3a336e.hex"));
+    let json = serde_json::to_value(Json(&tx))?;
+    assert_eq!(json["type"], "0x0");
+    assert_eq!(
+      json["hash"],
+      "0xbb3a336e3f823ec18197f1e13ee875700f08f03e2cab75f0d0b118dabb44cba0"
+    );
+    assert_eq!(json["from"], "0x398137383b3d25c92898c656696e41950e47316b");
+    assert_eq!(json["chainId"], "0x1");
+    assert!(json.get("accessList").is_none());
+    assert!(json.get("yParity").is_none());
+    assert!(json.get("blockHash").is_none());
+
+    let decoded: Json<Transaction> = serde_json::from_value(json)?;
+    assert_eq!(decoded.into_inner(), tx);
+
+    let tx = mainnet(include_str!("../../res/mainnet/tx_280cde7c.hex"));
+    let decoded: Json<Transaction> = serde_json::from_value(serde_json::to_value(Json(&tx))?)?;
+    assert_eq!(decoded.into_inner(), tx);
+    Ok(())
+  }
+
+  #[test]
+  fn typed_transactions() -> Result<(), serde_json::Error> {
+    for fixture in [
+      include_str!("../../res/mainnet/tx_0ec0b6a2.hex"),
+      include_str!("../../res/mainnet/tx_ce4dc6d7.hex"),
+    ]
+    .iter()
+    {
+      let tx = mainnet(fixture);
+      let json = serde_json::to_value(Json(&tx))?;
+      assert_eq!(json["type"], format!("{:#x}", tx.type_id()));
+      assert_eq!(json["yParity"], json["v"]);
+      assert!(json["accessList"].is_array());
+
+      let decoded: Json<Transaction> = serde_json::from_value(json)?;
+      assert_eq!(decoded.0, tx);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn huge_priority_fee() -> Result<(), serde_json::Error> {
+    let tx = Transaction {
+      kind: TransactionKind::DynamicFee {
+        max_priority_fee_per_gas: U256::MAX,
+        max_fee_per_gas: 1000.into(),
+        access_list: vec![],
+      },
+      ..Transaction::default()
+    };
+    let inclusion = Inclusion {
+      block_hash: Keccak::zero(),
+      block_number: 1,
+      index: 0,
+      base_fee_per_gas: Some(7.into()),
+    };
+
+    // the priority fee is capped by the max fee, it never overflows
+    let json = serde_json::to_value(TransactionJson::new(&tx, Some(inclusion)))?;
+    assert_eq!(json["gasPrice"], "0x3e8");
+    assert_eq!(json["maxPriorityFeePerGas"], format!("{:#x}", U256::MAX));
+    Ok(())
+  }
+
+  /// A contract creation as returned by a node
+  #[test]
+  fn rpc_response() -> Result<(), serde_json::Error> {
+    let response = json!({
+      "type": "0x2",
+      "chainId": "0x1",
+      "nonce": "0x0",
+      "gas": "0x5208",
+      "maxFeePerGas": "0x3b9aca00",
+      "maxPriorityFeePerGas": "0x1",
+      "to": null,
+      "value": "0xde0b6b3a7640000",
+      "input": "0x6000",
+      "accessList": [{
+        "address": "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf",
+        "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000001"]
+      }],
+      "v": "0x1",
+      "r": "0x1",
+      "s": "0x2",
+      "yParity": "0x1"
+    });
+    let tx = serde_json::from_value::<Json<Transaction>>(response.clone())?.into_inner();
+    assert_eq!(tx.recipient, None);
+    assert_eq!(tx.data, vec![0x60, 0x00]);
+    assert_eq!(tx.max_priority_fee_per_gas(), U256::one());
+    assert_eq!(tx.signature.v, 1);
+
+    // members are verified against each other
+    let mut wrong_hash = response.clone();
+    wrong_hash["hash"] = json!(format!("{:?}", Keccak::zero()));
+    assert!(serde_json::from_value::<Json<Transaction>>(wrong_hash).is_err());
+
+    let mut wrong_parity = response.clone();
+    wrong_parity["yParity"] = json!("0x0");
+    assert!(serde_json::from_value::<Json<Transaction>>(wrong_parity).is_err());
+
+    let mut wrong_sender = serde_json::to_value(Json(&mainnet(include_str!(
+      "../../res/mainnet/tx_ce4dc6d7.hex"
+    ))))?;
+    wrong_sender["from"] = json!("0x7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+    assert!(serde_json::from_value::<Json<Transaction>>(wrong_sender).is_err());
+
+    let mut forged_sender = response.clone();
+    forged_sender["from"] = json!("0x7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+    assert!(serde_json::from_value::<Json<Transaction>>(forged_sender).is_err());
+
+    let mut missing_fee = response;
+    missing_fee.as_object_mut().unwrap().remove("maxFeePerGas");
+    assert!(serde_json::from_value::<Json<Transaction>>(missing_fee).is_err());
+    Ok(())
+  }
+}
@@ -0,0 +1,170 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{hash_message, Address, Keccak, Signature, SignatureError, H256};
+use keccak_hash::keccak;
+use std::{fmt, str::FromStr};
+
+/// Produces ECDSA signatures on behalf of an account. Implemented by
+/// plain secret keys, but also by anything that holds keys elsewhere,
+/// like unlocked keystore accounts or hardware wallets.
+pub trait Signer {
+  type Error: From<SignatureError>;
+
+  /// The address of the account whose key produces the signatures
+  fn address(&self) -> Address;
+
+  /// Signs a 32-byte message hash. V holds the bare recovery id,
+  /// callers adjust it to the encoding they need.
+  fn sign_hash(&self, hash: &Keccak) -> Result<Signature, Self::Error>;
+
+  /// Signs a message the way `personal_sign` does, see [hash_message].
+  /// V is 27 or 28, as expected by `ecrecover` and wallets.
+  fn sign_message(&self, message: &[u8]) -> Result<Signature, Self::Error> {
+    Ok(self.sign_hash(&hash_message(message))?.to_legacy(None)?)
+  }
+}
+
+/// A secp256k1 secret key, a 256-bit scalar in the range [1, n).
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretKey(secp256k1::key::SecretKey);
+
+/// A secp256k1 public key, the point on the curve that corresponds to a secret key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(pub(crate) secp256k1::key::PublicKey);
+
+impl SecretKey {
+  /// Generates a new key from the thread-local random number generator.
+  pub fn random() -> Self {
+    let ctx = secp256k1::Secp256k1::new();
+    SecretKey(secp256k1::key::SecretKey::new(
+      &ctx,
+      &mut rand::thread_rng(),
+    ))
+  }
+
+  /// Validates that the bytes are a big-endian scalar in the range of valid keys.
+  pub fn from_slice(bytes: &[u8]) -> Result<Self, secp256k1::Error> {
+    let ctx = secp256k1::Secp256k1::new();
+    secp256k1::key::SecretKey::from_slice(&ctx, bytes).map(SecretKey)
+  }
+
+  /// Raw big-endian representation of the key
+  pub fn to_bytes(&self) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&self.0[..]);
+    bytes
+  }
+
+  pub fn public(&self) -> PublicKey {
+    let ctx = secp256k1::Secp256k1::new();
+    PublicKey(
+      secp256k1::key::PublicKey::from_secret_key(&ctx, &self.0)
+        .expect("a validated secret key always has a public key"),
+    )
+  }
+
+  /// The address of the account controlled by this key
+  pub fn address(&self) -> Address {
+    self.public().address()
+  }
+}
+
+/// Never prints the key itself, so that it doesn't end up in logs.
+impl fmt::Debug for SecretKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "SecretKey({:?})", self.address())
+  }
+}
+
+/// Parses a hex encoded key, with or without the 0x prefix.
+impl FromStr for SecretKey {
+  type Err = secp256k1::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let bytes = hex::decode(s).map_err(|_| secp256k1::Error::InvalidSecretKey)?;
+    Self::from_slice(&bytes)
+  }
+}
+
+impl Signer for SecretKey {
+  type Error = SignatureError;
+
+  fn address(&self) -> Address {
+    SecretKey::address(self)
+  }
+
+  fn sign_hash(&self, hash: &Keccak) -> Result<Signature, Self::Error> {
+    Ok(Signature::new(hash, &H256::from(self.to_bytes()))?)
+  }
+}
+
+impl PublicKey {
+  /// Uncompressed encoding of the point: 32 bytes x followed by 32 bytes y,
+  /// without the 0x04 prefix used by SEC1.
+  pub fn to_bytes(&self) -> [u8; 64] {
+    let ctx = secp256k1::Secp256k1::new();
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(&self.0.serialize_vec(&ctx, false)[1..]);
+    bytes
+  }
+
+  /// The last 20 bytes of the keccak hash of the uncompressed key
+  pub fn address(&self) -> Address {
+    Address::from_slice(&keccak(&self.to_bytes()[..])[12..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hex_literal::hex;
+
+  #[test]
+  fn address_derivation() -> Result<(), secp256k1::Error> {
+    let key: SecretKey =
+      "0x0000000000000000000000000000000000000000000000000000000000000001".parse()?;
+    assert_eq!(
+      key.public().to_bytes()[..],
+      hex!(
+        "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+      )[..]
+    );
+    assert_eq!(
+      key.address(),
+      hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf").into()
+    );
+
+    let key = SecretKey::from_slice(&hex!(
+      "4646464646464646464646464646464646464646464646464646464646464646"
+    ))?;
+    assert_eq!(
+      key.address(),
+      hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").into()
+    );
+    assert_eq!(
+      format!("{:?}", key),
+      format!("SecretKey({:?})", key.address())
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_keys() {
+    assert!(SecretKey::from_slice(&[0u8; 32]).is_err());
+    assert!(SecretKey::from_slice(&[0xffu8; 32]).is_err());
+    assert!(SecretKey::from_slice(&[1u8; 31]).is_err());
+    assert!("0xzz".parse::<SecretKey>().is_err());
+  }
+
+  #[test]
+  fn sign_and_recover() -> Result<(), Box<dyn std::error::Error>> {
+    let key = SecretKey::random();
+    let hash = keccak(b"hello");
+    let signature = key.sign_hash(&hash)?;
+    assert_eq!(signature.recover(&hash)?, Signer::address(&key));
+    Ok(())
+  }
+}
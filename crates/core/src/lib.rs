@@ -3,21 +3,36 @@
 
 mod account;
 mod block;
+mod chainspec;
+mod json;
+mod keys;
+mod message;
 mod primitive;
-mod rlp;
+mod receipt;
 mod transaction;
 
 // large integers
-pub use primitive::{Address, Bloom, H256, H256 as Keccak, U256};
+pub use primitive::{Address, Bloom, H256, H256 as Keccak, H64, U256};
 
 // domain types
 pub use account::Account;
-pub use block::Block;
-pub use transaction::Transaction;
+pub use block::{Block, BlockHeader, Withdrawal};
+pub use chainspec::{
+  ChainSpec, ChainSpecError, Engine, ForkCondition, ForkId, ForkSchedule, Forks, Genesis,
+  GenesisAccount, Hardfork, ValidatorSet,
+};
+pub use keys::{PublicKey, SecretKey, Signer};
+pub use message::{hash_message, TypedData, TypedDataError, TypedField};
+pub use receipt::{Log, LogsBloom, Receipt, TransactionOutcome};
+pub use transaction::{
+  AccessList, AccessListItem, Signature, SignatureError, Transaction, TransactionKind,
+};
+
+// JSON-RPC representation
+pub use json::Json;
 
 // rlp de/serialization
-// TODO: consider isolating into a separate crate
-pub use crate::rlp::{
+pub use oe4_rlp::{
   deserialize as rlp_deserialize, deserialize_from as rlp_deserialize_from,
-  serialize as rlp_serialize,
-};
\ No newline at end of file
+  serialize as rlp_serialize, with as rlp_with, RlpItems, RlpReader, RlpView, RlpViewIter,
+};
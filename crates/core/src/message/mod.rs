@@ -0,0 +1,59 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Signing of data other than transactions. The hashes are prefixed
+//! so that a signed message can never be replayed as a transaction,
+//! https://eips.ethereum.org/EIPS/eip-191
+
+mod typed_data;
+
+pub use typed_data::{TypedData, TypedDataError, TypedField};
+
+use crate::Keccak;
+use keccak_hash::keccak;
+
+/// Hash of a message as signed by `personal_sign` and `eth_sign`,
+/// EIP-191 version 0x45: keccak("\x19Ethereum Signed Message:\n" || len || message)
+pub fn hash_message(message: impl AsRef<[u8]>) -> Keccak {
+  let message = message.as_ref();
+  let mut preimage = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+  preimage.extend_from_slice(message);
+  keccak(preimage)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{SecretKey, Signature, Signer};
+  use hex_literal::hex;
+
+  #[test]
+  fn personal_message() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+      hash_message(b"Hello World"),
+      hex!("a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2").into()
+    );
+    assert_eq!(
+      hash_message(""),
+      hex!("5f35dce98ba4fba25530a026ed80b2cecdaa31091ba4958b99b52ea1d068adad").into()
+    );
+
+    let key = SecretKey::from_slice(&hex!(
+      "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+    ))?;
+    let signature = key.sign_message(b"Some data")?;
+    assert_eq!(
+      signature.to_bytes()?[..],
+      hex!(
+        "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd"
+        "6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a029"
+        "1c"
+      )[..]
+    );
+
+    let parsed = Signature::from_bytes(&signature.to_bytes()?)?;
+    assert_eq!(parsed.recover(&hash_message(b"Some data"))?, key.address());
+    assert_ne!(parsed.recover(&hash_message(b"Other data"))?, key.address());
+    Ok(())
+  }
+}
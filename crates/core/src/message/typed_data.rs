@@ -0,0 +1,429 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Typed structured data hashing, https://eips.ethereum.org/EIPS/eip-712
+
+use crate::{Keccak, U256};
+use keccak_hash::keccak;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypedDataError {
+  /// A type is referenced but neither a struct in `types` nor an atomic type
+  UnknownType(String),
+  /// A struct value lacks one of the members of its type, as `Type.member`
+  MissingField(String),
+  /// A value can't be encoded as the type it is declared with
+  InvalidValue { ty: String, value: String },
+}
+
+impl std::fmt::Display for TypedDataError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self, f)
+  }
+}
+
+impl std::error::Error for TypedDataError {}
+
+/// A member of a struct type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypedField {
+  pub name: String,
+  #[serde(rename = "type")]
+  pub ty: String,
+}
+
+/// The payload of `eth_signTypedData_v4`: struct type definitions,
+/// the signing domain and the message, which is an instance of
+/// the primary type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+  pub types: BTreeMap<String, Vec<TypedField>>,
+  pub primary_type: String,
+  pub domain: Value,
+  pub message: Value,
+}
+
+fn invalid(ty: &str, value: &Value) -> TypedDataError {
+  TypedDataError::InvalidValue {
+    ty: ty.to_owned(),
+    value: value.to_string(),
+  }
+}
+
+/// Splits `Type[2][]` into `Type[2]` and the length of the outer array.
+fn split_array(ty: &str) -> Option<(&str, Option<&str>)> {
+  let inner = ty.strip_suffix(']')?;
+  let open = inner.rfind('[')?;
+  let length = &inner[open + 1..];
+  Some((
+    &inner[..open],
+    if length.is_empty() {
+      None
+    } else {
+      Some(length)
+    },
+  ))
+}
+
+/// The struct or atomic type of array elements, `Type` for `Type[2][]`.
+fn base_type(ty: &str) -> &str {
+  ty.find('[').map_or(ty, |open| &ty[..open])
+}
+
+fn decode_hex(ty: &str, value: &Value) -> Result<Vec<u8>, TypedDataError> {
+  let text = value.as_str().ok_or_else(|| invalid(ty, value))?;
+  let hex = text.strip_prefix("0x").unwrap_or(text);
+  hex::decode(hex).map_err(|_| invalid(ty, value))
+}
+
+/// Parses a JSON number or a decimal / hex string into its sign and magnitude.
+fn parse_integer(ty: &str, value: &Value) -> Result<(bool, U256), TypedDataError> {
+  let text = match value {
+    Value::Number(n) => n.to_string(),
+    Value::String(s) => s.clone(),
+    _ => return Err(invalid(ty, value)),
+  };
+  let (negative, digits) = match text.strip_prefix('-') {
+    Some(digits) => (true, digits),
+    None => (false, text.as_str()),
+  };
+  let magnitude = match digits.strip_prefix("0x") {
+    Some(hex) => U256::from_str_radix(hex, 16).ok(),
+    None => U256::from_dec_str(digits).ok(),
+  };
+  magnitude
+    .map(|m| (negative && !m.is_zero(), m))
+    .ok_or_else(|| invalid(ty, value))
+}
+
+/// Bit width of an `intN` or `uintN` type, None for anything else.
+fn integer_bits(ty: &str, prefix: &str) -> Option<usize> {
+  let bits = ty.strip_prefix(prefix)?;
+  match bits {
+    "" => Some(256),
+    bits => bits
+      .parse()
+      .ok()
+      .filter(|bits| bits % 8 == 0 && (8..=256).contains(bits)),
+  }
+}
+
+impl TypedData {
+  /// `Name(type1 name1,...)` followed by the encodings of all
+  /// struct types it references, sorted by name.
+  pub fn encode_type(&self, name: &str) -> Result<String, TypedDataError> {
+    let mut referenced = BTreeSet::new();
+    self.collect_references(name, &mut referenced)?;
+    referenced.remove(name);
+
+    let mut encoded = self.encode_single_type(name)?;
+    for dependency in referenced {
+      encoded.push_str(&self.encode_single_type(dependency)?);
+    }
+    Ok(encoded)
+  }
+
+  pub fn type_hash(&self, name: &str) -> Result<Keccak, TypedDataError> {
+    Ok(keccak(self.encode_type(name)?))
+  }
+
+  /// keccak(typeHash || encodeData(value))
+  pub fn hash_struct(&self, name: &str, value: &Value) -> Result<Keccak, TypedDataError> {
+    let fields = self.fields(name)?;
+    let object = value.as_object().ok_or_else(|| invalid(name, value))?;
+
+    let mut encoded = Vec::with_capacity(32 * (fields.len() + 1));
+    encoded.extend_from_slice(self.type_hash(name)?.as_bytes());
+    for field in fields {
+      let member = object
+        .get(&field.name)
+        .ok_or_else(|| TypedDataError::MissingField(format!("{}.{}", name, field.name)))?;
+      encoded.extend_from_slice(&self.encode_value(&field.ty, member)?);
+    }
+    Ok(keccak(encoded))
+  }
+
+  /// hashStruct of the domain, identifies the dapp and
+  /// contract that is going to verify the signature.
+  pub fn domain_separator(&self) -> Result<Keccak, TypedDataError> {
+    self.hash_struct(DOMAIN_TYPE, &self.domain)
+  }
+
+  /// The hash that gets signed: keccak(0x19 0x01 || domainSeparator || hashStruct(message)).
+  /// When the primary type is the domain itself only the domain separator is hashed.
+  pub fn signing_hash(&self) -> Result<Keccak, TypedDataError> {
+    let mut preimage = vec![0x19, 0x01];
+    preimage.extend_from_slice(self.domain_separator()?.as_bytes());
+    if self.primary_type != DOMAIN_TYPE {
+      preimage.extend_from_slice(
+        self
+          .hash_struct(&self.primary_type, &self.message)?
+          .as_bytes(),
+      );
+    }
+    Ok(keccak(preimage))
+  }
+
+  fn fields(&self, name: &str) -> Result<&[TypedField], TypedDataError> {
+    self
+      .types
+      .get(name)
+      .map(Vec::as_slice)
+      .ok_or_else(|| TypedDataError::UnknownType(name.to_owned()))
+  }
+
+  fn encode_single_type(&self, name: &str) -> Result<String, TypedDataError> {
+    let members: Vec<String> = self
+      .fields(name)?
+      .iter()
+      .map(|field| format!("{} {}", field.ty, field.name))
+      .collect();
+    Ok(format!("{}({})", name, members.join(",")))
+  }
+
+  fn collect_references<'a>(
+    &'a self,
+    name: &'a str,
+    found: &mut BTreeSet<&'a str>,
+  ) -> Result<(), TypedDataError> {
+    if !found.insert(name) {
+      return Ok(());
+    }
+    for field in self.fields(name)? {
+      let base = base_type(&field.ty);
+      if self.types.contains_key(base) {
+        self.collect_references(base, found)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Encodes a single member value into its 32-byte word.
+  fn encode_value(&self, ty: &str, value: &Value) -> Result<[u8; 32], TypedDataError> {
+    if let Some((element, length)) = split_array(ty) {
+      let items = value.as_array().ok_or_else(|| invalid(ty, value))?;
+      if let Some(length) = length {
+        if length.parse::<usize>().ok() != Some(items.len()) {
+          return Err(invalid(ty, value));
+        }
+      }
+      let mut encoded = Vec::with_capacity(32 * items.len());
+      for item in items {
+        encoded.extend_from_slice(&self.encode_value(element, item)?);
+      }
+      return Ok(keccak(encoded).to_fixed_bytes());
+    }
+
+    if self.types.contains_key(ty) {
+      return Ok(self.hash_struct(ty, value)?.to_fixed_bytes());
+    }
+
+    let mut word = [0u8; 32];
+    match ty {
+      "string" => {
+        let text = value.as_str().ok_or_else(|| invalid(ty, value))?;
+        word = keccak(text.as_bytes()).to_fixed_bytes();
+      }
+      "bytes" => word = keccak(decode_hex(ty, value)?).to_fixed_bytes(),
+      "bool" => {
+        let flag = match value {
+          Value::Bool(flag) => *flag,
+          Value::String(s) if s == "true" || s == "false" => s == "true",
+          _ => return Err(invalid(ty, value)),
+        };
+        word[31] = flag as u8;
+      }
+      "address" => {
+        let bytes = decode_hex(ty, value)?;
+        if bytes.len() != 20 {
+          return Err(invalid(ty, value));
+        }
+        word[12..].copy_from_slice(&bytes);
+      }
+      _ => {
+        if let Some(size) = ty.strip_prefix("bytes") {
+          let size: usize = size
+            .parse()
+            .ok()
+            .filter(|size| (1..=32).contains(size))
+            .ok_or_else(|| TypedDataError::UnknownType(ty.to_owned()))?;
+          let bytes = decode_hex(ty, value)?;
+          if bytes.len() > size {
+            return Err(invalid(ty, value));
+          }
+          word[..bytes.len()].copy_from_slice(&bytes);
+        } else if let Some(bits) = integer_bits(ty, "uint") {
+          let (negative, magnitude) = parse_integer(ty, value)?;
+          if negative || magnitude.bits() > bits {
+            return Err(invalid(ty, value));
+          }
+          magnitude.to_big_endian(&mut word);
+        } else if let Some(bits) = integer_bits(ty, "int") {
+          let (negative, magnitude) = parse_integer(ty, value)?;
+          let limit = U256::one() << (bits - 1);
+          if (!negative && magnitude >= limit) || (negative && magnitude > limit) {
+            return Err(invalid(ty, value));
+          }
+          // two's complement, sign extended to 256 bits
+          let encoded = match negative {
+            true => (!magnitude).overflowing_add(U256::one()).0,
+            false => magnitude,
+          };
+          encoded.to_big_endian(&mut word);
+        } else {
+          return Err(TypedDataError::UnknownType(ty.to_owned()));
+        }
+      }
+    }
+    Ok(word)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{SecretKey, Signer};
+  use hex_literal::hex;
+
+  fn fixture(json: &str) -> TypedData {
+    serde_json::from_str(json).unwrap()
+  }
+
+  /// The example from the EIP, https://eips.ethereum.org/assets/eip-712/Example.js
+  #[test]
+  fn mail_example() -> Result<(), Box<dyn std::error::Error>> {
+    let data = fixture(include_str!("../../res/eip712/mail.json"));
+    assert_eq!(
+      data.encode_type("Mail")?,
+      "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+    );
+    assert_eq!(
+      data.type_hash("Mail")?,
+      hex!("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2").into()
+    );
+    assert_eq!(
+      data.hash_struct("Mail", &data.message)?,
+      hex!("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e").into()
+    );
+    assert_eq!(
+      data.domain_separator()?,
+      hex!("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f").into()
+    );
+
+    let hash = data.signing_hash()?;
+    assert_eq!(
+      hash,
+      hex!("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2").into()
+    );
+
+    let cow = SecretKey::from_slice(keccak("cow").as_bytes())?;
+    assert_eq!(
+      cow.address(),
+      hex!("cd2a3d9f938e13cd947ec05abc7fe734df8dd826").into()
+    );
+
+    let signature = cow.sign_hash(&hash)?.to_legacy(None)?;
+    assert_eq!(signature.v, 28);
+    assert_eq!(
+      signature.r,
+      hex!("4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d").into()
+    );
+    assert_eq!(
+      signature.s,
+      hex!("07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562").into()
+    );
+    assert_eq!(signature.recover(&hash)?, cow.address());
+    Ok(())
+  }
+
+  #[test]
+  fn arrays_and_nested_structs() -> Result<(), TypedDataError> {
+    let arrays = fixture(include_str!("../../res/eip712/arrays.json"));
+    assert_eq!(
+      arrays.signing_hash()?,
+      hex!("80a3aeb51161cfc47884ddf8eac0d2343d6ae640efe78b6a69be65e3045c1321").into()
+    );
+
+    // members not declared in the type are ignored
+    let seaport = fixture(include_str!("../../res/eip712/seaport.json"));
+    assert_eq!(
+      seaport.encode_type("OrderComponents")?,
+      "OrderComponents(address offerer,address zone,OfferItem[] offer,uint256 startTime,\
+       uint256 endTime,bytes32 zoneHash,uint256 salt,bytes32 conduitKey,uint256 counter)\
+       OfferItem(address token)"
+    );
+    assert_eq!(
+      seaport.signing_hash()?,
+      hex!("0b8aa9f3712df0034bc29fe5b24dd88cfdba02c7f499856ab24632e2969709a8").into()
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn domain_only() -> Result<(), TypedDataError> {
+    let minimal = fixture(
+      r#"{ "types": { "EIP712Domain": [] }, "primaryType": "EIP712Domain", "domain": {}, "message": {} }"#,
+    );
+    assert_eq!(
+      minimal.signing_hash()?,
+      hex!("8d4a3f4082945b7879e2b55f181c31a77c8c0a464b70669458abbaaf99de4c38").into()
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn atomic_types() -> Result<(), TypedDataError> {
+    let data = fixture(
+      r#"{ "types": { "EIP712Domain": [] }, "primaryType": "EIP712Domain", "domain": {}, "message": {} }"#,
+    );
+    let word = |ty: &str, value: Value| data.encode_value(ty, &value);
+
+    let mut expected = [0u8; 32];
+    expected[31] = 1;
+    assert_eq!(word("bool", Value::Bool(true))?, expected);
+    assert_eq!(word("uint8", "0x01".into())?, expected);
+    assert_eq!(word("uint256", 1.into())?, expected);
+    assert_eq!(word("int64", "1".into())?, expected);
+
+    assert_eq!(word("int8", (-1).into())?, [0xff; 32]);
+    assert_eq!(word("int256", "-1".into())?, [0xff; 32]);
+    assert_eq!(word("bytes2", "0xabcd".into())?[..3], [0xab, 0xcd, 0x00]);
+    assert_eq!(word("bytes", "0x".into())?, keccak([]).to_fixed_bytes());
+
+    // out of range values
+    assert!(word("uint8", 256.into()).is_err());
+    assert!(word("uint256", (-1).into()).is_err());
+    assert!(word("int8", 128.into()).is_err());
+    assert!(word("int8", (-128).into()).is_ok());
+    assert!(word("int8", (-129).into()).is_err());
+    assert!(word("bytes1", "0xabcd".into()).is_err());
+    assert!(word("address", "0x1234".into()).is_err());
+    assert!(word("uint256[2]", serde_json::json!([1])).is_err());
+
+    assert_eq!(
+      word("uint7", 1.into()),
+      Err(TypedDataError::UnknownType("uint7".into()))
+    );
+    assert_eq!(
+      word("Person", Value::Null),
+      Err(TypedDataError::UnknownType("Person".into()))
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn missing_members() {
+    let mut data = fixture(include_str!("../../res/eip712/mail.json"));
+    data.message.as_object_mut().unwrap().remove("contents");
+    assert_eq!(
+      data.signing_hash(),
+      Err(TypedDataError::MissingField("Mail.contents".into()))
+    );
+  }
+}
@@ -0,0 +1,387 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Address, Bloom, H256, U256};
+use ethereum_types::BloomInput;
+use rlp::{DecoderError, Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
+
+/// An event emitted by a contract while executing a transaction
+#[derive(Default, Debug, Eq, Clone, PartialEq)]
+pub struct Log {
+  pub address: Address,
+  pub topics: Vec<H256>,
+  pub data: Vec<u8>,
+}
+
+impl Log {
+  /// The bloom filter of this log alone, its address and all of its topics.
+  pub fn bloom(&self) -> Bloom {
+    let mut bloom = Bloom::zero();
+    bloom.accrue_log(self);
+    bloom
+  }
+}
+
+impl rlp::Encodable for Log {
+  fn rlp_append(&self, s: &mut RlpStream) {
+    s.begin_list(3);
+    s.append(&self.address);
+    s.append_list(&self.topics);
+    s.append(&self.data);
+  }
+}
+
+impl rlp::Decodable for Log {
+  fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+    if rlp.item_count()? != 3 {
+      return Err(DecoderError::RlpIncorrectListLen);
+    }
+    Ok(Log {
+      address: rlp.val_at(0)?,
+      topics: rlp.list_at(1)?,
+      data: rlp.val_at(2)?,
+    })
+  }
+}
+
+/// Building and querying the bloom filters that blocks and receipts
+/// carry, used to quickly rule out blocks when filtering logs.
+pub trait LogsBloom {
+  /// Adds the address and topics of the log to the filter
+  fn accrue_log(&mut self, log: &Log);
+
+  /// Adds the address and topics of all logs to the filter
+  fn accrue_logs<'a>(&mut self, logs: impl IntoIterator<Item = &'a Log>);
+
+  /// False if the log was definitely not accrued into the filter.
+  fn contains_log(&self, log: &Log) -> bool;
+
+  /// False if no log emitted by the address was accrued into the filter.
+  fn contains_address(&self, address: &Address) -> bool;
+
+  /// False if no log with the topic was accrued into the filter.
+  fn contains_topic(&self, topic: &H256) -> bool;
+}
+
+impl LogsBloom for Bloom {
+  fn accrue_log(&mut self, log: &Log) {
+    self.accrue(BloomInput::Raw(log.address.as_bytes()));
+    for topic in &log.topics {
+      self.accrue(BloomInput::Raw(topic.as_bytes()));
+    }
+  }
+
+  fn accrue_logs<'a>(&mut self, logs: impl IntoIterator<Item = &'a Log>) {
+    for log in logs {
+      self.accrue_log(log);
+    }
+  }
+
+  fn contains_log(&self, log: &Log) -> bool {
+    self.contains_bloom(&log.bloom())
+  }
+
+  fn contains_address(&self, address: &Address) -> bool {
+    self.contains_input(BloomInput::Raw(address.as_bytes()))
+  }
+
+  fn contains_topic(&self, topic: &H256) -> bool {
+    self.contains_input(BloomInput::Raw(topic.as_bytes()))
+  }
+}
+
+/// The result of executing a transaction as committed to by its receipt
+#[derive(Debug, Eq, Clone, PartialEq)]
+pub enum TransactionOutcome {
+  /// Receipts before Byzantium hold the state root after the transaction
+  StateRoot(H256),
+
+  /// https://eips.ethereum.org/EIPS/eip-658
+  Status(bool),
+}
+
+impl Default for TransactionOutcome {
+  fn default() -> Self {
+    TransactionOutcome::Status(false)
+  }
+}
+
+/// https://ethereum.stackexchange.com/questions/268/ethereum-block-architecture
+#[derive(Default, Debug, Eq, Clone, PartialEq)]
+pub struct Receipt {
+  pub type_id: u8, // EIP-2718 type of the transaction, 0 for legacy
+  pub outcome: TransactionOutcome,
+  pub cumulative_gas_used: U256,
+  pub logs_bloom: Bloom,
+  pub logs: Vec<Log>,
+}
+
+impl Receipt {
+  /// Creates a receipt for a transaction of the given type,
+  /// accruing the bloom filter from its logs.
+  pub fn new(
+    type_id: u8,
+    outcome: TransactionOutcome,
+    cumulative_gas_used: U256,
+    logs: Vec<Log>,
+  ) -> Self {
+    let mut logs_bloom = Bloom::zero();
+    logs_bloom.accrue_logs(&logs);
+    Receipt {
+      type_id,
+      outcome,
+      cumulative_gas_used,
+      logs_bloom,
+      logs,
+    }
+  }
+
+  /// EIP-2718 encoding of the receipt. This is rlp(receipt) for legacy
+  /// transactions and type || rlp(receipt) for typed transactions.
+  pub fn encode(&self) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    match self.outcome {
+      TransactionOutcome::StateRoot(ref root) => stream.append(root),
+      TransactionOutcome::Status(true) => stream.append(&1u8),
+      TransactionOutcome::Status(false) => stream.append_empty_data(),
+    };
+    stream.append(&self.cumulative_gas_used);
+    stream.append(&self.logs_bloom);
+    stream.append_list(&self.logs);
+    match self.type_id {
+      0 => stream.as_raw().to_vec(),
+      type_id => [&[type_id], stream.as_raw()].concat(),
+    }
+  }
+
+  /// Decodes a receipt from its EIP-2718 encoding.
+  pub fn decode(bytes: &[u8]) -> Result<Self, DecoderError> {
+    let (type_id, payload) = match bytes.first() {
+      None => return Err(DecoderError::RlpIsTooShort),
+      Some(0xc0..=0xff) => (0, bytes),
+      // 0x00 is the type of legacy receipts, which are never enveloped
+      Some(&type_id @ 0x01..=0x7f) => (type_id, &bytes[1..]),
+      Some(_) => return Err(DecoderError::Custom("unknown receipt type")),
+    };
+
+    let rlp = Rlp::new(payload);
+    if rlp.payload_info()?.total() != payload.len() {
+      return Err(DecoderError::RlpIsTooBig);
+    }
+    Self::decode_payload(type_id, &rlp)
+  }
+
+  fn decode_payload(type_id: u8, rlp: &Rlp) -> Result<Self, DecoderError> {
+    if rlp.item_count()? != 4 {
+      return Err(DecoderError::RlpIncorrectListLen);
+    }
+
+    let outcome = rlp.at(0)?;
+    let outcome = match outcome.size() {
+      32 => TransactionOutcome::StateRoot(outcome.as_val()?),
+      _ => match outcome.as_val::<u8>()? {
+        0 => TransactionOutcome::Status(false),
+        1 => TransactionOutcome::Status(true),
+        _ => return Err(DecoderError::Custom("invalid receipt status")),
+      },
+    };
+
+    Ok(Receipt {
+      type_id,
+      outcome,
+      cumulative_gas_used: rlp.val_at(1)?,
+      logs_bloom: rlp.val_at(2)?,
+      logs: rlp.list_at(3)?,
+    })
+  }
+}
+
+impl Serialize for Receipt {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    use oe4_rlp::with::{encodable, raw};
+    match self.type_id {
+      // legacy receipts are an rlp list on their own
+      0 => raw::serialize(&self.encode(), serializer),
+      // typed receipts are embedded in network messages
+      // as an rlp string holding their envelope.
+      _ => encodable::serialize(&self.encode(), serializer),
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for Receipt {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+      type Value = Receipt;
+
+      fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "an rlp encoded legacy or typed receipt")
+      }
+
+      fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+      where
+        E: serde::de::Error,
+      {
+        let rlp = Rlp::new(v);
+        let envelope = match rlp.is_list() {
+          true => v,
+          false => rlp.data().map_err(E::custom)?,
+        };
+        Receipt::decode(envelope).map_err(E::custom)
+      }
+    }
+
+    deserializer.deserialize_bytes(Visitor)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hex_literal::hex;
+
+  /// Receipt of mainnet transaction 0x21f6554c28453a01e7276c1db2fc1695bb512b170818bfa98fa8136433100616
+  fn usdt_approval() -> Receipt {
+    Receipt::new(
+      2,
+      TransactionOutcome::Status(true),
+      0xa42aec.into(),
+      vec![Log {
+        address: hex!("dac17f958d2ee523a2206206994597c13d831ec7").into(),
+        topics: vec![
+          hex!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925").into(),
+          hex!("0000000000000000000000009a53bfba35269414f3b2d20b52ca01b15932c7b2").into(),
+          hex!("00000000000000000000000039e5dbb9d2fead31234d7c647d6ce77d85826f76").into(),
+        ],
+        data: hex!("00000000000000000000000000000000000000000052b7d2dcc80cd2e4000000").to_vec(),
+      }],
+    )
+  }
+
+  /// Receipt of mainnet transaction 0xea1093d492a1dcb1bef708f771a99a96ff05dcab81ca76c31940300177fcf49f
+  fn pre_byzantium() -> Receipt {
+    Receipt::new(
+      0,
+      TransactionOutcome::StateRoot(
+        hex!("284d35bf53b82ef480ab4208527325477439c64fb90ef518450f05ee151c8e10").into(),
+      ),
+      0x723c.into(),
+      vec![Log {
+        address: hex!("c083e9947cf02b8ffc7d3090ae9aea72df98fd47").into(),
+        topics: vec![
+          hex!("e1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c").into(),
+        ],
+        data: hex!(
+          "00000000000000000000000039fa8c5f2793459d6622857e7d9fbb4bd91766d3"
+          "0000000000000000000000000000000000000000000000056bc75e2d63100000"
+        )
+        .to_vec(),
+      }],
+    )
+  }
+
+  #[test]
+  fn mainnet_blooms() {
+    let expected: Bloom = hex!(
+      "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000200000"
+      "00000000000004000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000040000080000"
+      "00000000000000000000000000000040000000000000000008000000001000000200000000000000000000800000000000000000000000000000000000000000"
+      "00000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000010000000000000000000000000000"
+    )
+    .into();
+    assert_eq!(usdt_approval().logs_bloom, expected);
+
+    let expected: Bloom = hex!(
+      "00000000000000000000000000000000000800000000000000000000000800000000000000000400000000000000000000000000000000000000000000000000"
+      "00000000000000000000000000000000000000000000000000000000800000000000000000000000000000000000000000000000000000000000000000000000"
+      "00000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+      "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000400000000000000000"
+    )
+    .into();
+    assert_eq!(pre_byzantium().logs_bloom, expected);
+  }
+
+  #[test]
+  fn bloom_queries() {
+    let receipt = usdt_approval();
+    let log = &receipt.logs[0];
+
+    assert!(receipt.logs_bloom.contains_log(log));
+    assert!(receipt.logs_bloom.contains_address(&log.address));
+    for topic in &log.topics {
+      assert!(receipt.logs_bloom.contains_topic(topic));
+    }
+
+    let other = &pre_byzantium().logs[0];
+    assert!(!receipt.logs_bloom.contains_log(other));
+    assert!(!receipt.logs_bloom.contains_address(&other.address));
+    assert!(!receipt.logs_bloom.contains_topic(&other.topics[0]));
+
+    // a block bloom is the union of its receipts blooms
+    let mut block = Bloom::zero();
+    block.accrue_logs(usdt_approval().logs.iter().chain(&pre_byzantium().logs));
+    assert!(block.contains_bloom(&receipt.logs_bloom));
+    assert!(block.contains_log(other));
+  }
+
+  #[test]
+  fn encoding_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    for receipt in [usdt_approval(), pre_byzantium(), Receipt::default()].iter() {
+      let encoded = receipt.encode();
+      match receipt.type_id {
+        0 => assert!(encoded[0] >= 0xc0),
+        type_id => assert_eq!(encoded[0], type_id),
+      }
+      assert_eq!(&Receipt::decode(&encoded)?, receipt);
+
+      let rlp_encoded = crate::rlp_serialize(receipt)?;
+      let decoded: Receipt = crate::rlp_deserialize(&rlp_encoded)?;
+      assert_eq!(&decoded, receipt);
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn status_encoding() {
+    let mut receipt = Receipt::default();
+    assert_eq!(Rlp::new(&receipt.encode()).at(0).unwrap().as_raw(), &[0x80]);
+    receipt.outcome = TransactionOutcome::Status(true);
+    assert_eq!(Rlp::new(&receipt.encode()).at(0).unwrap().as_raw(), &[0x01]);
+
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&2u8);
+    stream.append(&0u8);
+    stream.append(&Bloom::zero());
+    stream.begin_list(0);
+    assert_eq!(
+      Receipt::decode(stream.as_raw()),
+      Err(DecoderError::Custom("invalid receipt status"))
+    );
+  }
+
+  #[test]
+  fn malformed_envelopes() {
+    for encoded in [pre_byzantium().encode(), usdt_approval().encode()] {
+      assert!(Receipt::decode(&encoded).is_ok());
+      let padded = [&encoded[..], &[0x80]].concat();
+      assert_eq!(Receipt::decode(&padded), Err(DecoderError::RlpIsTooBig));
+    }
+
+    let legacy = pre_byzantium().encode();
+    assert_eq!(
+      Receipt::decode(&[&[0x00], &legacy[..]].concat()),
+      Err(DecoderError::Custom("unknown receipt type"))
+    );
+    assert_eq!(Receipt::decode(&[0x02]), Err(DecoderError::RlpIsTooShort));
+  }
+}
@@ -1,68 +1,386 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use crate::{Address, H256, Keccak, U256};
+use crate::{Address, Keccak, PublicKey, Signer, H256, U256};
 use keccak_hash::keccak;
-use rlp::RlpStream;
-use serde::{Deserialize, Serialize, ser::SerializeSeq};
+use rlp::{DecoderError, Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Errors that could occur while recovering the signer of a transaction
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SignatureError {
+  /// The V field is neither 27/28 (pre EIP-155), chain_id * 2 + 35/36
+  /// nor a y parity of 0/1 for typed transactions.
+  InvalidV(u64),
+  /// The R or S fields are zero or do not fit on the curve
+  InvalidRS,
+  /// An encoded signature is not 65 bytes long
+  InvalidLength(usize),
+  /// The chain id is too large to be mixed into V (EIP-155)
+  InvalidChainId(u64),
+  /// Error reported by the underlying secp256k1 library
+  Secp256k1(secp256k1::Error),
+}
+
+impl std::fmt::Display for SignatureError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self, f)
+  }
+}
+
+impl std::error::Error for SignatureError {}
+
+impl From<secp256k1::Error> for SignatureError {
+  fn from(e: secp256k1::Error) -> Self {
+    SignatureError::Secp256k1(e)
+  }
+}
 
 /// Components that constitute transaction signature
 #[derive(Default, Debug, Eq, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Signature {
-  pub v: u64, // The V field of the signature; which half of the curve our point falls in. either 27 or 28.
+  pub v: u64, // The V field of the signature; which half of the curve our point falls in. 27/28 or 35/36 + chain_id * 2 for legacy, 0/1 for typed transactions.
   pub r: U256, // The R field of the signature; helps describe the point on the curve.
   pub s: U256, // The S field of the signature; helps describe the point on the curve.
 }
 
+/// A single entry of an EIP-2930 access list
+#[derive(Default, Debug, Eq, Clone, PartialEq)]
+pub struct AccessListItem {
+  pub address: Address,
+  pub storage_keys: Vec<H256>,
+}
+
+/// https://eips.ethereum.org/EIPS/eip-2930
+pub type AccessList = Vec<AccessListItem>;
+
+impl rlp::Encodable for AccessListItem {
+  fn rlp_append(&self, s: &mut RlpStream) {
+    s.begin_list(2);
+    s.append(&self.address);
+    s.append_list(&self.storage_keys);
+  }
+}
+
+impl rlp::Decodable for AccessListItem {
+  fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+    if rlp.item_count()? != 2 {
+      return Err(DecoderError::RlpIncorrectListLen);
+    }
+    Ok(AccessListItem {
+      address: rlp.val_at(0)?,
+      storage_keys: rlp.list_at(1)?,
+    })
+  }
+}
+
+/// Fields that differ between the transaction types defined
+/// through the EIP-2718 typed transaction envelope.
+#[derive(Debug, Eq, Clone, PartialEq)]
+pub enum TransactionKind {
+  /// The original transaction format, optionally replay protected
+  /// by mixing the chain id into V according to EIP-155.
+  Legacy { gas_price: U256 },
+
+  /// Type 0x01, https://eips.ethereum.org/EIPS/eip-2930
+  AccessList {
+    gas_price: U256,
+    access_list: AccessList,
+  },
+
+  /// Type 0x02, https://eips.ethereum.org/EIPS/eip-1559
+  DynamicFee {
+    max_priority_fee_per_gas: U256,
+    max_fee_per_gas: U256,
+    access_list: AccessList,
+  },
+}
+
+impl TransactionKind {
+  /// The EIP-2718 transaction type byte. Legacy transactions
+  /// have no type byte in their encoding and report 0.
+  pub fn type_id(&self) -> u8 {
+    match self {
+      TransactionKind::Legacy { .. } => 0x00,
+      TransactionKind::AccessList { .. } => 0x01,
+      TransactionKind::DynamicFee { .. } => 0x02,
+    }
+  }
+}
+
+impl Default for TransactionKind {
+  fn default() -> Self {
+    TransactionKind::Legacy {
+      gas_price: U256::zero(),
+    }
+  }
+}
+
 /// https://ethereum.stackexchange.com/questions/268/ethereum-block-architecture
 #[derive(Default, Debug, Eq, Clone, PartialEq)]
 pub struct Transaction {
+  pub kind: TransactionKind,
+  pub chain_id: Option<u64>, // required for typed transactions, optional (EIP-155) for legacy
   pub nonce: U256,
-  pub gas_price: U256,
   pub gas_limit: U256,
-  pub recipient: Address,
+  pub recipient: Option<Address>, // None for contract creation
   pub value: U256,
   pub data: Vec<u8>,
   pub signature: Signature,
 }
 
 impl Transaction {
-  pub fn new(
+  pub fn new<S: Signer>(
+    kind: TransactionKind,
     nonce: U256,
-    gas_price: U256,
     gas_limit: U256,
-    recipient: Address,
+    recipient: Option<Address>,
     value: U256,
     data: Vec<u8>,
     chain_id: u64,
-    secret: H256,
-  ) -> Result<Self, secp256k1::Error> {
-    
-    let mut enc_stream = RlpStream::new();
-    enc_stream.begin_list(9);
-    enc_stream.append(&nonce);
-    enc_stream.append(&gas_price);
-    enc_stream.append(&gas_limit);
-    enc_stream.append(&recipient);
-    enc_stream.append(&value);
-    enc_stream.append(&data);
-    enc_stream.append(&chain_id);
-    enc_stream.append(&0u64);
-    enc_stream.append(&0u64);
-
-    let rlp = enc_stream.as_raw();
-    let rlp_hash = keccak(&rlp).to_fixed_bytes();
-
-    Ok(Transaction {
+    signer: &S,
+  ) -> Result<Self, S::Error> {
+    let mut tx = Transaction {
+      kind,
+      chain_id: Some(chain_id),
       nonce,
-      gas_price,
       gas_limit,
       recipient,
       value,
       data,
-      signature: Signature::new(&keccak(rlp_hash), &secret, chain_id)?,
+      signature: Signature::default(),
+    };
+    tx.sign(signer)?;
+    Ok(tx)
+  }
+
+  /// Signs the transaction with the given signer, replacing any existing
+  /// signature. V is encoded according to the transaction type.
+  pub fn sign<S: Signer>(&mut self, signer: &S) -> Result<(), S::Error> {
+    let signature = signer.sign_hash(&self.signing_hash())?;
+    self.signature = match self.kind {
+      TransactionKind::Legacy { .. } => signature.to_legacy(self.chain_id)?,
+      _ => signature,
+    };
+    Ok(())
+  }
+
+  /// The EIP-2718 transaction type byte, 0 for legacy transactions.
+  pub fn type_id(&self) -> u8 {
+    self.kind.type_id()
+  }
+
+  /// The maximum price per unit of gas the sender is willing to pay.
+  pub fn max_fee_per_gas(&self) -> U256 {
+    match self.kind {
+      TransactionKind::Legacy { gas_price } => gas_price,
+      TransactionKind::AccessList { gas_price, .. } => gas_price,
+      TransactionKind::DynamicFee {
+        max_fee_per_gas, ..
+      } => max_fee_per_gas,
+    }
+  }
+
+  /// The maximum price per unit of gas paid to the block producer on top of
+  /// the base fee. Pre EIP-1559 transactions pay their entire gas price.
+  pub fn max_priority_fee_per_gas(&self) -> U256 {
+    match self.kind {
+      TransactionKind::DynamicFee {
+        max_priority_fee_per_gas,
+        ..
+      } => max_priority_fee_per_gas,
+      _ => self.max_fee_per_gas(),
+    }
+  }
+
+  /// The hash that gets signed by the sender. For legacy transactions with a
+  /// chain id the transaction is replay protected according to EIP-155, typed
+  /// transactions always sign keccak(type || rlp(payload)).
+  pub fn signing_hash(&self) -> Keccak {
+    let mut stream = RlpStream::new();
+    match self.kind {
+      TransactionKind::Legacy { .. } => match self.chain_id {
+        Some(chain_id) => {
+          stream.begin_list(9);
+          self.append_payload(&mut stream);
+          stream.append(&chain_id);
+          stream.append(&0u64);
+          stream.append(&0u64);
+        }
+        None => {
+          stream.begin_list(6);
+          self.append_payload(&mut stream);
+        }
+      },
+      TransactionKind::AccessList { .. } => {
+        stream.begin_list(8);
+        self.append_payload(&mut stream);
+      }
+      TransactionKind::DynamicFee { .. } => {
+        stream.begin_list(9);
+        self.append_payload(&mut stream);
+      }
+    }
+    keccak(self.envelope(stream.as_raw()))
+  }
+
+  /// EIP-2718 encoding of the signed transaction. This is rlp(tx) for
+  /// legacy transactions and type || rlp(payload) for typed transactions.
+  pub fn encode(&self) -> Vec<u8> {
+    let mut stream = RlpStream::new();
+    stream.begin_list(match self.kind {
+      TransactionKind::Legacy { .. } => 9,
+      TransactionKind::AccessList { .. } => 11,
+      TransactionKind::DynamicFee { .. } => 12,
+    });
+    self.append_payload(&mut stream);
+    stream.append(&self.signature.v);
+    stream.append(&self.signature.r);
+    stream.append(&self.signature.s);
+    self.envelope(stream.as_raw())
+  }
+
+  /// Decodes a signed transaction from its EIP-2718 encoding. Like any
+  /// other rlp item, the transaction has to span all of `bytes`.
+  pub fn decode(bytes: &[u8]) -> Result<Self, DecoderError> {
+    let (type_id, payload) = match bytes.first() {
+      None => return Err(DecoderError::RlpIsTooShort),
+      Some(0xc0..=0xff) => (None, bytes),
+      Some(&type_id) => (Some(type_id), &bytes[1..]),
+    };
+
+    let rlp = Rlp::new(payload);
+    if rlp.payload_info()?.total() != payload.len() {
+      return Err(DecoderError::RlpIsTooBig);
+    }
+    match type_id {
+      None => Self::decode_legacy(&rlp),
+      Some(type_id) => Self::decode_typed(type_id, &rlp),
+    }
+  }
+
+  fn decode_legacy(rlp: &Rlp) -> Result<Self, DecoderError> {
+    if rlp.item_count()? != 9 {
+      return Err(DecoderError::RlpIncorrectListLen);
+    }
+    let signature = Signature {
+      v: rlp.val_at(6)?,
+      r: rlp.val_at(7)?,
+      s: rlp.val_at(8)?,
+    };
+    Ok(Transaction {
+      kind: TransactionKind::Legacy {
+        gas_price: rlp.val_at(1)?,
+      },
+      chain_id: signature.chain_id(),
+      nonce: rlp.val_at(0)?,
+      gas_limit: rlp.val_at(2)?,
+      recipient: decode_recipient(&rlp.at(3)?)?,
+      value: rlp.val_at(4)?,
+      data: rlp.val_at(5)?,
+      signature,
+    })
+  }
+
+  fn decode_typed(type_id: u8, rlp: &Rlp) -> Result<Self, DecoderError> {
+    let (kind, offset) = match type_id {
+      0x01 => (
+        TransactionKind::AccessList {
+          gas_price: rlp.val_at(2)?,
+          access_list: rlp.list_at(7)?,
+        },
+        0,
+      ),
+      0x02 => (
+        TransactionKind::DynamicFee {
+          max_priority_fee_per_gas: rlp.val_at(2)?,
+          max_fee_per_gas: rlp.val_at(3)?,
+          access_list: rlp.list_at(8)?,
+        },
+        1,
+      ),
+      _ => return Err(DecoderError::Custom("unknown transaction type")),
+    };
+
+    if rlp.item_count()? != 11 + offset {
+      return Err(DecoderError::RlpIncorrectListLen);
+    }
+
+    Ok(Transaction {
+      kind,
+      chain_id: Some(rlp.val_at(0)?),
+      nonce: rlp.val_at(1)?,
+      gas_limit: rlp.val_at(3 + offset)?,
+      recipient: decode_recipient(&rlp.at(4 + offset)?)?,
+      value: rlp.val_at(5 + offset)?,
+      data: rlp.val_at(6 + offset)?,
+      signature: Signature {
+        v: rlp.val_at(8 + offset)?,
+        r: rlp.val_at(9 + offset)?,
+        s: rlp.val_at(10 + offset)?,
+      },
     })
   }
+
+  /// Appends the unsigned fields in the order specified by the transaction type
+  fn append_payload(&self, stream: &mut RlpStream) {
+    match self.kind {
+      TransactionKind::Legacy { ref gas_price } => {
+        stream.append(&self.nonce);
+        stream.append(gas_price);
+        self.append_common(stream);
+      }
+      TransactionKind::AccessList {
+        ref gas_price,
+        ref access_list,
+      } => {
+        stream.append(&self.chain_id.unwrap_or_default());
+        stream.append(&self.nonce);
+        stream.append(gas_price);
+        self.append_common(stream);
+        stream.append_list(access_list);
+      }
+      TransactionKind::DynamicFee {
+        ref max_priority_fee_per_gas,
+        ref max_fee_per_gas,
+        ref access_list,
+      } => {
+        stream.append(&self.chain_id.unwrap_or_default());
+        stream.append(&self.nonce);
+        stream.append(max_priority_fee_per_gas);
+        stream.append(max_fee_per_gas);
+        self.append_common(stream);
+        stream.append_list(access_list);
+      }
+    }
+  }
+
+  fn append_common(&self, stream: &mut RlpStream) {
+    stream.append(&self.gas_limit);
+    match self.recipient {
+      Some(ref recipient) => stream.append(recipient),
+      None => stream.append_empty_data(),
+    };
+    stream.append(&self.value);
+    stream.append(&self.data);
+  }
+
+  /// prefixes typed transactions payloads with their type byte
+  fn envelope(&self, payload: &[u8]) -> Vec<u8> {
+    match self.kind {
+      TransactionKind::Legacy { .. } => payload.to_vec(),
+      _ => [&[self.type_id()], payload].concat(),
+    }
+  }
+}
+
+/// Contract creations leave the recipient empty
+fn decode_recipient(rlp: &Rlp) -> Result<Option<Address>, DecoderError> {
+  match rlp.is_data() && rlp.is_empty() {
+    true => Ok(None),
+    false => rlp.as_val().map(Some),
+  }
 }
 
 impl Serialize for Transaction {
@@ -70,66 +388,188 @@ impl Serialize for Transaction {
   where
     S: serde::Serializer,
   {
-    use crate::primitive::RlpSer;
-    let mut ser = serializer.serialize_seq(Some(9))?;
-    ser.serialize_element(&RlpSer::wrap(self.nonce))?;
-    ser.serialize_element(&RlpSer::wrap(self.gas_price))?;
-    ser.serialize_element(&RlpSer::wrap(self.gas_limit))?;
-    ser.serialize_element(&RlpSer::wrap(self.recipient))?;
-    ser.serialize_element(&RlpSer::wrap(self.value))?;
-    ser.serialize_element(&RlpSer::wrap(&self.data[..]))?;
-    ser.serialize_element(&self.signature.v)?;
-    ser.serialize_element(&RlpSer::wrap(self.signature.r))?;
-    ser.serialize_element(&RlpSer::wrap(self.signature.s))?;
-    ser.end()
+    use oe4_rlp::with::{encodable, raw};
+    match self.kind {
+      // legacy transactions are an rlp list on their own
+      TransactionKind::Legacy { .. } => raw::serialize(&self.encode(), serializer),
+      // typed transactions are embedded in blocks and network
+      // messages as an rlp string holding their envelope.
+      _ => encodable::serialize(&self.encode(), serializer),
+    }
   }
 }
 
 impl<'de> Deserialize<'de> for Transaction {
-  fn deserialize<D>(_deserializer: D) -> Result<Self, D::Error>
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
   where
     D: serde::Deserializer<'de>,
   {
-    todo!()
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+      type Value = Transaction;
+
+      fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "an rlp encoded legacy or typed transaction")
+      }
+
+      fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+      where
+        E: serde::de::Error,
+      {
+        let rlp = Rlp::new(v);
+        let envelope = match rlp.is_list() {
+          true => v,
+          false => rlp.data().map_err(E::custom)?,
+        };
+        Transaction::decode(envelope).map_err(E::custom)
+      }
+    }
+
+    deserializer.deserialize_bytes(Visitor)
   }
 }
 
 impl Transaction {
-  pub fn sender(&self) -> Address {
-    todo!(); // recover
+  /// Recovers the address of the account that signed this transaction.
+  pub fn sender(&self) -> Result<Address, SignatureError> {
+    match self.kind {
+      TransactionKind::Legacy { .. } => {
+        if self.signature.v < 27 || self.signature.chain_id() != self.chain_id {
+          return Err(SignatureError::InvalidV(self.signature.v));
+        }
+      }
+      _ => {
+        if self.signature.v > 1 {
+          return Err(SignatureError::InvalidV(self.signature.v));
+        }
+      }
+    }
+    self.signature.recover(&self.signing_hash())
   }
 
+  /// keccak of the EIP-2718 encoding, the identifier of the transaction on
+  /// the network.
   pub fn hash(&self) -> Keccak {
-    todo!(); // keccak(rlp(tx))
+    keccak(self.encode())
   }
 }
 
 impl Signature {
-  pub fn new(hash: &Keccak, secret: &H256, chain_id: u64) -> Result<Self, secp256k1::Error> {
+  /// Signs the given hash. V holds the bare recovery id (y parity) as used by
+  /// typed transactions, see [Signature::to_legacy] for legacy transactions.
+  pub fn new(hash: &Keccak, secret: &H256) -> Result<Self, secp256k1::Error> {
     let ctx = secp256k1::Secp256k1::new();
     let message = secp256k1::Message::from(*hash.as_fixed_bytes());
     let secret = secp256k1::key::SecretKey::from(*secret.as_fixed_bytes());
     let s = ctx.sign_recoverable(&message, &secret)?;
-    let (recovery_id, sigdata) = s.serialize_compact(&secp256k1::Secp256k1::new());
+    let (recovery_id, sigdata) = s.serialize_compact(&ctx);
     Ok(Self {
-      v: recovery_id.to_i32() as u64 + 35 + chain_id * 2,
+      v: recovery_id.to_i32() as u64,
       r: U256::from(&sigdata[0..32]),
       s: U256::from(&sigdata[32..64]),
     })
   }
+
+  /// Encodes the recovery id into V the way legacy transactions expect it,
+  /// 27/28 without a chain id and chain_id * 2 + 35/36 with one (EIP-155).
+  pub fn to_legacy(&self, chain_id: Option<u64>) -> Result<Self, SignatureError> {
+    let parity = self.recovery_id()? as u64;
+    let v = match chain_id {
+      Some(chain_id) => chain_id
+        .checked_mul(2)
+        .and_then(|v| v.checked_add(35 + parity))
+        .ok_or(SignatureError::InvalidChainId(chain_id))?,
+      None => parity + 27,
+    };
+    Ok(Self { v, ..self.clone() })
+  }
+
+  /// The chain id encoded in V for EIP-155 replay protected
+  /// signatures, or None for legacy signatures (v = 27 or 28).
+  pub fn chain_id(&self) -> Option<u64> {
+    match self.v {
+      v if v >= 35 => Some((v - 35) / 2),
+      _ => None,
+    }
+  }
+
+  /// The secp256k1 recovery id (0 or 1) encoded in V
+  pub fn recovery_id(&self) -> Result<u8, SignatureError> {
+    match self.v {
+      0 | 1 => Ok(self.v as u8),
+      27 | 28 => Ok((self.v - 27) as u8),
+      v if v >= 35 => Ok(((v - 35) % 2) as u8),
+      v => Err(SignatureError::InvalidV(v)),
+    }
+  }
+
+  /// The 65 byte r || s || v encoding used by wallets and `ecrecover` based
+  /// contracts. Fails for EIP-155 signatures whose V doesn't fit in a byte.
+  pub fn to_bytes(&self) -> Result<[u8; 65], SignatureError> {
+    let mut bytes = [0u8; 65];
+    self.r.to_big_endian(&mut bytes[0..32]);
+    self.s.to_big_endian(&mut bytes[32..64]);
+    bytes[64] = u8::try_from(self.v).map_err(|_| SignatureError::InvalidV(self.v))?;
+    Ok(bytes)
+  }
+
+  /// Parses the 65 byte r || s || v encoding, V is either
+  /// the bare recovery id or 27/28.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, SignatureError> {
+    if bytes.len() != 65 {
+      return Err(SignatureError::InvalidLength(bytes.len()));
+    }
+    let signature = Self {
+      v: bytes[64] as u64,
+      r: U256::from(&bytes[0..32]),
+      s: U256::from(&bytes[32..64]),
+    };
+    signature.recovery_id()?;
+    Ok(signature)
+  }
+
+  /// Recovers the address of the account that produced this
+  /// signature over the given hash.
+  pub fn recover(&self, hash: &Keccak) -> Result<Address, SignatureError> {
+    if self.r.is_zero() || self.s.is_zero() {
+      return Err(SignatureError::InvalidRS);
+    }
+
+    let mut sigdata = [0u8; 64];
+    self.r.to_big_endian(&mut sigdata[0..32]);
+    self.s.to_big_endian(&mut sigdata[32..64]);
+
+    let ctx = secp256k1::Secp256k1::new();
+    let recovery_id = secp256k1::RecoveryId::from_i32(self.recovery_id()? as i32)?;
+    let signature = secp256k1::RecoverableSignature::from_compact(&ctx, &sigdata, recovery_id)
+      .map_err(|_| SignatureError::InvalidRS)?;
+    let message = secp256k1::Message::from(*hash.as_fixed_bytes());
+    let public = ctx.recover(&message, &signature)?;
+    Ok(PublicKey(public).address())
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::SecretKey;
+  use hex_literal::hex;
+
+  const SECRET_ONE: [u8; 32] =
+    hex!("0000000000000000000000000000000000000000000000000000000000000001");
+  const SECRET_ONE_ADDRESS: [u8; 20] = hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf");
 
   #[test]
-  fn test_rlp_serialization() {
-    let _tx = Transaction {
+  fn test_rlp_serialization() -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = Transaction {
+      kind: TransactionKind::Legacy {
+        gas_price: U256::zero(),
+      },
+      chain_id: None,
       nonce: U256::zero(),
-      gas_price: U256::zero(),
       gas_limit: U256::zero(),
-      recipient: Address::zero(),
+      recipient: Some(Address::zero()),
       value: U256::zero(),
       signature: Signature {
         v: 27,
@@ -139,8 +579,416 @@ mod tests {
       data: [0, 1, 2].into(),
     };
 
-    //let rlp_encoded = rlp::encode(&tx);
-    //let decoded: Transaction = rlp::decode(&rlp_encoded).unwrap();
-    //assert_eq!(decoded, tx);
+    let rlp_encoded = crate::rlp_serialize(&tx)?;
+    assert_eq!(rlp_encoded, tx.encode());
+    let decoded: Transaction = crate::rlp_deserialize(&rlp_encoded)?;
+    assert_eq!(decoded, tx);
+
+    // contract creation
+    tx.recipient = None;
+    let rlp_encoded = crate::rlp_serialize(&tx)?;
+    assert_eq!(Rlp::new(&rlp_encoded).at(3)?.as_raw(), &[0x80]);
+    let decoded: Transaction = crate::rlp_deserialize(&rlp_encoded)?;
+    assert_eq!(decoded, tx);
+
+    Ok(())
+  }
+
+  #[test]
+  fn signed_rlp_serialization() -> Result<(), Box<dyn std::error::Error>> {
+    let tx = Transaction::new(
+      TransactionKind::Legacy {
+        gas_price: 2000000000.into(),
+      },
+      690.into(),
+      21000.into(),
+      Some(hex!("4592d8f8d7b001e72cb26a73e4fa1806a51ac79d").into()),
+      100.into(),
+      Vec::new(),
+      1,
+      &SecretKey::from_slice(&SECRET_ONE)?,
+    )?;
+
+    let rlp_encoded = crate::rlp_serialize(&tx)?;
+    assert_eq!(keccak(&rlp_encoded), tx.hash());
+    assert_eq!(tx.sender()?, SECRET_ONE_ADDRESS.into());
+
+    Ok(())
+  }
+
+  #[test]
+  fn mainnet_transactions() -> Result<(), Box<dyn std::error::Error>> {
+    let fixtures = [
+      (
+        include_str!("../res/mainnet/tx_bb3a336e.hex"),
+        hex!("bb3a336e3f823ec18197f1e13ee875700f08f03e2cab75f0d0b118dabb44cba0"),
+        hex!("398137383b3d25c92898c656696e41950e47316b"),
+      ),
+      (
+        include_str!("../res/mainnet/tx_280cde7c.hex"),
+        hex!("280cde7cdefe4b188750e76c888f13bd05ce9a4d7767730feefe8a0e50ca6fc4"),
+        hex!("a12e1462d0ced572f396f58b6e2d03894cd7c8a4"),
+      ),
+      (
+        include_str!("../res/mainnet/tx_0ec0b6a2.hex"),
+        hex!("0ec0b6a2df4d87424e5f6ad2a654e27aaeb7dac20ae9e8385cc09087ad532ee0"),
+        hex!("dd6b8b3dc6b7ad97db52f08a275ff4483e024cea"),
+      ),
+      (
+        include_str!("../res/mainnet/tx_ce4dc6d7.hex"),
+        hex!("ce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31"),
+        hex!("001e2b7de757ba469a57bf6b23d982458a07efce"),
+      ),
+    ];
+
+    for (fixture, hash, sender) in fixtures.iter() {
+      let encoded = hex::decode(fixture.trim())?;
+      let tx = Transaction::decode(&encoded)?;
+      assert_eq!(tx.chain_id, Some(1));
+      assert_eq!(tx.hash(), hash.into());
+      assert_eq!(tx.sender()?, sender.into());
+      assert_eq!(tx.encode(), encoded);
+
+      // within blocks typed transactions are wrapped in an rlp string
+      let rlp_encoded = crate::rlp_serialize(&tx)?;
+      match tx.kind {
+        TransactionKind::Legacy { .. } => assert_eq!(rlp_encoded, encoded),
+        _ => assert_eq!(rlp_encoded, rlp::encode(&encoded).to_vec()),
+      }
+      let decoded: Transaction = crate::rlp_deserialize(&rlp_encoded)?;
+      assert_eq!(decoded, tx);
+    }
+
+    Ok(())
+  }
+
+  /// The example from the EIP-155 specification
+  fn eip155_example() -> Transaction {
+    Transaction {
+      kind: TransactionKind::Legacy {
+        gas_price: 20_000_000_000u64.into(),
+      },
+      chain_id: Some(1),
+      nonce: 9.into(),
+      gas_limit: 21000.into(),
+      recipient: Some(hex!("3535353535353535353535353535353535353535").into()),
+      value: 1_000_000_000_000_000_000u64.into(),
+      data: vec![],
+      signature: Signature {
+        v: 37,
+        r: hex!("28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276").into(),
+        s: hex!("67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").into(),
+      },
+    }
+  }
+
+  #[test]
+  fn eip155_signing_hash() {
+    let tx = eip155_example();
+    assert_eq!(
+      tx.signing_hash(),
+      hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53").into()
+    );
+  }
+
+  #[test]
+  fn eip155_sign_and_recover() -> Result<(), Box<dyn std::error::Error>> {
+    let expected = eip155_example();
+    let tx = Transaction::new(
+      expected.kind.clone(),
+      expected.nonce,
+      expected.gas_limit,
+      expected.recipient,
+      expected.value,
+      expected.data.clone(),
+      1,
+      &SecretKey::from_slice(&hex!(
+        "4646464646464646464646464646464646464646464646464646464646464646"
+      ))?,
+    )?;
+
+    // rfc6979 signatures are deterministic
+    assert_eq!(tx, expected);
+    assert_eq!(tx.signature.chain_id(), Some(1));
+    assert_eq!(
+      tx.sender()?,
+      hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").into()
+    );
+
+    let encoded = hex!(
+      "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000"
+      "8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f"
+      "761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+    );
+    assert_eq!(tx.encode(), encoded.to_vec());
+    assert_eq!(tx.hash(), keccak(&encoded[..]));
+    assert_eq!(Transaction::decode(&encoded)?, tx);
+    Ok(())
+  }
+
+  #[test]
+  fn legacy_sign_and_recover() -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = Transaction {
+      chain_id: None,
+      ..eip155_example()
+    };
+    tx.sign(&SecretKey::from_slice(&SECRET_ONE)?)?;
+
+    // pre EIP-155 signatures carry only the recovery id in v
+    assert!(tx.signature.v == 27 || tx.signature.v == 28);
+    assert_eq!(tx.signature.chain_id(), None);
+    assert_eq!(tx.sender()?, SECRET_ONE_ADDRESS.into());
+    assert_eq!(Transaction::decode(&tx.encode())?, tx);
+    Ok(())
+  }
+
+  #[test]
+  fn eip155_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    // https://github.com/ethereum/EIPs/blob/master/EIPS/eip-155.md
+    // (signed rlp, sender)
+    let vectors = [
+      (
+        &hex!("f864808504a817c800825208943535353535353535353535353535353535353535808025a0044852b2a670ade5407e78fb2863c51de9fcb96542a07186fe3aeda6bb8a116da0044852b2a670ade5407e78fb2863c51de9fcb96542a07186fe3aeda6bb8a116d")[..],
+        hex!("f0f6f18bca1b28cd68e4357452947e021241e9ce"),
+      ),
+      (
+        &hex!("f864018504a817c80182a410943535353535353535353535353535353535353535018025a0489efdaa54c0f20c7adf612882df0950f5a951637e0307cdcb4c672f298b8bcaa0489efdaa54c0f20c7adf612882df0950f5a951637e0307cdcb4c672f298b8bc6")[..],
+        hex!("23ef145a395ea3fa3deb533b8a9e1b4c6c25d112"),
+      ),
+      (
+        &hex!("f864028504a817c80282f618943535353535353535353535353535353535353535088025a02d7c5bef027816a800da1736444fb58a807ef4c9603b7848673f7e3a68eb14a5a02d7c5bef027816a800da1736444fb58a807ef4c9603b7848673f7e3a68eb14a5")[..],
+        hex!("2e485e0c23b4c3c542628a5f672eeab0ad4888be"),
+      ),
+      (
+        &hex!("f865038504a817c803830148209435353535353535353535353535353535353535351b8025a02a80e1ef1d7842f27f2e6be0972bb708b9a135c38860dbe73c27c3486c34f4e0a02a80e1ef1d7842f27f2e6be0972bb708b9a135c38860dbe73c27c3486c34f4de")[..],
+        hex!("82a88539669a3fd524d669e858935de5e5410cf0"),
+      ),
+      (
+        &hex!("f865048504a817c80483019a28943535353535353535353535353535353535353535408025a013600b294191fc92924bb3ce4b969c1e7e2bab8f4c93c3fc6d0a51733df3c063a013600b294191fc92924bb3ce4b969c1e7e2bab8f4c93c3fc6d0a51733df3c060")[..],
+        hex!("f9358f2538fd5ccfeb848b64a96b743fcc930554"),
+      ),
+      (
+        &hex!("f865058504a817c8058301ec309435353535353535353535353535353535353535357d8025a04eebf77a833b30520287ddd9478ff51abbdffa30aa90a8d655dba0e8a79ce0c1a04eebf77a833b30520287ddd9478ff51abbdffa30aa90a8d655dba0e8a79ce0c1")[..],
+        hex!("a8f7aba377317440bc5b26198a363ad22af1f3a4"),
+      ),
+      (
+        &hex!("f866068504a817c80683023e3894353535353535353535353535353535353535353581d88025a06455bf8ea6e7463a1046a0b52804526e119b4bf5136279614e0b1e8e296a4e2fa06455bf8ea6e7463a1046a0b52804526e119b4bf5136279614e0b1e8e296a4e2d")[..],
+        hex!("f1f571dc362a0e5b2696b8e775f8491d3e50de35"),
+      ),
+      (
+        &hex!("f867078504a817c807830290409435353535353535353535353535353535353535358201578025a052f1a9b320cab38e5da8a8f97989383aab0a49165fc91c737310e4f7e9821021a052f1a9b320cab38e5da8a8f97989383aab0a49165fc91c737310e4f7e9821021")[..],
+        hex!("d37922162ab7cea97c97a87551ed02c9a38b7332"),
+      ),
+      (
+        &hex!("f867088504a817c8088302e2489435353535353535353535353535353535353535358202008025a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c12a064b1702d9298fee62dfeccc57d322a463ad55ca201256d01f62b45b2e1c21c10")[..],
+        hex!("9bddad43f934d313c2b79ca28a432dd2b7281029"),
+      ),
+      (
+        &hex!("f867098504a817c809830334509435353535353535353535353535353535353535358202d98025a052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afba052f8f61201b2b11a78d6e866abc9c3db2ae8631fa656bfe5cb53668255367afb")[..],
+        hex!("3c24d7329e92f84f08556ceb6df1cdb0104ca49f"),
+      ),
+    ];
+
+    for (bytes, sender) in vectors.iter() {
+      let tx = Transaction::decode(bytes)?;
+
+      assert_eq!(tx.type_id(), 0);
+      assert_eq!(tx.chain_id, Some(1));
+      assert_eq!(tx.sender()?, Address::from(*sender));
+      assert_eq!(tx.hash(), keccak(bytes));
+      assert_eq!(&tx.encode()[..], *bytes);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn eip2930_encoding() -> Result<(), DecoderError> {
+    // go-ethereum core/types TestEIP2718TransactionEncode
+    let tx = Transaction {
+      kind: TransactionKind::AccessList {
+        gas_price: 1.into(),
+        access_list: vec![],
+      },
+      chain_id: Some(1),
+      nonce: 3.into(),
+      gas_limit: 25000.into(),
+      recipient: Some(hex!("b94f5374fce5edbc8e2a8697c15331677e6ebf0b").into()),
+      value: 10.into(),
+      data: hex!("5544").to_vec(),
+      signature: Signature {
+        v: 1,
+        r: hex!("c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660").into(),
+        s: hex!("32f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521").into(),
+      },
+    };
+
+    let encoded = hex!(
+      "01f8630103018261a894b94f5374fce5edbc8e2a8697c15331677e6ebf0b0a825544c001a0c9519f4f2b30"
+      "335884581971573fadf60c6204f59a911df35ee8a540456b2660a032f1e8e2c5dd761f9e4f88f41c8310ae"
+      "aba26a8bfcdacfedfa12ec3862d37521"
+    );
+
+    assert_eq!(tx.type_id(), 1);
+    assert_eq!(tx.encode(), encoded.to_vec());
+    assert_eq!(tx.hash(), keccak(&encoded[..]));
+    assert_eq!(Transaction::decode(&encoded)?, tx);
+    Ok(())
+  }
+
+  #[test]
+  fn typed_sign_and_recover() -> Result<(), Box<dyn std::error::Error>> {
+    let access_list = vec![AccessListItem {
+      address: hex!("de0b295669a9fd93d5f28d9ec85e40f4cb697bae").into(),
+      storage_keys: vec![
+        hex!("0000000000000000000000000000000000000000000000000000000000000003").into(),
+        hex!("0000000000000000000000000000000000000000000000000000000000000007").into(),
+      ],
+    }];
+
+    let kinds = vec![
+      TransactionKind::AccessList {
+        gas_price: 2_000_000_000u64.into(),
+        access_list: access_list.clone(),
+      },
+      TransactionKind::DynamicFee {
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+        max_fee_per_gas: 100_000_000_000u64.into(),
+        access_list,
+      },
+    ];
+
+    for kind in kinds {
+      let tx = Transaction::new(
+        kind,
+        7.into(),
+        50000.into(),
+        Some(hex!("4592d8f8d7b001e72cb26a73e4fa1806a51ac79d").into()),
+        100.into(),
+        hex!("deadbeef").to_vec(),
+        5,
+        &SecretKey::from_slice(&SECRET_ONE)?,
+      )?;
+
+      assert!(tx.signature.v <= 1);
+      assert_eq!(tx.signature.chain_id(), None);
+      assert_eq!(tx.sender()?, SECRET_ONE_ADDRESS.into());
+
+      let encoded = tx.encode();
+      assert_eq!(encoded[0], tx.type_id());
+      assert_eq!(tx.hash(), keccak(&encoded));
+
+      let decoded = Transaction::decode(&encoded)?;
+      assert_eq!(decoded, tx);
+      assert_eq!(decoded.chain_id, Some(5));
+      assert_eq!(decoded.sender()?, SECRET_ONE_ADDRESS.into());
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn fee_accessors() {
+    let legacy = eip155_example();
+    assert_eq!(legacy.max_fee_per_gas(), 20_000_000_000u64.into());
+    assert_eq!(legacy.max_priority_fee_per_gas(), 20_000_000_000u64.into());
+
+    let dynamic = Transaction {
+      kind: TransactionKind::DynamicFee {
+        max_priority_fee_per_gas: 2.into(),
+        max_fee_per_gas: 10.into(),
+        access_list: vec![],
+      },
+      ..Transaction::default()
+    };
+    assert_eq!(dynamic.max_fee_per_gas(), 10.into());
+    assert_eq!(dynamic.max_priority_fee_per_gas(), 2.into());
+  }
+
+  #[test]
+  fn unknown_transaction_type() {
+    assert_eq!(
+      Transaction::decode(&hex!("03c0")),
+      Err(DecoderError::Custom("unknown transaction type"))
+    );
+    assert_eq!(Transaction::decode(&[]), Err(DecoderError::RlpIsTooShort));
+  }
+
+  #[test]
+  fn trailing_bytes() {
+    let typed = Transaction {
+      kind: TransactionKind::AccessList {
+        gas_price: 1.into(),
+        access_list: vec![],
+      },
+      ..eip155_example()
+    };
+
+    for encoded in [eip155_example().encode(), typed.encode()] {
+      assert!(Transaction::decode(&encoded).is_ok());
+      let padded = [&encoded[..], &[0x80]].concat();
+      assert_eq!(Transaction::decode(&padded), Err(DecoderError::RlpIsTooBig));
+    }
+    assert_eq!(
+      Transaction::decode(&[0x01]),
+      Err(DecoderError::RlpIsTooShort)
+    );
+  }
+
+  #[test]
+  fn malformed_signatures() {
+    let mut tx = eip155_example();
+
+    tx.signature.v = 29;
+    assert_eq!(tx.sender(), Err(SignatureError::InvalidV(29)));
+
+    // chain id in v does not match the transaction
+    tx.signature.v = 39;
+    assert_eq!(tx.sender(), Err(SignatureError::InvalidV(39)));
+
+    tx.signature.v = 37;
+    tx.signature.r = U256::zero();
+    assert_eq!(tx.sender(), Err(SignatureError::InvalidRS));
+
+    tx.signature.r = U256::MAX;
+    assert_eq!(tx.sender(), Err(SignatureError::InvalidRS));
+
+    // typed transactions only carry the y parity
+    let mut typed = Transaction {
+      kind: TransactionKind::AccessList {
+        gas_price: 1.into(),
+        access_list: vec![],
+      },
+      ..eip155_example()
+    };
+    typed.signature.v = 27;
+    assert_eq!(typed.sender(), Err(SignatureError::InvalidV(27)));
+
+    let bytes = tx.signature.to_bytes().unwrap();
+    assert_eq!(
+      Signature::from_bytes(&bytes[..64]),
+      Err(SignatureError::InvalidLength(64))
+    );
+  }
+
+  #[test]
+  fn legacy_v_bounds() {
+    let mut signature = eip155_example().signature;
+    signature.v = 1;
+    assert_eq!(signature.to_legacy(Some(1)).map(|s| s.v), Ok(38));
+    assert_eq!(signature.to_legacy(None).map(|s| s.v), Ok(28));
+    assert_eq!(
+      signature.to_legacy(Some(u64::MAX / 2)),
+      Err(SignatureError::InvalidChainId(u64::MAX / 2))
+    );
+
+    signature.v = 29;
+    assert_eq!(
+      signature.to_legacy(Some(1)),
+      Err(SignatureError::InvalidV(29))
+    );
+
+    // chain ids above 110 don't fit in the one byte of the 65 byte encoding
+    signature.v = 1;
+    let signature = signature.to_legacy(Some(111)).unwrap();
+    assert_eq!(signature.to_bytes(), Err(SignatureError::InvalidV(258)));
   }
 }
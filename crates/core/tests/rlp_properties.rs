@@ -0,0 +1,260 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Round trips of the core types through the rlp codec,
+//! for values generated by proptest.
+
+use ethereum::{
+  AccessListItem, Account, Address, Block, BlockHeader, Bloom, Keccak, Log, Receipt, Signature,
+  Transaction, TransactionKind, TransactionOutcome, Withdrawal, H64, U256,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+fn u256() -> impl Strategy<Value = U256> {
+  prop_oneof![
+    any::<u64>().prop_map(U256::from),
+    any::<[u8; 32]>().prop_map(|bytes| U256::from_big_endian(&bytes)),
+  ]
+}
+
+fn keccak() -> impl Strategy<Value = Keccak> {
+  any::<[u8; 32]>().prop_map(Keccak::from)
+}
+
+fn address() -> impl Strategy<Value = Address> {
+  any::<[u8; 20]>().prop_map(Address::from)
+}
+
+fn bloom() -> impl Strategy<Value = Bloom> {
+  vec(any::<u8>(), 256).prop_map(|bytes| Bloom::from_slice(&bytes))
+}
+
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+  // long enough for both the short and the long string form
+  vec(any::<u8>(), 0..80)
+}
+
+fn access_list() -> impl Strategy<Value = Vec<AccessListItem>> {
+  vec(
+    (address(), vec(keccak(), 0..3)).prop_map(|(address, storage_keys)| AccessListItem {
+      address,
+      storage_keys,
+    }),
+    0..3,
+  )
+}
+
+fn signature() -> impl Strategy<Value = Signature> {
+  (any::<u64>(), u256(), u256()).prop_map(|(v, r, s)| Signature { v, r, s })
+}
+
+fn kind() -> impl Strategy<Value = TransactionKind> {
+  prop_oneof![
+    u256().prop_map(|gas_price| TransactionKind::Legacy { gas_price }),
+    (u256(), access_list()).prop_map(|(gas_price, access_list)| TransactionKind::AccessList {
+      gas_price,
+      access_list
+    }),
+    (u256(), u256(), access_list()).prop_map(
+      |(max_priority_fee_per_gas, max_fee_per_gas, access_list)| TransactionKind::DynamicFee {
+        max_priority_fee_per_gas,
+        max_fee_per_gas,
+        access_list,
+      }
+    ),
+  ]
+}
+
+fn transaction() -> impl Strategy<Value = Transaction> {
+  (
+    kind(),
+    any::<u64>(),
+    (u256(), u256(), u256()),
+    prop::option::of(address()),
+    bytes(),
+    signature(),
+  )
+    .prop_map(
+      |(kind, chain_id, (nonce, gas_limit, value), recipient, data, signature)| Transaction {
+        // legacy transactions carry their chain id in V
+        chain_id: match kind {
+          TransactionKind::Legacy { .. } => signature.chain_id(),
+          _ => Some(chain_id),
+        },
+        kind,
+        nonce,
+        gas_limit,
+        recipient,
+        value,
+        data,
+        signature,
+      },
+    )
+}
+
+fn header() -> impl Strategy<Value = BlockHeader> {
+  (
+    (keccak(), keccak(), address()),
+    (keccak(), keccak(), keccak(), bloom()),
+    (u256(), any::<u64>(), u256(), u256(), any::<u64>(), bytes()),
+    (keccak(), any::<[u8; 8]>()),
+    (u256(), keccak(), any::<u64>(), any::<u64>(), keccak()),
+    // number of fork fields that are present, absent
+    // ones in between would decode as their default.
+    0..=5usize,
+  )
+    .prop_map(
+      |(
+        (parent_hash, ommers_hash, beneficiary),
+        (state_root, transactions_root, receipts_root, logs_bloom),
+        (difficulty, number, gas_limit, gas_used, timestamp, extra_data),
+        (mix_hash, nonce),
+        (base_fee_per_gas, withdrawals_root, blob_gas_used, excess_blob_gas, beacon_root),
+        forks,
+      )| BlockHeader {
+        parent_hash,
+        ommers_hash,
+        beneficiary,
+        state_root,
+        transactions_root,
+        receipts_root,
+        logs_bloom,
+        difficulty,
+        number,
+        gas_limit,
+        gas_used,
+        timestamp,
+        extra_data,
+        mix_hash,
+        nonce: H64::from(nonce),
+        base_fee_per_gas: Some(base_fee_per_gas).filter(|_| forks > 0),
+        withdrawals_root: Some(withdrawals_root).filter(|_| forks > 1),
+        blob_gas_used: Some(blob_gas_used).filter(|_| forks > 2),
+        excess_blob_gas: Some(excess_blob_gas).filter(|_| forks > 3),
+        parent_beacon_block_root: Some(beacon_root).filter(|_| forks > 4),
+      },
+    )
+}
+
+fn withdrawal() -> impl Strategy<Value = Withdrawal> {
+  (any::<u64>(), any::<u64>(), address(), any::<u64>()).prop_map(
+    |(index, validator_index, address, amount)| Withdrawal {
+      index,
+      validator_index,
+      address,
+      amount,
+    },
+  )
+}
+
+fn block() -> impl Strategy<Value = Block> {
+  (
+    header(),
+    vec(transaction(), 0..4),
+    vec(header(), 0..2),
+    prop::option::of(vec(withdrawal(), 0..3)),
+  )
+    .prop_map(|(header, transactions, ommers, withdrawals)| Block {
+      header,
+      transactions,
+      ommers,
+      withdrawals,
+    })
+}
+
+fn receipt() -> impl Strategy<Value = Receipt> {
+  let log = (address(), vec(keccak(), 0..4), bytes()).prop_map(|(address, topics, data)| Log {
+    address,
+    topics,
+    data,
+  });
+  (
+    prop_oneof![Just(0u8), 1..=0x7fu8],
+    prop_oneof![
+      keccak().prop_map(TransactionOutcome::StateRoot),
+      any::<bool>().prop_map(TransactionOutcome::Status),
+    ],
+    u256(),
+    bloom(),
+    vec(log, 0..3),
+  )
+    .prop_map(
+      |(type_id, outcome, cumulative_gas_used, logs_bloom, logs)| Receipt {
+        type_id,
+        outcome,
+        cumulative_gas_used,
+        logs_bloom,
+        logs,
+      },
+    )
+}
+
+fn account() -> impl Strategy<Value = Account> {
+  (u256(), u256(), keccak(), keccak()).prop_map(|(nonce, balance, storage_root, code_hash)| {
+    Account {
+      nonce,
+      balance,
+      storage_root,
+      code_hash,
+    }
+  })
+}
+
+/// Decoding inverts encoding, and the encoding of the decoded
+/// value is the one it was decoded from.
+fn round_trip<T>(value: &T) -> Result<(), TestCaseError>
+where
+  T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+  let encoded = ethereum::rlp_serialize(value).map_err(|e| TestCaseError::fail(e.to_string()))?;
+  let decoded: T =
+    ethereum::rlp_deserialize(&encoded).map_err(|e| TestCaseError::fail(e.to_string()))?;
+  prop_assert_eq!(&decoded, value);
+  prop_assert_eq!(ethereum::rlp_serialize(&decoded).unwrap(), encoded);
+  Ok(())
+}
+
+proptest! {
+  #[test]
+  fn transaction_round_trip(tx in transaction()) {
+    round_trip(&tx)?;
+    prop_assert_eq!(Transaction::decode(&tx.encode()).unwrap(), tx);
+  }
+
+  #[test]
+  fn header_round_trip(header in header()) {
+    round_trip(&header)?;
+  }
+
+  #[test]
+  fn withdrawal_round_trip(withdrawal in withdrawal()) {
+    round_trip(&withdrawal)?;
+  }
+
+  #[test]
+  fn block_round_trip(block in block()) {
+    round_trip(&block)?;
+  }
+
+  #[test]
+  fn receipt_round_trip(receipt in receipt()) {
+    round_trip(&receipt)?;
+    prop_assert_eq!(Receipt::decode(&receipt.encode()).unwrap(), receipt);
+  }
+
+  #[test]
+  fn account_round_trip(account in account()) {
+    round_trip(&account)?;
+  }
+
+  /// Arbitrary input is rejected with an error rather than a panic
+  #[test]
+  fn arbitrary_input(bytes in vec(any::<u8>(), 0..256)) {
+    let _ = ethereum::rlp_deserialize::<Block>(&bytes);
+    let _ = ethereum::rlp_deserialize::<Transaction>(&bytes);
+    let _ = ethereum::rlp_deserialize::<Receipt>(&bytes);
+  }
+}
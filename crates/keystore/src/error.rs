@@ -0,0 +1,49 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use ethereum::Address;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+  /// Reading or writing a key file failed
+  Io(std::io::Error),
+  /// The key file is not valid JSON or is missing required fields
+  Json(serde_json::Error),
+  /// The MAC of the key file does not match, most likely a wrong password
+  InvalidPassword,
+  /// The decrypted secret is not a valid secp256k1 key
+  InvalidSecretKey,
+  /// Only aes-128-ctr is part of the V3 specification
+  UnsupportedCipher(String),
+  /// Only scrypt and pbkdf2 with hmac-sha256 are supported
+  UnsupportedKdf(String),
+  /// The key derivation parameters are out of range
+  InvalidKdfParams,
+  /// Only version 3 key files are supported
+  UnsupportedVersion(u32),
+  /// No key file in the keystore belongs to this account
+  AccountNotFound(Address),
+  /// The keystore directory does not exist
+  KeystoreNotFound(PathBuf),
+}
+
+impl std::fmt::Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self, f)
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+  fn from(e: std::io::Error) -> Self {
+    Error::Io(e)
+  }
+}
+
+impl From<serde_json::Error> for Error {
+  fn from(e: serde_json::Error) -> Self {
+    Error::Json(e)
+  }
+}
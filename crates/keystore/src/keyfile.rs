@@ -0,0 +1,494 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Web3 Secret Storage Definition, version 3
+//! https://github.com/ethereum/wiki/wiki/Web3-Secret-Storage-Definition
+
+use crate::Error;
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use ethereum::{Address, SecretKey};
+use keccak_hash::keccak;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+const CIPHER: &str = "aes-128-ctr";
+const PRF: &str = "hmac-sha256";
+const DKLEN: usize = 32;
+
+/// Upper bounds of the key derivation parameters: geth's standard scrypt
+/// cost (with the parallelism of its light one) and the pbkdf2 iterations of
+/// the specification. Key files asking for more could stall or exhaust the
+/// node when unlocking an account.
+const MAX_SCRYPT_N: u32 = 1 << 18;
+const MAX_SCRYPT_R: u32 = 8;
+const MAX_SCRYPT_P: u32 = 6;
+const MAX_PBKDF2_C: u32 = 1 << 18;
+
+/// Key derivation function that stretches a password into the encryption key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+  /// n is the CPU/memory cost, a power of two below 2^(16r) (RFC 7914)
+  Scrypt { n: u32, r: u32, p: u32 },
+  /// c is the number of hmac-sha256 iterations
+  Pbkdf2 { c: u32 },
+}
+
+impl Kdf {
+  /// Same parameters as geth's default, about 256MB of memory per derivation.
+  pub const STANDARD: Kdf = Kdf::Scrypt {
+    n: 1 << 18,
+    r: 8,
+    p: 1,
+  };
+
+  /// Same parameters as geth's --lightkdf, for tests and constrained devices.
+  pub const LIGHT: Kdf = Kdf::Scrypt {
+    n: 1 << 12,
+    r: 8,
+    p: 6,
+  };
+
+  fn derive(&self, password: &[u8], salt: &[u8], dklen: usize) -> Result<Vec<u8>, Error> {
+    // aes-128 and the MAC use exactly 32 bytes, anything else in a key
+    // file is at best unused and at worst a huge allocation
+    if dklen != DKLEN {
+      return Err(Error::InvalidKdfParams);
+    }
+
+    let mut key = vec![0u8; dklen];
+    match *self {
+      Kdf::Scrypt { n, r, p } => {
+        if n < 2 || !n.is_power_of_two() || n > MAX_SCRYPT_N {
+          return Err(Error::InvalidKdfParams);
+        }
+        if r > MAX_SCRYPT_R || p > MAX_SCRYPT_P {
+          return Err(Error::InvalidKdfParams);
+        }
+        let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p, dklen)
+          .map_err(|_| Error::InvalidKdfParams)?;
+        scrypt::scrypt(password, salt, &params, &mut key).map_err(|_| Error::InvalidKdfParams)?;
+      }
+      Kdf::Pbkdf2 { c } => {
+        if c == 0 || c > MAX_PBKDF2_C {
+          return Err(Error::InvalidKdfParams);
+        }
+        pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, c, &mut key)
+      }
+    }
+    Ok(key)
+  }
+}
+
+/// An encrypted secret key, as stored in a keystore file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFile {
+  /// Random UUID identifying the file
+  pub id: String,
+  /// Account of the encrypted key. Optional in the specification,
+  /// but written by all common clients.
+  pub address: Option<Address>,
+  kdf: Kdf,
+  salt: Vec<u8>,
+  dklen: usize,
+  iv: Vec<u8>,
+  ciphertext: Vec<u8>,
+  mac: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawKeyFile {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  address: Option<String>,
+  #[serde(alias = "Crypto")]
+  crypto: RawCrypto,
+  id: String,
+  version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawCrypto {
+  cipher: String,
+  cipherparams: CipherParams,
+  ciphertext: String,
+  kdf: String,
+  kdfparams: serde_json::Value,
+  mac: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CipherParams {
+  iv: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScryptParams {
+  dklen: usize,
+  n: u32,
+  r: u32,
+  p: u32,
+  salt: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Pbkdf2Params {
+  c: u32,
+  dklen: usize,
+  prf: String,
+  salt: String,
+}
+
+fn unhex(s: &str) -> Result<Vec<u8>, Error> {
+  let s = s.strip_prefix("0x").unwrap_or(s);
+  hex::decode(s).map_err(|e| Error::Json(serde::de::Error::custom(e)))
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+  let mut bytes = vec![0u8; len];
+  OsRng.fill_bytes(&mut bytes);
+  bytes
+}
+
+/// Random (version 4) UUID
+fn random_uuid() -> String {
+  let mut b = random_bytes(16);
+  b[6] = (b[6] & 0x0f) | 0x40;
+  b[8] = (b[8] & 0x3f) | 0x80;
+  format!(
+    "{}-{}-{}-{}-{}",
+    hex::encode(&b[0..4]),
+    hex::encode(&b[4..6]),
+    hex::encode(&b[6..8]),
+    hex::encode(&b[8..10]),
+    hex::encode(&b[10..16])
+  )
+}
+
+fn mac(derived_key: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+  let mut preimage = derived_key[16..32].to_vec();
+  preimage.extend_from_slice(ciphertext);
+  keccak(&preimage).as_bytes().to_vec()
+}
+
+fn apply_cipher(derived_key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), Error> {
+  let mut cipher = Aes128Ctr::new_from_slices(&derived_key[..16], iv)
+    .map_err(|_| Error::UnsupportedCipher(format!("{} with a {} byte iv", CIPHER, iv.len())))?;
+  cipher.apply_keystream(data);
+  Ok(())
+}
+
+impl KeyFile {
+  /// Encrypts a secret key with a key derived from the password.
+  pub fn encrypt(secret: &SecretKey, password: &[u8], kdf: Kdf) -> Result<Self, Error> {
+    let salt = random_bytes(32);
+    let iv = random_bytes(16);
+    let derived_key = kdf.derive(password, &salt, DKLEN)?;
+
+    let mut ciphertext = secret.to_bytes().to_vec();
+    apply_cipher(&derived_key, &iv, &mut ciphertext)?;
+
+    Ok(KeyFile {
+      id: random_uuid(),
+      address: Some(secret.address()),
+      kdf,
+      salt,
+      dklen: DKLEN,
+      iv,
+      mac: mac(&derived_key, &ciphertext),
+      ciphertext,
+    })
+  }
+
+  /// Recovers the secret key. Fails with [Error::InvalidPassword] if the
+  /// password does not reproduce the MAC stored along the ciphertext.
+  pub fn decrypt(&self, password: &[u8]) -> Result<SecretKey, Error> {
+    let derived_key = self.kdf.derive(password, &self.salt, self.dklen)?;
+    // constant time, so the comparison leaks nothing about the expected MAC
+    if !bool::from(mac(&derived_key, &self.ciphertext).ct_eq(&self.mac)) {
+      return Err(Error::InvalidPassword);
+    }
+
+    let mut plaintext = self.ciphertext.clone();
+    apply_cipher(&derived_key, &self.iv, &mut plaintext)?;
+    SecretKey::from_slice(&plaintext).map_err(|_| Error::InvalidSecretKey)
+  }
+
+  /// Key derivation function protecting this file
+  pub fn kdf(&self) -> Kdf {
+    self.kdf
+  }
+
+  pub fn from_json(json: &str) -> Result<Self, Error> {
+    let raw: RawKeyFile = serde_json::from_str(json)?;
+    if raw.version != 3 {
+      return Err(Error::UnsupportedVersion(raw.version));
+    }
+
+    let crypto = raw.crypto;
+    if crypto.cipher != CIPHER {
+      return Err(Error::UnsupportedCipher(crypto.cipher));
+    }
+
+    let (kdf, salt, dklen) = match crypto.kdf.as_str() {
+      "scrypt" => {
+        let params: ScryptParams = serde_json::from_value(crypto.kdfparams)?;
+        let kdf = Kdf::Scrypt {
+          n: params.n,
+          r: params.r,
+          p: params.p,
+        };
+        (kdf, params.salt, params.dklen)
+      }
+      "pbkdf2" => {
+        let params: Pbkdf2Params = serde_json::from_value(crypto.kdfparams)?;
+        if params.prf != PRF {
+          return Err(Error::UnsupportedKdf(format!("pbkdf2 with {}", params.prf)));
+        }
+        (Kdf::Pbkdf2 { c: params.c }, params.salt, params.dklen)
+      }
+      other => return Err(Error::UnsupportedKdf(other.to_owned())),
+    };
+
+    let address = match raw.address {
+      Some(address) => match unhex(&address)? {
+        bytes if bytes.len() == 20 => Some(Address::from_slice(&bytes)),
+        _ => {
+          return Err(Error::Json(serde::de::Error::custom(
+            "invalid address length",
+          )))
+        }
+      },
+      None => None,
+    };
+
+    Ok(KeyFile {
+      id: raw.id,
+      address,
+      kdf,
+      salt: unhex(&salt)?,
+      dklen,
+      iv: unhex(&crypto.cipherparams.iv)?,
+      ciphertext: unhex(&crypto.ciphertext)?,
+      mac: unhex(&crypto.mac)?,
+    })
+  }
+
+  pub fn to_json(&self) -> String {
+    let salt = hex::encode(&self.salt);
+    let (kdf, kdfparams) = match self.kdf {
+      Kdf::Scrypt { n, r, p } => (
+        "scrypt",
+        serde_json::to_value(ScryptParams {
+          dklen: self.dklen,
+          n,
+          r,
+          p,
+          salt,
+        }),
+      ),
+      Kdf::Pbkdf2 { c } => (
+        "pbkdf2",
+        serde_json::to_value(Pbkdf2Params {
+          c,
+          dklen: self.dklen,
+          prf: PRF.to_owned(),
+          salt,
+        }),
+      ),
+    };
+
+    let raw = RawKeyFile {
+      address: self.address.map(hex::encode),
+      crypto: RawCrypto {
+        cipher: CIPHER.to_owned(),
+        cipherparams: CipherParams {
+          iv: hex::encode(&self.iv),
+        },
+        ciphertext: hex::encode(&self.ciphertext),
+        kdf: kdf.to_owned(),
+        kdfparams: kdfparams.expect("kdf parameters are plain numbers and strings"),
+        mac: hex::encode(&self.mac),
+      },
+      id: self.id.clone(),
+      version: 3,
+    };
+    serde_json::to_string(&raw).expect("key files serialize to json")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Test vectors from the Web3 Secret Storage Definition
+  const PBKDF2_VECTOR: &str = r#"{
+    "crypto" : {
+      "cipher" : "aes-128-ctr",
+      "cipherparams" : { "iv" : "6087dab2f9fdbbfaddc31a909735c1e6" },
+      "ciphertext" : "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+      "kdf" : "pbkdf2",
+      "kdfparams" : {
+        "c" : 262144,
+        "dklen" : 32,
+        "prf" : "hmac-sha256",
+        "salt" : "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+      },
+      "mac" : "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+    },
+    "id" : "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+    "version" : 3
+  }"#;
+
+  /// The scrypt vector of the specification uses r = 1 with n = 2^18,
+  /// which RFC 7914 (and therefore our scrypt implementation) rejects.
+  /// This one encrypts the same key with geth's light scrypt parameters,
+  /// it was produced with OpenSSL's scrypt.
+  const SCRYPT_VECTOR: &str = r#"{
+    "address" : "008aeeda4d805471df9b2a5b0f38a0c3bcba786b",
+    "crypto" : {
+      "cipher" : "aes-128-ctr",
+      "cipherparams" : { "iv" : "3ad3d7f02a8c0cd1f1a37f1c3e8d7c55" },
+      "ciphertext" : "e1d3d3470345487c8f0e666498c8e3a6d6b8a2a9073ea9429891ab2bff91967b",
+      "kdf" : "scrypt",
+      "kdfparams" : {
+        "dklen" : 32,
+        "n" : 4096,
+        "p" : 6,
+        "r" : 8,
+        "salt" : "b5f2b2fb0e5fb2f4d1bd3e1cf3ec2e4bd8a6fe21e0b3b0f7fd5d9d8e1f0c2a37"
+      },
+      "mac" : "1381f0793357ccc77fe416004d0a0d2db3ca49f4414fd1c7cd6d76e0981a1917"
+    },
+    "id" : "cd4c5fbb-5e6b-4f4e-9c4c-3e4a6a0e8e7d",
+    "version" : 3
+  }"#;
+
+  const VECTOR_SECRET: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+  #[test]
+  fn decrypt_spec_vectors() -> Result<(), Error> {
+    let expected: SecretKey = VECTOR_SECRET.parse().unwrap();
+    for json in &[PBKDF2_VECTOR, SCRYPT_VECTOR] {
+      let file = KeyFile::from_json(json)?;
+      assert_eq!(file.decrypt(b"testpassword")?, expected);
+      assert!(matches!(
+        file.decrypt(b"wrong"),
+        Err(Error::InvalidPassword)
+      ));
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn vector_addresses() -> Result<(), Error> {
+    let expected: SecretKey = VECTOR_SECRET.parse().unwrap();
+    assert_eq!(KeyFile::from_json(PBKDF2_VECTOR)?.address, None);
+    assert_eq!(
+      KeyFile::from_json(SCRYPT_VECTOR)?.address,
+      Some(expected.address())
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn encrypt_round_trip() -> Result<(), Error> {
+    let secret = SecretKey::random();
+    for kdf in &[Kdf::LIGHT, Kdf::Pbkdf2 { c: 1024 }] {
+      let file = KeyFile::encrypt(&secret, b"correct horse", *kdf)?;
+      let parsed = KeyFile::from_json(&file.to_json())?;
+      assert_eq!(parsed, file);
+      assert_eq!(parsed.address, Some(secret.address()));
+      assert_eq!(parsed.kdf(), *kdf);
+      assert_eq!(parsed.decrypt(b"correct horse")?, secret);
+      assert!(matches!(
+        parsed.decrypt(b"battery staple"),
+        Err(Error::InvalidPassword)
+      ));
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn unsupported_files() {
+    let aes256 = PBKDF2_VECTOR.replace("aes-128-ctr", "aes-256-cbc");
+    assert!(matches!(
+      KeyFile::from_json(&aes256),
+      Err(Error::UnsupportedCipher(_))
+    ));
+
+    let sha512 = PBKDF2_VECTOR.replace("hmac-sha256", "hmac-sha512");
+    assert!(matches!(
+      KeyFile::from_json(&sha512),
+      Err(Error::UnsupportedKdf(_))
+    ));
+
+    let v2 = PBKDF2_VECTOR.replace("\"version\" : 3", "\"version\" : 2");
+    assert!(matches!(
+      KeyFile::from_json(&v2),
+      Err(Error::UnsupportedVersion(2))
+    ));
+
+    let bad_n = SCRYPT_VECTOR.replace("4096", "4097");
+    let file = KeyFile::from_json(&bad_n).unwrap();
+    assert!(matches!(
+      file.decrypt(b"testpassword"),
+      Err(Error::InvalidKdfParams)
+    ));
+
+    for dklen in &["16", "64", "18446744073709551615"] {
+      let bad_dklen = SCRYPT_VECTOR.replace("\"dklen\" : 32", &format!("\"dklen\" : {}", dklen));
+      let file = KeyFile::from_json(&bad_dklen).unwrap();
+      assert!(matches!(
+        file.decrypt(b"testpassword"),
+        Err(Error::InvalidKdfParams)
+      ));
+    }
+  }
+
+  #[test]
+  fn kdf_limits() {
+    let too_costly = [
+      SCRYPT_VECTOR.replace("\"n\" : 4096", "\"n\" : 524288"),
+      SCRYPT_VECTOR.replace("\"r\" : 8", "\"r\" : 9"),
+      SCRYPT_VECTOR.replace("\"p\" : 6", "\"p\" : 7"),
+      PBKDF2_VECTOR.replace("\"c\" : 262144", "\"c\" : 262145"),
+      PBKDF2_VECTOR.replace("\"c\" : 262144", "\"c\" : 0"),
+    ];
+    for json in too_costly.iter() {
+      let file = KeyFile::from_json(json).unwrap();
+      assert!(matches!(
+        file.decrypt(b"testpassword"),
+        Err(Error::InvalidKdfParams)
+      ));
+    }
+
+    let secret = SecretKey::random();
+    assert!(matches!(
+      KeyFile::encrypt(&secret, b"password", Kdf::Pbkdf2 { c: u32::MAX }),
+      Err(Error::InvalidKdfParams)
+    ));
+  }
+
+  #[test]
+  fn mac_mismatch() -> Result<(), Error> {
+    let mut file = KeyFile::from_json(SCRYPT_VECTOR)?;
+    let expected = file.mac.clone();
+    *file.mac.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+      file.decrypt(b"testpassword"),
+      Err(Error::InvalidPassword)
+    ));
+
+    // a truncated MAC never matches
+    file.mac = expected[..16].to_vec();
+    assert!(matches!(
+      file.decrypt(b"testpassword"),
+      Err(Error::InvalidPassword)
+    ));
+    Ok(())
+  }
+}
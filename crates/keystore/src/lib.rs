@@ -0,0 +1,286 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Accounts whose secret keys are stored encrypted on disk, one
+//! Web3 Secret Storage (V3) JSON file per account, in a layout that is
+//! interchangeable with the keystore directories of geth and OpenEthereum.
+
+mod error;
+mod keyfile;
+
+pub use error::Error;
+pub use keyfile::{Kdf, KeyFile};
+
+use ethereum::{Address, SecretKey};
+use std::{
+  collections::BTreeMap,
+  fs::{self, OpenOptions},
+  io::Write,
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// A directory of key files.
+#[derive(Debug, Clone)]
+pub struct Keystore {
+  dir: PathBuf,
+}
+
+impl Keystore {
+  /// Opens an existing keystore directory.
+  pub fn open(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+    let dir = dir.into();
+    if !dir.is_dir() {
+      return Err(Error::KeystoreNotFound(dir));
+    }
+    Ok(Keystore { dir })
+  }
+
+  /// Opens a keystore directory, creating it if it doesn't exist yet.
+  pub fn create(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+    let dir = dir.into();
+    fs::create_dir_all(&dir)?;
+    Ok(Keystore { dir })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.dir
+  }
+
+  /// All key files in the directory that declare their account address.
+  /// Files that are not valid V3 key files are skipped.
+  fn key_files(&self) -> Result<Vec<(PathBuf, KeyFile)>, Error> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&self.dir)? {
+      let path = entry?.path();
+      let name = path.file_name().and_then(|name| name.to_str());
+      let visible = matches!(name, Some(name) if !name.starts_with('.'));
+      if !visible || !path.is_file() {
+        continue;
+      }
+      if let Ok(file) = KeyFile::from_json(&fs::read_to_string(&path)?) {
+        if file.address.is_some() {
+          files.push((path, file));
+        }
+      }
+    }
+    Ok(files)
+  }
+
+  /// Addresses of the accounts stored in this keystore
+  pub fn accounts(&self) -> Result<Vec<Address>, Error> {
+    let mut accounts: Vec<Address> = self
+      .key_files()?
+      .into_iter()
+      .filter_map(|(_, file)| file.address)
+      .collect();
+    accounts.sort();
+    accounts.dedup();
+    Ok(accounts)
+  }
+
+  /// The key file of an account
+  pub fn key_file(&self, address: &Address) -> Result<KeyFile, Error> {
+    self
+      .key_files()?
+      .into_iter()
+      .map(|(_, file)| file)
+      .find(|file| file.address.as_ref() == Some(address))
+      .ok_or(Error::AccountNotFound(*address))
+  }
+
+  /// Decrypts the secret key of an account. The returned key
+  /// implements [ethereum::Signer] and can sign transactions.
+  pub fn unlock(&self, address: &Address, password: &[u8]) -> Result<SecretKey, Error> {
+    self.key_file(address)?.decrypt(password)
+  }
+
+  /// Encrypts an existing secret key and stores it in a new key file
+  /// that only its owner can read. Existing files are never overwritten.
+  pub fn import(&self, secret: &SecretKey, password: &[u8], kdf: Kdf) -> Result<Address, Error> {
+    let file = KeyFile::encrypt(secret, password, kdf)?;
+    let address = secret.address();
+    let name = format!(
+      "UTC--{}--{}",
+      utc_timestamp(SystemTime::now()),
+      hex::encode(address)
+    );
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+      .open(self.dir.join(name))?
+      .write_all(file.to_json().as_bytes())?;
+    Ok(address)
+  }
+
+  /// Generates a new random account and stores its key.
+  pub fn new_account(&self, password: &[u8], kdf: Kdf) -> Result<Address, Error> {
+    self.import(&SecretKey::random(), password, kdf)
+  }
+}
+
+/// Accounts whose keys were unlocked, so they can sign on their behalf.
+#[derive(Debug, Clone, Default)]
+pub struct Accounts {
+  signers: BTreeMap<Address, SecretKey>,
+}
+
+impl Accounts {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds an unlocked key, replacing an earlier key of the same account.
+  pub fn insert(&mut self, secret: SecretKey) {
+    self.signers.insert(secret.address(), secret);
+  }
+
+  /// The key that signs for an account, if that account was unlocked
+  pub fn signer(&self, address: &Address) -> Option<&SecretKey> {
+    self.signers.get(address)
+  }
+
+  /// Addresses of the unlocked accounts, in ascending order
+  pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+    self.signers.keys()
+  }
+
+  pub fn len(&self) -> usize {
+    self.signers.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.signers.is_empty()
+  }
+}
+
+/// Timestamp in the format used for key file names by geth,
+/// RFC 3339 with the colons replaced by dashes.
+fn utc_timestamp(time: SystemTime) -> String {
+  let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+  let seconds = since_epoch.as_secs();
+  let (days, secs_of_day) = (seconds / 86400, seconds % 86400);
+
+  // civil date from days since the epoch, http://howardhinnant.github.io/date_algorithms.html
+  let z = days as i64 + 719468;
+  let era = z / 146097;
+  let doe = z - era * 146097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  format!(
+    "{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:09}Z",
+    year,
+    month,
+    day,
+    secs_of_day / 3600,
+    secs_of_day % 3600 / 60,
+    secs_of_day % 60,
+    since_epoch.subsec_nanos()
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn temp_keystore(name: &str) -> Keystore {
+    let dir = std::env::temp_dir().join(format!("oe-keystore-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    Keystore::create(dir).unwrap()
+  }
+
+  #[test]
+  fn import_and_unlock() -> Result<(), Error> {
+    let keystore = temp_keystore("unlock");
+    let secret = SecretKey::random();
+
+    let address = keystore.import(&secret, b"password", Kdf::LIGHT)?;
+    let generated = keystore.new_account(b"other", Kdf::Pbkdf2 { c: 1024 })?;
+    fs::write(keystore.path().join("README"), "not a key file")?;
+
+    let mut expected = vec![address, generated];
+    expected.sort();
+    assert_eq!(keystore.accounts()?, expected);
+
+    assert_eq!(keystore.unlock(&address, b"password")?, secret);
+    assert_eq!(keystore.unlock(&generated, b"other")?.address(), generated);
+    assert!(matches!(
+      keystore.unlock(&address, b"other"),
+      Err(Error::InvalidPassword)
+    ));
+    assert!(matches!(
+      keystore.unlock(&Address::zero(), b"password"),
+      Err(Error::AccountNotFound(_))
+    ));
+
+    fs::remove_dir_all(keystore.path())?;
+    Ok(())
+  }
+
+  #[test]
+  fn open_missing_keystore() {
+    let dir = std::env::temp_dir().join(format!("oe-keystore-missing-{}", std::process::id()));
+    assert!(matches!(
+      Keystore::open(&dir),
+      Err(Error::KeystoreNotFound(_))
+    ));
+    assert!(!dir.exists());
+  }
+
+  #[test]
+  fn unlocked_accounts() -> Result<(), Error> {
+    let keystore = temp_keystore("accounts");
+    let first = keystore.new_account(b"password", Kdf::Pbkdf2 { c: 1024 })?;
+    let second = keystore.new_account(b"password", Kdf::Pbkdf2 { c: 1024 })?;
+
+    let mut accounts = Accounts::new();
+    accounts.insert(keystore.unlock(&first, b"password")?);
+    accounts.insert(keystore.unlock(&second, b"password")?);
+    accounts.insert(keystore.unlock(&first, b"password")?);
+
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(accounts.addresses().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(accounts.signer(&first).map(|s| s.address()), Some(first));
+    assert!(accounts.signer(&Address::zero()).is_none());
+
+    fs::remove_dir_all(keystore.path())?;
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn key_files_are_private() -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let keystore = temp_keystore("private");
+    keystore.new_account(b"password", Kdf::Pbkdf2 { c: 1024 })?;
+    for (path, _) in keystore.key_files()? {
+      assert_eq!(fs::metadata(path)?.permissions().mode() & 0o777, 0o600);
+    }
+
+    fs::remove_dir_all(keystore.path())?;
+    Ok(())
+  }
+
+  #[test]
+  fn key_file_names() {
+    let time = UNIX_EPOCH + Duration::new(1458651475, 920751759);
+    assert_eq!(utc_timestamp(time), "2016-03-22T12-57-55.920751759Z");
+    assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00-00-00.000000000Z");
+
+    let leap_day = UNIX_EPOCH + Duration::from_secs(951782400);
+    assert_eq!(utc_timestamp(leap_day), "2000-02-29T00-00-00.000000000Z");
+  }
+}
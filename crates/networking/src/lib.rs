@@ -6,8 +6,11 @@ mod config;
 pub use config::Config;
 
 use ethereum::{Block, Transaction};
-use oe4_runtime::{async_trait, Message, Result, Source, UnboundedBuffer};
-use std::{sync::Arc, time::Duration};
+use oe4_runtime::{async_trait, Message, Result, Source, Target, UnboundedBuffer};
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
 use tokio::time::sleep;
 
 /// Implements ethereum devp2p networking
@@ -17,7 +20,8 @@ use tokio::time::sleep;
 /// This type is exposed as an async target, it allows polling
 /// for new work through [receive()].
 pub struct NetworkInterface {
-  localnode: Arc<discv4::Node>,
+  /// taken out on shutdown, dropping the node stops its discovery tasks
+  localnode: Mutex<Option<Arc<discv4::Node>>>,
   txs: UnboundedBuffer<Transaction>,
   blocks: UnboundedBuffer<Block>,
 }
@@ -25,15 +29,17 @@ pub struct NetworkInterface {
 impl NetworkInterface {
   pub async fn new(config: Config) -> std::result::Result<Self, Box<dyn std::error::Error>> {
     Ok(NetworkInterface {
-      localnode: discv4::Node::new(
-        config.local_addr,
-        secp256k1::SecretKey::from_slice(&config.secret_key)?,
-        config.boot_nodes,
-        None,
-        true,
-        config.local_port,
-      )
-      .await?,
+      localnode: Mutex::new(Some(
+        discv4::Node::new(
+          config.local_addr,
+          secp256k1::SecretKey::from_slice(&config.secret_key)?,
+          config.boot_nodes,
+          None,
+          true,
+          config.local_port,
+        )
+        .await?,
+      )),
       txs: UnboundedBuffer::new(),
       blocks: UnboundedBuffer::new(),
     })
@@ -41,11 +47,24 @@ impl NetworkInterface {
 
   pub async fn run(&self, iters: u64) -> std::result::Result<(), Box<dyn std::error::Error>> {
     for _ in 0..iters {
-      println!("peers count: {}", self.localnode.num_nodes());
+      let peers = match *self.localnode.lock().unwrap() {
+        Some(ref node) => node.num_nodes(),
+        None => break,
+      };
+      println!("peers count: {}", peers);
       sleep(Duration::from_secs(3)).await;
     }
     Ok(())
   }
+
+  /// Stops peer discovery and handing out new transactions and blocks.
+  /// Consumers still receive what was already buffered, followed by
+  /// [oe4_runtime::Error::Completed].
+  pub async fn shutdown(&self) {
+    drop(self.localnode.lock().unwrap().take());
+    self.txs.complete().await;
+    self.blocks.complete().await;
+  }
 }
 
 #[async_trait]
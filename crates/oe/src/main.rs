@@ -1,13 +1,121 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use std::error::Error;
+use std::{error::Error, path::PathBuf, sync::Arc};
 
+use auction::{BlockProposal, TransactionsAuction};
+use clap::Clap;
+use ethereum::{Address, Block, Transaction};
+use keystore::{Accounts, Keystore};
 use networking::{Config, NetworkInterface};
+use oe4_runtime::{receive, Link, LinkOptions, Target};
+use storage::BlockStore;
+
+#[derive(Clap, Debug)]
+struct Opts {
+  #[clap(long, about = "Directory with encrypted V3 key files")]
+  keystore: Option<PathBuf>,
+
+  #[clap(long, about = "Account to unlock from the keystore, may be repeated")]
+  unlock: Vec<Address>,
+
+  #[clap(
+    long,
+    about = "File with one password per line, every account is tried with each of them"
+  )]
+  password: Option<PathBuf>,
+
+  #[clap(
+    long,
+    default_value = "blocks",
+    about = "Directory the imported blocks are stored in"
+  )]
+  blocks: PathBuf,
+}
+
+/// Decrypts the keys of all accounts passed with --unlock
+fn unlock_accounts(opts: &Opts) -> Result<Accounts, Box<dyn Error>> {
+  let mut unlocked = Accounts::new();
+  if opts.unlock.is_empty() {
+    return Ok(unlocked);
+  }
+
+  let keystore = Keystore::open(
+    opts
+      .keystore
+      .as_ref()
+      .ok_or("--unlock requires --keystore")?,
+  )?;
+  let passwords = match &opts.password {
+    Some(path) => std::fs::read_to_string(path)?,
+    None => return Err("--unlock requires --password".into()),
+  };
+
+  for address in &opts.unlock {
+    keystore.key_file(address)?;
+    let secret = passwords
+      .lines()
+      .find_map(|password| keystore.unlock(address, password.as_bytes()).ok())
+      .ok_or_else(|| format!("no password in --password unlocks account {:?}", address))?;
+    unlocked.insert(secret);
+  }
+  Ok(unlocked)
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-  let _network = NetworkInterface::new(Config::default());
+  env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+  let opts = Opts::parse();
+  // nothing signs on behalf of the node yet, the unlocked accounts are
+  // kept for its whole lifetime so sealing can borrow them once it exists
+  let accounts = unlock_accounts(&opts)?;
+  for address in accounts.addresses() {
+    log::info!("unlocked account {:?}", address);
+  }
+
+  let network = Arc::new(NetworkInterface::new(Config::default()).await?);
+  let auction = Arc::new(TransactionsAuction::new());
+  let storage = Arc::new(BlockStore::open(&opts.blocks)?);
+  let txs_link = Link::<Transaction>::link_to(
+    &network,
+    auction.clone(),
+    LinkOptions {
+      propagate_completion: true,
+      spawn: Some(Box::new(|forward| {
+        tokio::spawn(forward);
+      })),
+      ..Default::default()
+    },
+  );
+  let blocks_link = Link::<Block>::link_to(
+    &network,
+    storage.clone(),
+    LinkOptions {
+      propagate_completion: true,
+      spawn: Some(Box::new(|forward| {
+        tokio::spawn(forward);
+      })),
+      ..Default::default()
+    },
+  );
+
+  tokio::signal::ctrl_c().await?;
+  log::info!("shutting down");
+
+  // stops peer discovery, then completion travels from the network through
+  // the links into the auction and the storage. The auction is completed
+  // once more in case its link ended before passing completion on, so
+  // draining it always finishes.
+  network.shutdown().await;
+  txs_link.join().await;
+  auction.complete().await;
+  while let Ok(proposal) = receive::<BlockProposal>(&*auction).await {
+    log::warn!("dropping unsealed proposal of {} txs", proposal.len());
+  }
+
+  blocks_link.join().await;
+  storage.close()?;
+  log::info!("stored blocks flushed to {}", storage.path().display());
   Ok(())
 }
 
@@ -19,45 +127,44 @@ mod tests {
   };
 
   use auction::TransactionsAuction;
-  use ethereum::{Transaction, U256};
+  use ethereum::{SecretKey, Transaction, U256};
   use oe4_runtime::{receive, send};
 
+  fn signed(key: &SecretKey, nonce: u64) -> Transaction {
+    let mut tx = Transaction {
+      chain_id: Some(1),
+      nonce: U256::from(nonce),
+      ..Transaction::default()
+    };
+    tx.sign(key).unwrap();
+    tx
+  }
+
   #[tokio::test(flavor = "multi_thread")]
   async fn transaction_auction_io() {
     let auction = Arc::new(TransactionsAuction::new());
+    let key = SecretKey::random();
 
-    let ref1 = auction.clone();
+    let (ref1, key1) = (auction.clone(), key.clone());
     let task1 = tokio::task::spawn(async move {
       std::thread::sleep(Duration::from_secs(1));
-      let t1 = Transaction {
-        nonce: U256::from(1),
-        ..Transaction::default()
-      };
+      let t1 = signed(&key1, 1);
 
       std::thread::sleep(Duration::from_secs(1));
-      let t2 = Transaction {
-        nonce: U256::from(2),
-        ..Transaction::default()
-      };
+      let t2 = signed(&key1, 2);
 
       // send a transaction to the auction/pool
       send(&*ref1, t1).await;
       send(&*ref1, t2).await;
     });
 
-    let ref2 = auction.clone();
+    let (ref2, key2) = (auction.clone(), key.clone());
     let task2 = tokio::task::spawn(async move {
       std::thread::sleep(Duration::from_secs(1));
-      let t3 = Transaction {
-        nonce: U256::from(3),
-        ..Transaction::default()
-      };
+      let t3 = signed(&key2, 3);
 
       std::thread::sleep(Duration::from_secs(2));
-      let t4 = Transaction {
-        nonce: U256::from(4),
-        ..Transaction::default()
-      };
+      let t4 = signed(&key2, 4);
 
       // send a transaction to the auction/pool
       send(&*ref2, t3).await;
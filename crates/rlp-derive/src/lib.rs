@@ -0,0 +1,397 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Derive macros behind `oe4_rlp::RlpEncodable` and `oe4_rlp::RlpDecodable`.
+//! The generated code refers to items of `oe4_rlp`, use the macros
+//! through that crate rather than depending on this one.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+  parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields,
+  GenericArgument, GenericParam, Generics, Lifetime, LifetimeDef, Lit, Meta, NestedMeta, Path,
+  PathArguments, Type,
+};
+
+/// Implements `serde::Serialize` for a struct as the rlp list of its fields,
+/// in the order in which they are declared.
+///
+/// Fields take the `#[rlp(...)]` attributes:
+/// - `trailing`: an `Option` field that is omitted when it and all fields
+///   after it are `None`. Only trailing fields may follow a trailing field.
+/// - `encodable`: the field has an rlp encoding of its own, see
+///   `oe4_rlp::with::encodable`. Typically `U256`, `H256` or `Address`.
+/// - `raw`: the field holds an already encoded item, see `oe4_rlp::with::raw`.
+/// - `with = "module"`: a module with serde `serialize` and `deserialize`
+///   functions for the field. Those of trailing fields take and return the
+///   `Option`, and wrap `None` in `oe4_rlp::ser::Absent` when serializing,
+///   see `oe4_rlp::with::trailing`.
+#[proc_macro_derive(RlpEncodable, attributes(rlp))]
+pub fn derive_encodable(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(&input, encodable)
+    .unwrap_or_else(Error::into_compile_error)
+    .into()
+}
+
+/// Implements `serde::Deserialize` for a struct from the rlp list of its
+/// fields. Missing trailing fields decode as `None`, any other missing
+/// field or an item past the last field is an error. Fields take the same
+/// attributes as for [macro@RlpEncodable].
+#[proc_macro_derive(RlpDecodable, attributes(rlp))]
+pub fn derive_decodable(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(&input, decodable)
+    .unwrap_or_else(Error::into_compile_error)
+    .into()
+}
+
+/// A field of the struct and how it is encoded
+struct Field {
+  member: syn::Member,
+  name: String,
+  ty: Type,
+  trailing: bool,
+  with: Option<Path>,
+  span: Span,
+}
+
+impl Field {
+  /// The type inside the `Option` of a trailing field
+  fn inner(&self) -> syn::Result<&Type> {
+    option_argument(&self.ty)
+      .ok_or_else(|| Error::new(self.span, "trailing fields have to be of type Option<T>"))
+  }
+}
+
+fn expand(
+  input: &DeriveInput,
+  generate: fn(&DeriveInput, &[Field]) -> syn::Result<TokenStream2>,
+) -> syn::Result<TokenStream2> {
+  let fields = match &input.data {
+    Data::Struct(data) => fields(&data.fields)?,
+    _ => {
+      return Err(Error::new(
+        input.span(),
+        "only structs can be encoded as rlp lists",
+      ))
+    }
+  };
+  generate(input, &fields)
+}
+
+fn fields(fields: &Fields) -> syn::Result<Vec<Field>> {
+  let mut parsed = Vec::new();
+  for (index, field) in fields.iter().enumerate() {
+    let mut trailing = false;
+    let mut with = None;
+    let mut shorthand: Option<Path> = None;
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("rlp")) {
+      let list = match attr.parse_meta()? {
+        Meta::List(list) => list,
+        meta => return Err(Error::new(meta.span(), "expected #[rlp(...)]")),
+      };
+      for nested in list.nested {
+        match nested {
+          NestedMeta::Meta(Meta::Path(path)) if path.is_ident("trailing") => trailing = true,
+          NestedMeta::Meta(Meta::Path(path)) if path.is_ident("encodable") => {
+            shorthand = Some(path)
+          }
+          NestedMeta::Meta(Meta::Path(path)) if path.is_ident("raw") => shorthand = Some(path),
+          NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("with") => {
+            with = Some(match &pair.lit {
+              Lit::Str(path) => path.parse()?,
+              lit => return Err(Error::new(lit.span(), "expected a module path string")),
+            })
+          }
+          nested => return Err(Error::new(nested.span(), "unknown rlp attribute")),
+        }
+      }
+    }
+
+    if let Some(path) = shorthand {
+      with = Some(match (trailing, path.is_ident("encodable")) {
+        (false, true) => parse_quote!(::oe4_rlp::with::encodable),
+        (true, true) => parse_quote!(::oe4_rlp::with::trailing_encodable),
+        (false, false) => parse_quote!(::oe4_rlp::with::raw),
+        (true, false) => return Err(Error::new(path.span(), "raw fields can't be trailing")),
+      });
+    }
+
+    if !trailing && matches!(parsed.last(), Some(Field { trailing: true, .. })) {
+      return Err(Error::new(
+        field.span(),
+        "fields that follow a trailing field have to be trailing as well",
+      ));
+    }
+
+    parsed.push(Field {
+      member: match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(index.into()),
+      },
+      name: match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => index.to_string(),
+      },
+      ty: field.ty.clone(),
+      trailing,
+      with,
+      span: field.span(),
+    });
+  }
+  Ok(parsed)
+}
+
+/// `T` of a type spelled `Option<T>`
+fn option_argument(ty: &Type) -> Option<&Type> {
+  let segment = match ty {
+    Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+    _ => return None,
+  };
+  match &segment.arguments {
+    PathArguments::AngleBracketed(args) if segment.ident == "Option" && args.args.len() == 1 => {
+      match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+      }
+    }
+    _ => None,
+  }
+}
+
+/// The generics of the struct, with `bound` added to its type parameters
+fn bounded(generics: &Generics, bound: TokenStream2) -> Generics {
+  let mut generics = generics.clone();
+  for param in generics.params.iter_mut() {
+    if let GenericParam::Type(param) = param {
+      param.bounds.push(parse_quote!(#bound));
+    }
+  }
+  generics
+}
+
+fn encodable(input: &DeriveInput, fields: &[Field]) -> syn::Result<TokenStream2> {
+  let ident = &input.ident;
+  let name = ident.to_string();
+  let generics = bounded(
+    &input.generics,
+    quote!(::oe4_rlp::__private::serde::Serialize),
+  );
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  // fields encoded through a `with` module are wrapped in a type
+  // that forwards Serialize to the module, as serde does.
+  let mut wrapper_generics = generics.clone();
+  wrapper_generics.params.insert(0, parse_quote!('__a));
+  let (wrapper_impl_generics, wrapper_ty_generics, _) = wrapper_generics.split_for_impl();
+
+  let len = fields.len();
+  let mut wrappers = Vec::new();
+  let mut statements = Vec::new();
+  for (index, field) in fields.iter().enumerate() {
+    let member = &field.member;
+    let field_name = &field.name;
+    statements.push(match (&field.with, field.trailing) {
+      (Some(with), _) => {
+        let wrapper = format_ident!("__SerializeWith{}", index);
+        let ty = &field.ty;
+        wrappers.push(quote! {
+          struct #wrapper #wrapper_impl_generics #where_clause {
+            value: &'__a #ty,
+            phantom: ::std::marker::PhantomData<#ident #ty_generics>,
+          }
+
+          impl #wrapper_impl_generics ::oe4_rlp::__private::serde::Serialize
+            for #wrapper #wrapper_ty_generics #where_clause
+          {
+            fn serialize<__S>(&self, serializer: __S) -> ::std::result::Result<__S::Ok, __S::Error>
+            where
+              __S: ::oe4_rlp::__private::serde::Serializer,
+            {
+              #with::serialize(self.value, serializer)
+            }
+          }
+        });
+        quote! {
+          list.serialize_field(#field_name, &#wrapper {
+            value: &self.#member,
+            phantom: ::std::marker::PhantomData::<#ident #ty_generics>,
+          })?;
+        }
+      }
+      (None, true) => {
+        let inner = field.inner()?;
+        quote_spanned! {field.span=>
+          match &self.#member {
+            Some(value) => list.serialize_field(#field_name, value)?,
+            None => list.serialize_field(
+              #field_name,
+              &::oe4_rlp::__private::Absent(<#inner as ::std::default::Default>::default()),
+            )?,
+          }
+        }
+      }
+      (None, false) => quote!(list.serialize_field(#field_name, &self.#member)?;),
+    });
+  }
+
+  Ok(quote! {
+    impl #impl_generics ::oe4_rlp::__private::serde::Serialize for #ident #ty_generics #where_clause {
+      fn serialize<__S>(&self, serializer: __S) -> ::std::result::Result<__S::Ok, __S::Error>
+      where
+        __S: ::oe4_rlp::__private::serde::Serializer,
+      {
+        use ::oe4_rlp::__private::serde::ser::SerializeStruct;
+        #(#wrappers)*
+        let mut list = serializer.serialize_struct(#name, #len)?;
+        #(#statements)*
+        list.end()
+      }
+    }
+  })
+}
+
+fn decodable(input: &DeriveInput, fields: &[Field]) -> syn::Result<TokenStream2> {
+  let ident = &input.ident;
+  let name = ident.to_string();
+  let generics = bounded(
+    &input.generics,
+    quote!(::oe4_rlp::__private::serde::Deserialize<'de>),
+  );
+  let (_, ty_generics, where_clause) = generics.split_for_impl();
+
+  // 'de outlives the lifetimes of the struct, so that fields can borrow
+  // from the input. The visitor and wrappers are generic over 'de too.
+  let mut de = LifetimeDef::new(Lifetime::new("'de", Span::call_site()));
+  de.bounds
+    .extend(generics.lifetimes().map(|def| def.lifetime.clone()));
+  let mut de_generics = generics.clone();
+  de_generics.params.insert(0, GenericParam::Lifetime(de));
+  let (de_impl_generics, de_ty_generics, _) = de_generics.split_for_impl();
+
+  let required = fields.iter().filter(|field| !field.trailing).count();
+  let expecting = match required == fields.len() {
+    true => format!("a list of {} fields of {}", required, name),
+    false => format!(
+      "a list of {} to {} fields of {}",
+      required,
+      fields.len(),
+      name
+    ),
+  };
+
+  let mut wrappers = Vec::new();
+  let mut statements = Vec::new();
+  let mut members = Vec::new();
+  for (index, field) in fields.iter().enumerate() {
+    let var = format_ident!("__field{}", index);
+    let element = match (&field.with, field.trailing) {
+      (Some(with), _) => {
+        let wrapper = format_ident!("__DeserializeWith{}", index);
+        let ty = &field.ty;
+        wrappers.push(quote! {
+          struct #wrapper #de_impl_generics #where_clause {
+            value: #ty,
+            phantom: ::std::marker::PhantomData<#ident #ty_generics>,
+            lifetime: ::std::marker::PhantomData<&'de ()>,
+          }
+
+          impl #de_impl_generics ::oe4_rlp::__private::serde::Deserialize<'de>
+            for #wrapper #de_ty_generics #where_clause
+          {
+            fn deserialize<__D>(deserializer: __D) -> ::std::result::Result<Self, __D::Error>
+            where
+              __D: ::oe4_rlp::__private::serde::Deserializer<'de>,
+            {
+              Ok(#wrapper {
+                value: #with::deserialize(deserializer)?,
+                phantom: ::std::marker::PhantomData,
+                lifetime: ::std::marker::PhantomData,
+              })
+            }
+          }
+        });
+        match field.trailing {
+          true => quote!(seq.next_element::<#wrapper #de_ty_generics>()?.and_then(|w| w.value)),
+          false => quote!(seq.next_element::<#wrapper #de_ty_generics>()?.map(|w| w.value)),
+        }
+      }
+      (None, true) => {
+        let inner = field.inner()?;
+        quote!(seq.next_element::<#inner>()?)
+      }
+      (None, false) => {
+        let ty = &field.ty;
+        quote!(seq.next_element::<#ty>()?)
+      }
+    };
+    statements.push(match field.trailing {
+      true => quote!(let #var = #element;),
+      false => quote! {
+        let #var = match #element {
+          Some(value) => value,
+          None => {
+            return Err(::oe4_rlp::__private::serde::de::Error::invalid_length(#index, &self))
+          }
+        };
+      },
+    });
+    let member = &field.member;
+    members.push(quote!(#member: #var));
+  }
+
+  let deserialize = match fields.first().map(|field| &field.member) {
+    Some(syn::Member::Unnamed(_)) => {
+      let len = fields.len();
+      quote!(deserializer.deserialize_tuple_struct(#name, #len, visitor))
+    }
+    _ => {
+      let names = fields.iter().map(|field| &field.name);
+      quote!(deserializer.deserialize_struct(#name, &[#(#names),*], visitor))
+    }
+  };
+
+  Ok(quote! {
+    impl #de_impl_generics ::oe4_rlp::__private::serde::Deserialize<'de>
+      for #ident #ty_generics #where_clause
+    {
+      fn deserialize<__D>(deserializer: __D) -> ::std::result::Result<Self, __D::Error>
+      where
+        __D: ::oe4_rlp::__private::serde::Deserializer<'de>,
+      {
+        #(#wrappers)*
+
+        struct Visitor #de_impl_generics #where_clause {
+          phantom: ::std::marker::PhantomData<#ident #ty_generics>,
+          lifetime: ::std::marker::PhantomData<&'de ()>,
+        }
+
+        impl #de_impl_generics ::oe4_rlp::__private::serde::de::Visitor<'de>
+          for Visitor #de_ty_generics #where_clause
+        {
+          type Value = #ident #ty_generics;
+
+          fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            f.write_str(#expecting)
+          }
+
+          fn visit_seq<__A>(self, mut seq: __A) -> ::std::result::Result<Self::Value, __A::Error>
+          where
+            __A: ::oe4_rlp::__private::serde::de::SeqAccess<'de>,
+          {
+            #(#statements)*
+            Ok(#ident { #(#members),* })
+          }
+        }
+
+        let visitor = Visitor {
+          phantom: ::std::marker::PhantomData,
+          lifetime: ::std::marker::PhantomData,
+        };
+        #deserialize
+      }
+    }
+  })
+}
@@ -0,0 +1,695 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::err::ErrorKind;
+use super::header::{self, Header};
+use rlp::Rlp;
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+};
+
+/// A step on the way from the top-level item to the one being decoded
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Field(&'static str),
+    Index(usize),
+}
+
+/// The items of a list that have not been visited yet
+struct Items<'de> {
+    remaining: &'de [u8],
+}
+
+#[derive(Debug)]
+pub(crate) struct EthereumRlpDeserializer<'de> {
+    input: &'de [u8],
+    // the complete encoding of the item the deserializer is positioned at
+    current: &'de [u8],
+    path: Vec<Segment>,
+}
+
+impl<'de> EthereumRlpDeserializer<'de> {
+    /// Positions the deserializer at the top-level item, which
+    /// has to span the entire input.
+    pub fn from_slice(input: &'de [u8]) -> Result<Self, ErrorKind> {
+        let mut de = EthereumRlpDeserializer {
+            input,
+            current: input,
+            path: Vec::new(),
+        };
+        let header = de.header(input)?;
+        let len = header.total();
+        if len < input.len() {
+            return Err(ErrorKind::RlpTrailingBytes { offset: len });
+        }
+        de.current = &input[..len];
+        Ok(de)
+    }
+
+    fn offset(&self, item: &[u8]) -> usize {
+        item.as_ptr() as usize - self.input.as_ptr() as usize
+    }
+
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in &self.path {
+            match segment {
+                Segment::Field(name) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                }
+                Segment::Index(index) => path.push_str(&format!("[{}]", index)),
+            }
+        }
+        path
+    }
+
+    /// Header of the item at the start of `bytes`, with violations
+    /// reported at the current path.
+    fn header(&self, bytes: &'de [u8]) -> Result<Header, ErrorKind> {
+        header::parse(bytes).map_err(|fault| fault.at(self.offset(bytes), self.path()))
+    }
+
+    /// Custom errors are raised by the type decoding an item,
+    /// they get the location of that item attached.
+    fn locate(&self, item: &[u8], e: ErrorKind) -> ErrorKind {
+        match e {
+            ErrorKind::Custom(reason) => ErrorKind::RlpInvalidField {
+                offset: self.offset(item),
+                path: self.path(),
+                reason,
+            },
+            e => e,
+        }
+    }
+
+    /// Items of the list the deserializer is positioned at
+    fn items(&self) -> Result<Items<'de>, ErrorKind> {
+        let header = self.header(self.current)?;
+        if !header.list {
+            return Err(ErrorKind::RlpExpectedToBeList);
+        }
+        Ok(Items {
+            remaining: &self.current[header.len..],
+        })
+    }
+
+    /// Splits off the next item of a list, `segment` is its path segment.
+    fn next_item(
+        &mut self,
+        items: &mut Items<'de>,
+        segment: Segment,
+    ) -> Result<Option<&'de [u8]>, ErrorKind> {
+        if items.remaining.is_empty() {
+            return Ok(None);
+        }
+        self.path.push(segment);
+        let header = self.header(items.remaining);
+        self.path.pop();
+        let header = header?;
+        let (item, remaining) = items.remaining.split_at(header.total());
+        items.remaining = remaining;
+        Ok(Some(item))
+    }
+
+    /// Fails with the location of the first item a visitor left unread
+    fn expect_no_more(&self, items: &Items<'de>) -> Result<(), ErrorKind> {
+        match items.remaining.is_empty() {
+            true => Ok(()),
+            false => Err(ErrorKind::RlpUnexpectedItems {
+                offset: self.offset(items.remaining),
+                path: self.path(),
+            }),
+        }
+    }
+
+    /// Deserializes `item` at the given path segment
+    fn visit_item<T>(
+        &mut self,
+        item: &'de [u8],
+        segment: Segment,
+        seed: T,
+    ) -> Result<T::Value, ErrorKind>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.path.push(segment);
+        self.current = item;
+        let value = seed
+            .deserialize(&mut *self)
+            .map_err(|e| self.locate(item, e));
+        self.path.pop();
+        value
+    }
+
+    /// Checks the current item and all items nested within it,
+    /// for values that are handed out undecoded or skipped.
+    fn validate(&mut self) -> Result<(), ErrorKind> {
+        let depth = self.path.len();
+        let result = self.validate_nested();
+        self.path.truncate(depth);
+        result
+    }
+
+    fn validate_nested(&mut self) -> Result<(), ErrorKind> {
+        let item = self.current;
+        // the ends of the lists we are in, walked iteratively
+        // so that deeply nested input can't exhaust the stack.
+        let mut ends: Vec<usize> = Vec::new();
+        let mut position = 0;
+        loop {
+            let end = ends.last().copied().unwrap_or(item.len());
+            let header = self.header(&item[position..end])?;
+            let next = position + header.total();
+            if header.list && header.payload > 0 {
+                ends.push(next);
+                self.path.push(Segment::Index(0));
+                position += header.len;
+                continue;
+            }
+
+            position = next;
+            loop {
+                match ends.last() {
+                    None => return Ok(()),
+                    Some(&end) if end == position => {
+                        ends.pop();
+                        self.path.pop();
+                    }
+                    Some(_) => {
+                        if let Some(Segment::Index(index)) = self.path.last_mut() {
+                            *index += 1;
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Payload of the current item, which has to be a string
+    fn data(&self) -> Result<&'de [u8], ErrorKind> {
+        let header = self.header(self.current)?;
+        match header.list {
+            true => Err(ErrorKind::RlpExpectedToBeData),
+            false => Ok(&self.current[header.len..]),
+        }
+    }
+
+    fn value<T: rlp::Decodable>(&self) -> Result<T, ErrorKind> {
+        Ok(Rlp::new(self.current).as_val()?)
+    }
+
+    /// Integers are big endian without leading zero bytes
+    fn integer<T: rlp::Decodable>(&self) -> Result<T, ErrorKind> {
+        if let Some(0) = self.data()?.first() {
+            return Err(ErrorKind::RlpLeadingZeros {
+                offset: self.offset(self.current),
+                path: self.path(),
+            });
+        }
+        self.value()
+    }
+
+    /// Fails unless the current item is the empty string
+    fn expect_empty(&self) -> Result<(), ErrorKind> {
+        match self.data()? {
+            [] => Ok(()),
+            _ => Err(ErrorKind::RlpIsTooBig),
+        }
+    }
+
+    fn list<V>(
+        &mut self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value, ErrorKind>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        struct Access<'a, 'de> {
+            de: &'a mut EthereumRlpDeserializer<'de>,
+            items: &'a mut Items<'de>,
+            fields: Option<&'static [&'static str]>,
+            index: usize,
+        }
+
+        impl<'a, 'de> SeqAccess<'de> for Access<'a, 'de> {
+            type Error = ErrorKind;
+
+            fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+            where
+                T: DeserializeSeed<'de>,
+            {
+                let segment = match self.fields.and_then(|fields| fields.get(self.index)) {
+                    Some(name) => Segment::Field(name),
+                    None => Segment::Index(self.index),
+                };
+                let item = match self.de.next_item(self.items, segment)? {
+                    Some(item) => item,
+                    None => return Ok(None),
+                };
+                self.index += 1;
+                self.de.visit_item(item, segment, seed).map(Some)
+            }
+        }
+
+        let list = self.current;
+        let mut items = self.items()?;
+        let value = visitor.visit_seq(Access {
+            de: &mut *self,
+            items: &mut items,
+            fields,
+            index: 0,
+        })?;
+        self.current = list;
+        self.expect_no_more(&items)?;
+        Ok(value)
+    }
+}
+
+impl<'de, 'a> serde::Deserializer<'de> for &'a mut EthereumRlpDeserializer<'de> {
+    type Error = ErrorKind;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpAnyNotSupported)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_bool(self.integer()?)
+    }
+
+    fn deserialize_i8<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpSignedIntegersNotSupported)
+    }
+
+    fn deserialize_i16<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpSignedIntegersNotSupported)
+    }
+
+    fn deserialize_i32<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpSignedIntegersNotSupported)
+    }
+
+    fn deserialize_i64<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpSignedIntegersNotSupported)
+    }
+
+    fn deserialize_i128<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpSignedIntegersNotSupported)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_u8(self.integer()?)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_u16(self.integer()?)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.integer()?)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_u64(self.integer()?)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_u128(self.integer()?)
+    }
+
+    fn deserialize_f32<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpFloatingPorintNotSupported)
+    }
+
+    fn deserialize_f64<V>(self, _: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(Self::Error::RlpFloatingPorintNotSupported)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let scalar: u32 = self.integer()?;
+        match std::char::from_u32(scalar) {
+            Some(c) => visitor.visit_char(c),
+            None => Err(Self::Error::RlpInvalidChar(scalar)),
+        }
+    }
+
+    /// Strings are borrowed from the input where the visitor allows it
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match std::str::from_utf8(self.data()?) {
+            Ok(s) => visitor.visit_borrowed_str(s),
+            Err(_) => Err(Self::Error::RlpExpectedToBeData),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    /// The complete encoding of the current item, borrowed from the
+    /// input. Its nested items are checked before handing it out.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.validate()?;
+        visitor.visit_borrowed_bytes(self.current)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// None is the empty string, Some(v) is the single item list [v]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if !self.header(self.current)?.list {
+            self.expect_empty()?;
+            return visitor.visit_none();
+        }
+        let mut items = self.items()?;
+        let item = self
+            .next_item(&mut items, Segment::Index(0))?
+            .ok_or(Self::Error::RlpIncorrectListLen)?;
+        if !items.remaining.is_empty() {
+            return Err(Self::Error::RlpIncorrectListLen);
+        }
+        self.current = item;
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.expect_empty()?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.list(None, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    /// Maps are lists of [key, value] lists
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        struct Access<'a, 'de> {
+            de: &'a mut EthereumRlpDeserializer<'de>,
+            items: &'a mut Items<'de>,
+            index: usize,
+            value: Option<&'de [u8]>,
+        }
+
+        impl<'a, 'de> MapAccess<'de> for Access<'a, 'de> {
+            type Error = ErrorKind;
+
+            fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+            where
+                K: DeserializeSeed<'de>,
+            {
+                let entry = match self.de.next_item(self.items, Segment::Index(self.index))? {
+                    Some(entry) => entry,
+                    None => return Ok(None),
+                };
+                self.de.path.push(Segment::Index(self.index));
+                self.de.current = entry;
+                let kv = self.de.items().and_then(|mut kv| {
+                    let key = self.de.next_item(&mut kv, Segment::Field("key"))?;
+                    let value = self.de.next_item(&mut kv, Segment::Field("value"))?;
+                    match (key, value, kv.remaining.is_empty()) {
+                        (Some(key), Some(value), true) => Ok((key, value)),
+                        _ => Err(ErrorKind::RlpIncorrectListLen),
+                    }
+                });
+                self.de.path.pop();
+                let (key, value) = kv?;
+
+                self.de.path.push(Segment::Index(self.index));
+                let key = self.de.visit_item(key, Segment::Field("key"), seed);
+                self.de.path.pop();
+                self.value = Some(value);
+                key.map(Some)
+            }
+
+            fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+            where
+                V: DeserializeSeed<'de>,
+            {
+                let item = self.value.take().ok_or(ErrorKind::RlpIsTooShort)?;
+                self.de.path.push(Segment::Index(self.index));
+                let value = self.de.visit_item(item, Segment::Field("value"), seed);
+                self.de.path.pop();
+                self.index += 1;
+                value
+            }
+        }
+
+        let mut items = self.items()?;
+        visitor.visit_map(Access {
+            de: self,
+            items: &mut items,
+            index: 0,
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.list(Some(fields), visitor)
+    }
+
+    /// Enums are [index] for unit variants and [index, payload] otherwise
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        struct Access<'a, 'de> {
+            de: &'a mut EthereumRlpDeserializer<'de>,
+            index: &'de [u8],
+            payload: Option<&'de [u8]>,
+        }
+
+        impl<'a, 'de> Access<'a, 'de> {
+            fn payload(&self) -> Result<&'de [u8], ErrorKind> {
+                self.payload.ok_or(ErrorKind::RlpIncorrectListLen)
+            }
+        }
+
+        impl<'a, 'de> EnumAccess<'de> for Access<'a, 'de> {
+            type Error = ErrorKind;
+            type Variant = Self;
+
+            fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant), Self::Error>
+            where
+                T: DeserializeSeed<'de>,
+            {
+                self.de.path.push(Segment::Index(0));
+                self.de.current = self.index;
+                let index = self.de.integer::<u32>();
+                self.de.path.pop();
+                let value =
+                    seed.deserialize(IntoDeserializer::<ErrorKind>::into_deserializer(index?))?;
+                Ok((value, self))
+            }
+        }
+
+        impl<'a, 'de> VariantAccess<'de> for Access<'a, 'de> {
+            type Error = ErrorKind;
+
+            fn unit_variant(self) -> Result<(), Self::Error> {
+                match self.payload {
+                    None => Ok(()),
+                    Some(_) => Err(ErrorKind::RlpIncorrectListLen),
+                }
+            }
+
+            fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+            where
+                T: DeserializeSeed<'de>,
+            {
+                let payload = self.payload()?;
+                self.de.visit_item(payload, Segment::Index(1), seed)
+            }
+
+            fn tuple_variant<V>(self, _: usize, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                let payload = self.payload()?;
+                self.de.path.push(Segment::Index(1));
+                self.de.current = payload;
+                let value = self.de.list(None, visitor);
+                self.de.path.pop();
+                value
+            }
+
+            fn struct_variant<V>(
+                self,
+                fields: &'static [&'static str],
+                visitor: V,
+            ) -> Result<V::Value, Self::Error>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                let payload = self.payload()?;
+                self.de.path.push(Segment::Index(1));
+                self.de.current = payload;
+                let value = self.de.list(Some(fields), visitor);
+                self.de.path.pop();
+                value
+            }
+        }
+
+        let mut items = self.items()?;
+        let index = self
+            .next_item(&mut items, Segment::Index(0))?
+            .ok_or(Self::Error::RlpIncorrectListLen)?;
+        let payload = self.next_item(&mut items, Segment::Index(1))?;
+        if !items.remaining.is_empty() {
+            return Err(Self::Error::RlpIncorrectListLen);
+        }
+        visitor.visit_enum(Access {
+            de: self,
+            index,
+            payload,
+        })
+    }
+
+    /// Identifiers are encoded by their index, see [Self::deserialize_enum]
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.deserialize_u32(visitor)
+    }
+
+    /// Skips over the current item, which still has to be well formed.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.validate()?;
+        visitor.visit_unit()
+    }
+}
\ No newline at end of file
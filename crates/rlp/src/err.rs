@@ -0,0 +1,114 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use std::fmt::Display;
+
+/// Errors that could occur during de/serialization
+pub type Error = Box<ErrorKind>;
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    /// Data has additional bytes at the end of the valid RLP fragment.
+    RlpIsTooBig,
+    /// Data has too few bytes for valid RLP.
+    RlpIsTooShort,
+    /// Declared length is invalid and results in overflow
+    RlpInvalidLength,
+    /// Expected a list (sequence, tuple or struct) but found a string.
+    RlpExpectedToBeList,
+    /// Expected a string (integer, text or bytes) but found a list.
+    RlpExpectedToBeData,
+    /// A list has a different number of items than the type it is decoded into,
+    /// e.g. an option that is neither empty nor a single item list.
+    RlpIncorrectListLen,
+    /// Data length number has a prefixed zero byte, invalid for numbers.
+    RlpDataLenWithZeroPrefix,
+    /// List length number has a prefixed zero byte, invalid for numbers.
+    RlpListLenWithZeroPrefix,
+    /// Non-canonical (longer than necessary) representation used for data or list.
+    RlpInvalidIndirection,
+    /// Declared length is inconsistent with data specified after.
+    RlpInconsistentLengthAndData,
+    /// RLP encoding does not support signed integers
+    RlpSignedIntegersNotSupported,
+    /// RLP encoding does not support floating point numbers
+    RlpFloatingPorintNotSupported,
+    /// An attempt to deserialize RLP into &str. deserialize into String instead
+    RlpIntoBorrowedStringDeserializationNotSupported,
+    /// A decoded integer is not a valid unicode scalar value
+    RlpInvalidChar(u32),
+    /// Serde has a deserialize_any method that lets the format hint to the
+    /// object which route to take in deserializing.
+    RlpAnyNotSupported,
+    /// Error caused by the underlying IO. Most likey by std::io::reader el at.
+    IOError(String),
+    /// Custom rlp decoding error.
+    Custom(String),
+
+    // Violations of the canonical encoding that consensus requires. They carry
+    // the byte offset of the offending item within the input and the path of the
+    // field being decoded, e.g. `transactions[2].nonce`, or `[0][9]` for types
+    // that are decoded as plain lists. The root item has an empty path.
+    /// An integer is encoded with leading zero bytes, zero must be the empty string.
+    RlpLeadingZeros { offset: usize, path: String },
+    /// A single byte below 0x80 is wrapped in a string header instead of encoding itself.
+    RlpSingleByteWrapped { offset: usize, path: String },
+    /// A length uses the long form where the short form fits, or has leading zeros.
+    RlpNonCanonicalLength { offset: usize, path: String },
+    /// The input continues after the end of the top-level item.
+    RlpTrailingBytes { offset: usize },
+    /// A list has more items than the type it is decoded into reads.
+    RlpUnexpectedItems { offset: usize, path: String },
+    /// A field was rejected by the type decoding it, e.g. a hash of the wrong length.
+    RlpInvalidField {
+        offset: usize,
+        path: String,
+        reason: String,
+    },
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self, f)
+    }
+}
+
+impl std::error::Error for ErrorKind {}
+
+impl From<rlp::DecoderError> for ErrorKind {
+    fn from(e: rlp::DecoderError) -> Self {
+        use rlp::DecoderError::*;
+        match e {
+            RlpIsTooBig => ErrorKind::RlpIsTooBig,
+            RlpIsTooShort => ErrorKind::RlpIsTooShort,
+            RlpExpectedToBeList => ErrorKind::RlpExpectedToBeList,
+            RlpExpectedToBeData => ErrorKind::RlpExpectedToBeData,
+            RlpIncorrectListLen => ErrorKind::RlpIncorrectListLen,
+            RlpDataLenWithZeroPrefix => ErrorKind::RlpDataLenWithZeroPrefix,
+            RlpListLenWithZeroPrefix => ErrorKind::RlpListLenWithZeroPrefix,
+            RlpInvalidIndirection => ErrorKind::RlpInvalidIndirection,
+            RlpInconsistentLengthAndData => ErrorKind::RlpInconsistentLengthAndData,
+            RlpInvalidLength => ErrorKind::RlpInvalidLength,
+            Custom(msg) => ErrorKind::Custom(msg.to_owned()),
+        }
+    }
+}
+
+impl serde::ser::Error for ErrorKind {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        ErrorKind::Custom(msg.to_string()).into()
+    }
+}
+
+impl serde::de::Error for ErrorKind {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        ErrorKind::Custom(msg.to_string()).into()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,119 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::err::ErrorKind;
+
+/// Header of an rlp item: how many bytes precede the payload,
+/// how long the payload is and whether it is a list.
+pub(super) struct Header {
+    pub len: usize,
+    pub payload: usize,
+    pub list: bool,
+}
+
+impl Header {
+    /// Length of the complete encoding of the item
+    pub fn total(&self) -> usize {
+        self.len + self.payload
+    }
+}
+
+/// Ways in which an item header deviates from its canonical form
+pub(super) enum Fault {
+    SingleByteWrapped,
+    NonCanonicalLength,
+    Malformed(ErrorKind),
+}
+
+impl Fault {
+    /// The error for an item found at `offset` while decoding `path`
+    pub fn at(self, offset: usize, path: String) -> ErrorKind {
+        match self {
+            Fault::SingleByteWrapped => ErrorKind::RlpSingleByteWrapped { offset, path },
+            Fault::NonCanonicalLength => ErrorKind::RlpNonCanonicalLength { offset, path },
+            Fault::Malformed(e) => e,
+        }
+    }
+}
+
+/// Number of bytes that follow the first byte of an item and encode the
+/// length of its payload, zero for items with a short or implicit length.
+pub(super) fn length_digits(first: u8) -> usize {
+    match first {
+        0xb8..=0xbf => (first - 0xb7) as usize,
+        0xf8..=0xff => (first - 0xf7) as usize,
+        _ => 0,
+    }
+}
+
+/// Parses the header of the item at the start of `bytes`, enforcing the
+/// shortest possible encoding, and checks that the payload is complete.
+pub(super) fn parse(bytes: &[u8]) -> Result<Header, Fault> {
+    let header = parse_prefix(bytes)?;
+    match header.len.checked_add(header.payload) {
+        Some(total) if total <= bytes.len() => Ok(header),
+        Some(_) => Err(Fault::Malformed(ErrorKind::RlpIsTooShort)),
+        None => Err(Fault::Malformed(ErrorKind::RlpInvalidLength)),
+    }
+}
+
+/// Parses the header alone, the payload does not have to be available yet.
+pub(super) fn parse_prefix(bytes: &[u8]) -> Result<Header, Fault> {
+    let first = *bytes
+        .first()
+        .ok_or(Fault::Malformed(ErrorKind::RlpIsTooShort))?;
+
+    let header = match first {
+        // a single byte below 0x80 is its own encoding
+        0x00..=0x7f => Header {
+            len: 0,
+            payload: 1,
+            list: false,
+        },
+        0x80..=0xb7 => {
+            let payload = (first - 0x80) as usize;
+            if payload == 1 && matches!(bytes.get(1), Some(byte) if *byte < 0x80) {
+                return Err(Fault::SingleByteWrapped);
+            }
+            Header {
+                len: 1,
+                payload,
+                list: false,
+            }
+        }
+        0xb8..=0xbf => long_header(bytes, false)?,
+        0xc0..=0xf7 => Header {
+            len: 1,
+            payload: (first - 0xc0) as usize,
+            list: true,
+        },
+        0xf8..=0xff => long_header(bytes, true)?,
+    };
+    Ok(header)
+}
+
+/// Payloads of 56 bytes and more have their length encoded
+/// in the bytes that follow the first byte.
+fn long_header(bytes: &[u8], list: bool) -> Result<Header, Fault> {
+    let digits = length_digits(bytes[0]);
+    let length = bytes
+        .get(1..1 + digits)
+        .ok_or(Fault::Malformed(ErrorKind::RlpIsTooShort))?;
+    if length[0] == 0 {
+        return Err(Fault::NonCanonicalLength);
+    }
+    if digits > std::mem::size_of::<usize>() {
+        return Err(Fault::Malformed(ErrorKind::RlpInvalidLength));
+    }
+    let payload = length
+        .iter()
+        .fold(0usize, |acc, digit| (acc << 8) | *digit as usize);
+    if payload < 56 {
+        return Err(Fault::NonCanonicalLength);
+    }
+    Ok(Header {
+        len: 1 + digits,
+        payload,
+        list,
+    })
+}
@@ -0,0 +1,84 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Serde data format for Ethereum RLP. The serde data model maps onto RLP as:
+//!
+//! - unsigned integers, bool and char (its scalar value): minimal big endian strings
+//! - signed integers and floats: not supported, fail with a typed error
+//! - strings: their utf-8 bytes, bytes: an already rlp encoded item copied verbatim
+//! - unit and unit structs: the empty string, newtype structs: the value they wrap
+//! - None: the empty string, Some(v): the list [v]
+//! - sequences, tuples, tuple structs and structs: lists of their items
+//! - maps: lists of [key, value] lists
+//! - enums: [index] for unit variants, [index, payload] for newtype, tuple
+//!   and struct variants, with the payload encoded like the equivalent
+//!   newtype, tuple or struct.
+//!
+//! Strings and bytes are borrowed from the input where the target type
+//! allows it. [view::RlpView] gives lazy access into an encoded item and
+//! [stream::RlpReader] decodes one item at a time from a reader.
+//!
+//! Structs whose serde representation is their rlp encoding can derive it
+//! with [RlpEncodable] and [RlpDecodable], see their documentation.
+
+pub mod des;
+pub mod err;
+mod header;
+pub mod ser;
+pub mod stream;
+pub mod view;
+pub mod with;
+
+pub use err::{Error, ErrorKind};
+pub use oe4_rlp_derive::{RlpDecodable, RlpEncodable};
+pub use stream::{RlpItems, RlpReader};
+pub use view::{RlpView, RlpViewIter};
+
+use des::EthereumRlpDeserializer;
+use ser::EthereumRlpSerializer;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Result of de/serialization operations
+pub type Result<T> = std::result::Result<T, err::Error>;
+
+pub fn serialize<E>(object: &E) -> Result<Vec<u8>>
+where
+    E: Serialize + ?Sized,
+{
+    let mut serializer = EthereumRlpSerializer::new();
+    object.serialize(&mut serializer)?;
+    Ok(serializer.finalize())
+}
+
+pub fn deserialize<'a, T>(bytes: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    Ok(T::deserialize(&mut EthereumRlpDeserializer::from_slice(bytes)?)?)
+}
+
+/// Decodes the single item a reader holds, without
+/// buffering more than that item.
+pub fn deserialize_from<R, T>(reader: R) -> Result<T>
+where
+    R: std::io::Read,
+    T: DeserializeOwned,
+{
+    let mut stream = stream::RlpReader::new(reader);
+    let value = stream.read()?.ok_or(ErrorKind::RlpIsTooShort)?;
+    let offset = stream.consumed as usize;
+    match stream.read_byte()? {
+        Some(_) => Err(ErrorKind::RlpTrailingBytes { offset }.into()),
+        None => Ok(value),
+    }
+}
+
+/// Items used by the code that the derive macros generate
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::ser::Absent;
+    pub use serde;
+}
+
+#[cfg(test)]
+mod test;
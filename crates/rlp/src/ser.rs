@@ -0,0 +1,450 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::err::ErrorKind;
+
+/// Name of the newtype that [Absent] fields are wrapped
+/// in when they are passed to the serializer.
+const ABSENT: &str = "$rlp::absent";
+
+/// An absent trailing field of a struct, holding the value to write
+/// should a later field be present. See [super::with::trailing].
+pub struct Absent<T>(pub T);
+
+impl<T: serde::Serialize> serde::Serialize for Absent<T> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+      S: serde::Serializer,
+  {
+      serializer.serialize_newtype_struct(ABSENT, &self.0)
+  }
+}
+
+pub(crate) struct EthereumRlpSerializer {
+  stream: rlp::RlpStream,
+  lists: Vec<List>,
+}
+
+/// A list that is being serialized
+struct List {
+  // whether its length was unknown upfront and
+  // the list has to be finalized explicitly.
+  unbounded: bool,
+  // encodings of absent fields that are only written
+  // once a field that follows them is present.
+  absent: Vec<Vec<u8>>,
+}
+
+impl EthereumRlpSerializer {
+  pub fn new() -> Self {
+      EthereumRlpSerializer {
+          stream: rlp::RlpStream::new(),
+          lists: Vec::new(),
+      }
+  }
+
+  pub fn finalize(self) -> Vec<u8> {
+      self.stream.as_raw().into()
+  }
+
+  /// The stream to append the next item to, the absent
+  /// fields that precede the item are written first.
+  fn stream(&mut self) -> &mut rlp::RlpStream {
+      if let Some(list) = self.lists.last_mut() {
+          for item in list.absent.drain(..) {
+              self.stream.append_raw(&item, 1);
+          }
+      }
+      &mut self.stream
+  }
+
+  fn begin_list(&mut self, len: Option<usize>) {
+      match len {
+          Some(len) => self.stream().begin_list(len),
+          None => self.stream().begin_unbounded_list(),
+      };
+      self.lists.push(List {
+          unbounded: len.is_none(),
+          absent: Vec::new(),
+      });
+  }
+
+  /// Absent fields at the end of a list are omitted
+  fn end_list(&mut self) {
+      if let Some(List {
+          unbounded: true, ..
+      }) = self.lists.pop()
+      {
+          self.stream.finalize_unbounded_list();
+      }
+  }
+
+  /// Enums are encoded as [index] for unit variants and [index, payload]
+  /// otherwise, where the payload is encoded as the equivalent newtype,
+  /// tuple or struct would be.
+  fn begin_variant(&mut self, index: u32, has_payload: bool) {
+      let stream = self.stream();
+      stream.begin_list(if has_payload { 2 } else { 1 });
+      stream.append(&index);
+  }
+}
+
+impl<'a> serde::Serializer for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+  type Error = ErrorKind;
+
+  type SerializeSeq = Self;
+  type SerializeTuple = Self;
+  type SerializeTupleStruct = Self;
+  type SerializeTupleVariant = Self;
+  type SerializeMap = Self;
+  type SerializeStruct = Self;
+  type SerializeStructVariant = Self;
+
+  fn is_human_readable(&self) -> bool {
+      false
+  }
+
+  fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  fn serialize_i8(self, _: i8) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpSignedIntegersNotSupported)
+  }
+
+  fn serialize_i16(self, _: i16) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpSignedIntegersNotSupported)
+  }
+
+  fn serialize_i32(self, _: i32) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpSignedIntegersNotSupported)
+  }
+
+  fn serialize_i64(self, _: i64) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpSignedIntegersNotSupported)
+  }
+
+  fn serialize_i128(self, _: i128) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpSignedIntegersNotSupported)
+  }
+
+  fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpFloatingPorintNotSupported)
+  }
+
+  fn serialize_f64(self, _: f64) -> Result<Self::Ok, Self::Error> {
+      Err(ErrorKind::RlpFloatingPorintNotSupported)
+  }
+
+  /// chars are integers holding their unicode scalar value
+  fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+      self.serialize_u32(v as u32)
+  }
+
+  fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+      self.stream().append(&v);
+      Ok(())
+  }
+
+  /// Bytes are taken to be an already rlp encoded item and
+  /// are copied verbatim, see [super::with::raw].
+  fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+      self.stream().append_raw(v, 1);
+      Ok(())
+  }
+
+  /// None is the empty string, Some(v) is the single item list [v]
+  fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+      self.stream().append_empty_data();
+      Ok(())
+  }
+
+  fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      self.stream().begin_list(1);
+      value.serialize(self)
+  }
+
+  fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+      self.stream().append_empty_data();
+      Ok(())
+  }
+
+  fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+      self.serialize_unit()
+  }
+
+  fn serialize_unit_variant(
+      self,
+      _: &'static str,
+      variant_index: u32,
+      _: &'static str,
+  ) -> Result<Self::Ok, Self::Error> {
+      self.begin_variant(variant_index, false);
+      Ok(())
+  }
+
+  /// Newtypes are transparent and encoded as the value they wrap
+  fn serialize_newtype_struct<T: ?Sized>(
+      self,
+      name: &'static str,
+      value: &T,
+  ) -> Result<Self::Ok, Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      // absent fields can only be left out of lists of unknown length
+      if name == ABSENT && matches!(self.lists.last(), Some(list) if list.unbounded) {
+          let mut absent = EthereumRlpSerializer::new();
+          value.serialize(&mut absent)?;
+          if let Some(list) = self.lists.last_mut() {
+              list.absent.push(absent.finalize());
+          }
+          return Ok(());
+      }
+      value.serialize(self)
+  }
+
+  fn serialize_newtype_variant<T: ?Sized>(
+      self,
+      _: &'static str,
+      variant_index: u32,
+      _: &'static str,
+      value: &T,
+  ) -> Result<Self::Ok, Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      self.begin_variant(variant_index, true);
+      value.serialize(self)
+  }
+
+  fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+      self.begin_list(len);
+      Ok(self)
+  }
+
+  fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+      self.begin_list(Some(len));
+      Ok(self)
+  }
+
+  fn serialize_tuple_struct(
+      self,
+      _: &'static str,
+      len: usize,
+  ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+      self.serialize_tuple(len)
+  }
+
+  fn serialize_tuple_variant(
+      self,
+      _: &'static str,
+      variant_index: u32,
+      _: &'static str,
+      len: usize,
+  ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+      self.begin_variant(variant_index, true);
+      self.serialize_tuple(len)
+  }
+
+  /// Maps are lists of [key, value] lists
+  fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+      self.begin_list(len);
+      Ok(self)
+  }
+
+  /// Structs are lists of unknown length, so that absent
+  /// trailing fields can be omitted, see [super::with::trailing].
+  fn serialize_struct(
+      self,
+      _: &'static str,
+      _: usize,
+  ) -> Result<Self::SerializeStruct, Self::Error> {
+      self.begin_list(None);
+      Ok(self)
+  }
+
+  fn serialize_struct_variant(
+      self,
+      _: &'static str,
+      variant_index: u32,
+      _: &'static str,
+      len: usize,
+  ) -> Result<Self::SerializeStructVariant, Self::Error> {
+      self.begin_variant(variant_index, true);
+      self.serialize_struct("", len)
+  }
+}
+
+impl<'a> serde::ser::SerializeTuple for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+
+  type Error = ErrorKind;
+
+  fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      value.serialize(&mut **self)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
+
+impl<'a> serde::ser::SerializeSeq for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+  type Error = ErrorKind;
+
+  fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+  where
+      T: ?Sized + serde::Serialize,
+  {
+      value.serialize(&mut **self)?;
+      Ok(())
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
+
+impl<'a> serde::ser::SerializeStruct for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+
+  type Error = ErrorKind;
+
+  fn serialize_field<T: ?Sized>(
+      &mut self,
+      _: &'static str,
+      value: &T,
+  ) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      value.serialize(&mut **self)?;
+      Ok(())
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
+
+impl<'a> serde::ser::SerializeStructVariant for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+
+  type Error = ErrorKind;
+
+  fn serialize_field<T: ?Sized>(
+      &mut self,
+      _: &'static str,
+      value: &T,
+  ) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      value.serialize(&mut **self)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
+
+impl<'a> serde::ser::SerializeTupleStruct for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+
+  type Error = ErrorKind;
+
+  fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      value.serialize(&mut **self)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
+
+impl<'a> serde::ser::SerializeTupleVariant for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+
+  type Error = ErrorKind;
+
+  fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      value.serialize(&mut **self)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
+
+impl<'a> serde::ser::SerializeMap for &'a mut EthereumRlpSerializer {
+  type Ok = ();
+
+  type Error = ErrorKind;
+
+  fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      self.stream().begin_list(2);
+      key.serialize(&mut **self)
+  }
+
+  fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+  where
+      T: serde::Serialize,
+  {
+      value.serialize(&mut **self)
+  }
+
+  fn end(self) -> Result<Self::Ok, Self::Error> {
+      self.end_list();
+      Ok(())
+  }
+}
\ No newline at end of file
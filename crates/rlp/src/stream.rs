@@ -0,0 +1,142 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::err::ErrorKind;
+use super::header::{self, Header};
+use super::view::RlpView;
+use super::Result;
+use serde::de::DeserializeOwned;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// Pulls one top-level rlp item at a time from a reader, e.g. from a
+/// file of concatenated blocks.
+///
+/// Only the item being decoded is held in memory, its buffer is reused
+/// for the next one. Offsets in decoding errors are relative to the
+/// start of the item, which is at [RlpReader::position] in the stream.
+pub struct RlpReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    // stream offsets of the item read last and of the next byte
+    position: u64,
+    pub(super) consumed: u64,
+}
+
+impl<R: Read> RlpReader<R> {
+    pub fn new(reader: R) -> Self {
+        RlpReader {
+            reader,
+            buffer: Vec::new(),
+            position: 0,
+            consumed: 0,
+        }
+    }
+
+    /// Offset in the stream of the item that was read last
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Decodes the next item, None once the stream ends between items
+    pub fn read<T: DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        match self.read_item()? {
+            true => super::deserialize(&self.buffer).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// A view into the next item that borrows the internal buffer,
+    /// for looking at parts of an item without decoding all of it.
+    pub fn read_view(&mut self) -> Result<Option<RlpView<'_>>> {
+        match self.read_item()? {
+            true => RlpView::new(&self.buffer).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// Decodes all remaining items as `T`, up to the first error
+    pub fn items<T: DeserializeOwned>(self) -> RlpItems<R, T> {
+        RlpItems {
+            reader: self,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next item into the buffer, its header is checked
+    /// before reading the payload it announces.
+    fn read_item(&mut self) -> Result<bool> {
+        self.position = self.consumed;
+        self.buffer.clear();
+        let first = match self.read_byte()? {
+            Some(first) => first,
+            None => return Ok(false),
+        };
+        self.buffer.push(first);
+        self.read_exact(header::length_digits(first))?;
+        let Header { len, payload, .. } =
+            header::parse_prefix(&self.buffer).map_err(|fault| fault.at(0, String::new()))?;
+
+        // the buffer grows with the data that actually arrives rather
+        // than with the length the header claims.
+        let total = len
+            .checked_add(payload)
+            .ok_or(ErrorKind::RlpInvalidLength)?;
+        self.read_exact(total - self.buffer.len())?;
+        Ok(true)
+    }
+
+    /// Appends exactly `count` bytes from the stream to the buffer
+    fn read_exact(&mut self, count: usize) -> Result<()> {
+        let read = (&mut self.reader)
+            .take(count as u64)
+            .read_to_end(&mut self.buffer)
+            .map_err(|e| ErrorKind::IOError(e.to_string()))?;
+        self.consumed += read as u64;
+        match read == count {
+            true => Ok(()),
+            false => Err(ErrorKind::RlpIsTooShort.into()),
+        }
+    }
+
+    /// The next byte of the stream, None at its end
+    pub(super) fn read_byte(&mut self) -> Result<Option<u8>> {
+        let mut byte = [0u8];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {
+                    self.consumed += 1;
+                    return Ok(Some(byte[0]));
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(ErrorKind::IOError(e.to_string()).into()),
+            }
+        }
+    }
+}
+
+/// Iterator over the items of a stream, see [RlpReader::items]
+pub struct RlpItems<R, T> {
+    reader: RlpReader<R>,
+    failed: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for RlpItems<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let item = self.reader.read().transpose();
+        self.failed = matches!(item, Some(Err(_)));
+        item
+    }
+}
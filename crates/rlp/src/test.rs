@@ -0,0 +1,741 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use ethereum_types::U256;
+use hex_literal::hex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Person {
+  first_name: String,
+  last_name: String,
+  age: u64,
+}
+
+#[test]
+fn struct_simple_test() -> super::Result<()> {
+  #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+  struct Item {
+    a: String,
+  }
+
+  let item = Item { a: "cat".into() };
+  let expected = vec![0xc4, 0x83, b'c', b'a', b't'];
+  let out = super::serialize(&item)?;
+
+  assert_eq!(out, expected);
+
+  let decoded = super::deserialize(&expected)?;
+  assert_eq!(item, decoded);
+
+  Ok(())
+}
+
+#[test]
+fn struct_complex_test() -> super::Result<()> {
+  #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+  struct Item {
+    a: String,
+    b: u64,
+    c: ethereum_types::U256,
+  }
+
+  let item = Item {
+    a: "cat".into(),
+    b: 1599u64,
+    c: U256::from(208090),
+  };
+
+  let out = super::serialize(&item)?;
+  let deserialized: Item = super::deserialize(&out)?;
+  assert_eq!(item, deserialized);
+
+  Ok(())
+}
+
+#[test]
+fn vec_u64_test() -> super::Result<()> {
+  let empty_v: Vec<u64> = vec![];
+  let single_v = vec![15_u64];
+  let many_v = vec![1, 2, 3, 7, 0xff];
+
+  let encoded_empty_v = super::serialize(&empty_v)?;
+  let encoded_single_v = super::serialize(&single_v)?;
+  let encoded_many_v = super::serialize(&many_v)?;
+
+  assert_eq!(encoded_empty_v, hex!("c0"));
+  assert_eq!(encoded_single_v, hex!("c10f"));
+  assert_eq!(encoded_many_v, hex!("c60102030781ff"));
+
+  let decoded_many_v: Vec<u64> = super::deserialize(&encoded_many_v)?;
+  let decoded_empty_v: Vec<u64> = super::deserialize(&encoded_empty_v)?;
+  let decoded_single_v: Vec<u64> = super::deserialize(&encoded_single_v)?;
+
+  assert_eq!(empty_v, decoded_empty_v);
+  assert_eq!(single_v, decoded_single_v);
+  assert_eq!(many_v, decoded_many_v);
+
+  Ok(())
+}
+
+#[test]
+fn str_test() -> super::Result<()> {
+  let s = "Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+  let encoded_s = super::serialize(s)?;
+
+  assert_eq!(
+    encoded_s,
+    vec![
+      0xb8, 0x38, b'L', b'o', b'r', b'e', b'm', b' ', b'i', b'p', b's', b'u', b'm', b' ', b'd',
+      b'o', b'l', b'o', b'r', b' ', b's', b'i', b't', b' ', b'a', b'm', b'e', b't', b',', b' ',
+      b'c', b'o', b'n', b's', b'e', b'c', b't', b'e', b't', b'u', b'r', b' ', b'a', b'd', b'i',
+      b'p', b'i', b's', b'i', b'c', b'i', b'n', b'g', b' ', b'e', b'l', b'i', b't',
+    ]
+  );
+
+  let decoded_s: String = super::deserialize(&encoded_s)?;
+  assert_eq!(s, decoded_s);
+
+  Ok(())
+}
+
+#[test]
+fn integers_test() -> super::Result<()> {
+  assert_eq!(super::serialize(&0u16)?, hex!("80"));
+  assert_eq!(super::serialize(&0x0400u16)?, hex!("820400"));
+  assert_eq!(super::deserialize::<u16>(&hex!("820400"))?, 0x0400);
+  assert_eq!(
+    super::serialize(&u128::MAX)?,
+    hex!("90ffffffffffffffffffffffffffffffff")
+  );
+  assert_eq!(
+    super::deserialize::<u128>(&hex!("90ffffffffffffffffffffffffffffffff"))?,
+    u128::MAX
+  );
+
+  // chars are their scalar value, ascii chars encode like u8 did before
+  assert_eq!(super::serialize(&'a')?, hex!("61"));
+  assert_eq!(super::serialize(&'€')?, hex!("8220ac"));
+  assert_eq!(super::deserialize::<char>(&hex!("8220ac"))?, '€');
+  assert_eq!(
+    *super::deserialize::<char>(&hex!("82d800")).unwrap_err(),
+    super::ErrorKind::RlpInvalidChar(0xd800)
+  );
+
+  Ok(())
+}
+
+#[test]
+fn unsupported_types_test() {
+  use super::ErrorKind;
+
+  assert_eq!(
+    *super::serialize(&-1i32).unwrap_err(),
+    ErrorKind::RlpSignedIntegersNotSupported
+  );
+  assert_eq!(
+    *super::serialize(&1i128).unwrap_err(),
+    ErrorKind::RlpSignedIntegersNotSupported
+  );
+  assert_eq!(
+    *super::serialize(&1.5f64).unwrap_err(),
+    ErrorKind::RlpFloatingPorintNotSupported
+  );
+  assert_eq!(
+    *super::deserialize::<i64>(&hex!("01")).unwrap_err(),
+    ErrorKind::RlpSignedIntegersNotSupported
+  );
+  assert_eq!(
+    *super::deserialize::<f32>(&hex!("01")).unwrap_err(),
+    ErrorKind::RlpFloatingPorintNotSupported
+  );
+
+  // malformed input is reported instead of panicking
+  assert_eq!(
+    *super::deserialize::<u64>(&hex!("c0")).unwrap_err(),
+    ErrorKind::RlpExpectedToBeData
+  );
+  assert_eq!(
+    *super::deserialize::<u8>(&hex!("820400")).unwrap_err(),
+    ErrorKind::RlpIsTooBig
+  );
+  assert_eq!(
+    *super::deserialize::<String>(&hex!("83")).unwrap_err(),
+    ErrorKind::RlpIsTooShort
+  );
+  assert_eq!(
+    *super::deserialize::<Vec<u64>>(&hex!("05")).unwrap_err(),
+    ErrorKind::RlpExpectedToBeList
+  );
+}
+
+#[test]
+fn unit_and_newtype_test() -> super::Result<()> {
+  #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+  struct Unit;
+
+  #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+  struct Wrapper(u64);
+
+  #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+  struct Pair(u64, String);
+
+  assert_eq!(super::serialize(&())?, hex!("80"));
+  assert_eq!(super::serialize(&Unit)?, hex!("80"));
+  assert_eq!(super::deserialize::<Unit>(&hex!("80"))?, Unit);
+  assert!(super::deserialize::<Unit>(&hex!("01")).is_err());
+
+  assert_eq!(super::serialize(&Wrapper(1024))?, hex!("820400"));
+  assert_eq!(super::deserialize::<Wrapper>(&hex!("820400"))?, Wrapper(1024));
+
+  let pair = Pair(1, "cat".into());
+  assert_eq!(super::serialize(&pair)?, hex!("c50183636174"));
+  assert_eq!(super::deserialize::<Pair>(&hex!("c50183636174"))?, pair);
+
+  let tuple = (1u8, (2u16, 3u32));
+  assert_eq!(super::serialize(&tuple)?, hex!("c401c20203"));
+  assert_eq!(super::deserialize::<(u8, (u16, u32))>(&hex!("c401c20203"))?, tuple);
+
+  Ok(())
+}
+
+#[test]
+fn option_test() -> super::Result<()> {
+  assert_eq!(super::serialize(&None::<u64>)?, hex!("80"));
+  assert_eq!(super::serialize(&Some(0u64))?, hex!("c180"));
+  assert_eq!(super::serialize(&Some(Some(5u64)))?, hex!("c2c105"));
+
+  assert_eq!(super::deserialize::<Option<u64>>(&hex!("80"))?, None);
+  assert_eq!(super::deserialize::<Option<u64>>(&hex!("c180"))?, Some(0));
+  assert_eq!(
+    super::deserialize::<Option<Option<u64>>>(&hex!("c2c105"))?,
+    Some(Some(5))
+  );
+  assert_eq!(
+    *super::deserialize::<Option<u64>>(&hex!("c20102")).unwrap_err(),
+    super::ErrorKind::RlpIncorrectListLen
+  );
+
+  Ok(())
+}
+
+#[test]
+fn enum_test() -> super::Result<()> {
+  #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+  enum Message {
+    Ping,
+    Status(u64),
+    Range(u64, u64),
+    Hello { version: u8, name: String },
+  }
+
+  let cases = vec![
+    (Message::Ping, hex!("c180").to_vec()),
+    (Message::Status(1024), hex!("c401820400").to_vec()),
+    (Message::Range(1, 2), hex!("c402c20102").to_vec()),
+    (
+      Message::Hello {
+        version: 5,
+        name: "cat".into(),
+      },
+      hex!("c703c50583636174").to_vec(),
+    ),
+  ];
+
+  for (message, encoded) in cases {
+    assert_eq!(super::serialize(&message)?, encoded);
+    assert_eq!(super::deserialize::<Message>(&encoded)?, message);
+  }
+
+  // enums within other types
+  let messages = vec![Some(Message::Ping), None, Some(Message::Status(0))];
+  let encoded = super::serialize(&messages)?;
+  assert_eq!(super::deserialize::<Vec<Option<Message>>>(&encoded)?, messages);
+
+  // unknown variant, missing and unexpected payloads
+  assert!(super::deserialize::<Message>(&hex!("c104")).is_err());
+  assert!(super::deserialize::<Message>(&hex!("c101")).is_err());
+  assert!(super::deserialize::<Message>(&hex!("c20001")).is_err());
+  assert!(super::deserialize::<Message>(&hex!("80")).is_err());
+
+  Ok(())
+}
+
+#[test]
+fn map_test() -> super::Result<()> {
+  use std::collections::BTreeMap;
+
+  let mut map = BTreeMap::new();
+  map.insert(1u64, "cat".to_string());
+  map.insert(2u64, "dog".to_string());
+
+  let encoded = super::serialize(&map)?;
+  assert_eq!(encoded, hex!("ccc50183636174c50283646f67"));
+  assert_eq!(super::deserialize::<BTreeMap<u64, String>>(&encoded)?, map);
+
+  assert_eq!(
+    *super::deserialize::<BTreeMap<u64, u64>>(&hex!("c5c20102c101")).unwrap_err(),
+    super::ErrorKind::RlpIncorrectListLen
+  );
+
+  Ok(())
+}
+
+#[test]
+fn unknown_length_seq_test() -> super::Result<()> {
+  use serde::ser::SerializeSeq;
+
+  // sequences that don't know their length upfront, within a struct
+  struct Evens(u64);
+
+  impl Serialize for Evens {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      let mut seq = serializer.serialize_seq(None)?;
+      for i in (0..self.0).filter(|i| i % 2 == 0) {
+        seq.serialize_element(&i)?;
+      }
+      seq.end()
+    }
+  }
+
+  #[derive(Serialize)]
+  struct Item {
+    evens: Evens,
+    last: u64,
+  }
+
+  let encoded = super::serialize(&Item {
+    evens: Evens(7),
+    last: 9,
+  })?;
+  assert_eq!(encoded, hex!("c6c48002040609"));
+  assert_eq!(
+    super::deserialize::<(Vec<u64>, u64)>(&encoded)?,
+    (vec![0, 2, 4, 6], 9)
+  );
+
+  Ok(())
+}
+
+/// Big endian bytes of a decimal number, without leading zeros
+fn big_endian(decimal: &str) -> Vec<u8> {
+  let mut bytes: Vec<u8> = vec![];
+  for digit in decimal.bytes().map(|d| (d - b'0') as u32) {
+    let mut carry = digit;
+    for byte in bytes.iter_mut().rev() {
+      let value = *byte as u32 * 10 + carry;
+      *byte = value as u8;
+      carry = value >> 8;
+    }
+    while carry > 0 {
+      bytes.insert(0, carry as u8);
+      carry >>= 8;
+    }
+  }
+  bytes
+}
+
+/// Encodes the input of an ethereum/tests rlp vector, strings starting
+/// with '#' are big integers.
+struct Input<'a>(&'a serde_json::Value);
+
+impl<'a> Serialize for Input<'a> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::{Error, SerializeSeq};
+    use serde_json::Value;
+
+    match self.0 {
+      Value::String(s) if s.starts_with('#') => {
+        super::with::encodable::serialize(&big_endian(&s[1..]), serializer)
+      }
+      Value::String(s) => serializer.serialize_str(s),
+      Value::Number(n) => serializer.serialize_u64(n.as_u64().ok_or_else(|| S::Error::custom(n))?),
+      Value::Array(items) => {
+        let mut seq = serializer.serialize_seq(Some(items.len()))?;
+        for item in items {
+          seq.serialize_element(&Input(item))?;
+        }
+        seq.end()
+      }
+      other => Err(S::Error::custom(format!("unexpected input {}", other))),
+    }
+  }
+}
+
+/// Decodes an item in the shape of the input of a vector and
+/// fails unless it holds the same value.
+struct Expect<'a>(&'a serde_json::Value);
+
+impl<'de, 'a> serde::de::DeserializeSeed<'de> for Expect<'a> {
+  type Value = ();
+
+  fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+    use serde::de::Error;
+    use serde_json::Value;
+
+    struct Items<'a>(&'a [Value]);
+
+    impl<'de, 'a> serde::de::Visitor<'de> for Items<'a> {
+      type Value = ();
+
+      fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a list of {} items", self.0.len())
+      }
+
+      fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        for (i, item) in self.0.iter().enumerate() {
+          seq
+            .next_element_seed(Expect(item))?
+            .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+        }
+        Ok(())
+      }
+    }
+
+    let matches = match self.0 {
+      Value::String(s) if s.starts_with('#') => {
+        super::with::encodable::deserialize::<Vec<u8>, _>(deserializer)? == big_endian(&s[1..])
+      }
+      Value::String(s) => String::deserialize(deserializer)? == *s,
+      Value::Number(n) => Some(u64::deserialize(deserializer)?) == n.as_u64(),
+      Value::Array(items) => return deserializer.deserialize_seq(Items(items)),
+      _ => false,
+    };
+    match matches {
+      true => Ok(()),
+      false => Err(D::Error::custom(format!("expected {}", self.0))),
+    }
+  }
+}
+
+fn rlp_vectors(json: &str) -> Vec<(String, serde_json::Value, Vec<u8>)> {
+  let vectors: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json).unwrap();
+  vectors
+    .into_iter()
+    .map(|(name, vector)| {
+      let out = vector["out"].as_str().unwrap().trim_start_matches("0x");
+      (name, vector["in"].clone(), hex::decode(out).unwrap())
+    })
+    .collect()
+}
+
+#[test]
+fn ethereum_tests_valid_rlp_test() -> super::Result<()> {
+  use serde::de::DeserializeSeed;
+
+  for (name, input, out) in rlp_vectors(include_str!("../res/rlptest.json")) {
+    assert_eq!(super::serialize(&Input(&input))?, out, "{}", name);
+
+    let mut deserializer = super::EthereumRlpDeserializer::from_slice(&out)?;
+    if let Err(e) = Expect(&input).deserialize(&mut deserializer) {
+      panic!("{}: {}", name, e);
+    }
+    super::deserialize::<serde::de::IgnoredAny>(&out)?;
+  }
+
+  Ok(())
+}
+
+#[test]
+fn ethereum_tests_invalid_rlp_test() {
+  for (name, _, out) in rlp_vectors(include_str!("../res/invalidRLPTest.json")) {
+    assert!(
+      super::deserialize::<serde::de::IgnoredAny>(&out).is_err(),
+      "{} was accepted",
+      name
+    );
+  }
+}
+
+#[test]
+fn non_canonical_test() {
+  use super::ErrorKind;
+
+  // zero is the empty string, not 0x00
+  assert_eq!(
+    *super::deserialize::<u64>(&hex!("00")).unwrap_err(),
+    ErrorKind::RlpLeadingZeros {
+      offset: 0,
+      path: String::new()
+    }
+  );
+  assert_eq!(
+    *super::deserialize::<(u8, u16)>(&hex!("c401820005")).unwrap_err(),
+    ErrorKind::RlpLeadingZeros {
+      offset: 2,
+      path: "[1]".into()
+    }
+  );
+  assert_eq!(
+    *super::deserialize::<Vec<u64>>(&hex!("c28105")).unwrap_err(),
+    ErrorKind::RlpSingleByteWrapped {
+      offset: 1,
+      path: "[0]".into()
+    }
+  );
+  assert_eq!(
+    *super::deserialize::<Vec<String>>(&hex!("c3b80161")).unwrap_err(),
+    ErrorKind::RlpNonCanonicalLength {
+      offset: 1,
+      path: "[0]".into()
+    }
+  );
+  assert_eq!(
+    *super::deserialize::<u64>(&hex!("0102")).unwrap_err(),
+    ErrorKind::RlpTrailingBytes { offset: 1 }
+  );
+  assert_eq!(
+    *super::deserialize::<(u8, u8)>(&hex!("c3010203")).unwrap_err(),
+    ErrorKind::RlpUnexpectedItems {
+      offset: 3,
+      path: String::new()
+    }
+  );
+
+  // nested items are checked even when they are skipped over
+  assert_eq!(
+    *super::deserialize::<serde::de::IgnoredAny>(&hex!("c5c0c3808105")).unwrap_err(),
+    ErrorKind::RlpSingleByteWrapped {
+      offset: 4,
+      path: "[1][1]".into()
+    }
+  );
+}
+
+#[test]
+fn located_errors_test() {
+  use super::ErrorKind;
+  use std::collections::BTreeMap;
+
+  #[allow(dead_code)]
+  #[derive(Debug, Deserialize)]
+  struct Transaction {
+    nonce: u64,
+    value: u64,
+  }
+
+  #[allow(dead_code)]
+  #[derive(Debug, Deserialize)]
+  struct Block {
+    number: u64,
+    transactions: Vec<Transaction>,
+  }
+
+  assert_eq!(
+    *super::deserialize::<Block>(&hex!("ca01c8c20102c482000502")).unwrap_err(),
+    ErrorKind::RlpLeadingZeros {
+      offset: 7,
+      path: "transactions[1].nonce".into()
+    }
+  );
+
+  assert_eq!(
+    *super::deserialize::<BTreeMap<u64, u64>>(&hex!("c5c401820005")).unwrap_err(),
+    ErrorKind::RlpLeadingZeros {
+      offset: 3,
+      path: "[0].value".into()
+    }
+  );
+
+  // errors raised by the decoded type itself get located as well
+  #[allow(dead_code)]
+  #[derive(Deserialize)]
+  struct Header {
+    number: u64,
+    #[serde(with = "super::with::encodable")]
+    parent: ethereum_types::H256,
+  }
+
+  let error = super::deserialize::<Header>(&hex!("c40182abcd"))
+    .err()
+    .unwrap();
+  assert!(matches!(
+    *error,
+    ErrorKind::RlpInvalidField { offset: 2, ref path, .. } if path == "parent"
+  ));
+}
+
+#[test]
+fn borrowed_test() -> super::Result<()> {
+  #[derive(Debug, PartialEq, Deserialize)]
+  struct Frame<'a> {
+    id: u64,
+    name: &'a str,
+    // bytes hold an rlp encoded item
+    body: &'a [u8],
+  }
+
+  let encoded = hex!("c9078470696e67c20102");
+  let decoded: Frame = super::deserialize(&encoded)?;
+  assert_eq!(
+    decoded,
+    Frame {
+      id: 7,
+      name: "ping",
+      body: &hex!("c20102"),
+    }
+  );
+  assert_eq!(decoded.name.as_ptr(), encoded[3..].as_ptr());
+  assert_eq!(decoded.body.as_ptr(), encoded[7..].as_ptr());
+
+  assert_eq!(
+    *super::deserialize::<&str>(&hex!("82c328")).unwrap_err(),
+    super::ErrorKind::RlpExpectedToBeData
+  );
+
+  Ok(())
+}
+
+#[test]
+fn deserialize_from_test() -> super::Result<()> {
+  use super::ErrorKind;
+
+  let bytes = hex!("c50183636174");
+  assert_eq!(
+    super::deserialize_from::<_, (u8, String)>(&bytes[..])?,
+    (1, "cat".into())
+  );
+  assert_eq!(
+    *super::deserialize_from::<_, (u8, String)>(&hex!("c5018363617400")[..]).unwrap_err(),
+    ErrorKind::RlpTrailingBytes { offset: 6 }
+  );
+  assert_eq!(
+    *super::deserialize_from::<_, u8>(&[][..]).unwrap_err(),
+    ErrorKind::RlpIsTooShort
+  );
+
+  Ok(())
+}
+
+#[test]
+fn with_trailing_test() -> super::Result<()> {
+  use super::with::{trailing, trailing_encodable};
+
+  #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+  struct Header {
+    number: u64,
+    #[serde(default, with = "trailing_encodable")]
+    base_fee: Option<U256>,
+    #[serde(default, with = "trailing")]
+    blob_gas: Option<u64>,
+  }
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Block {
+    header: Header,
+    number: u64,
+  }
+
+  let header = Header {
+    number: 1,
+    ..Default::default()
+  };
+  assert_eq!(super::serialize(&header)?, hex!("c101"));
+  assert_eq!(super::deserialize::<Header>(&hex!("c101"))?, header);
+
+  let header = Header {
+    number: 1,
+    base_fee: Some(7.into()),
+    blob_gas: None,
+  };
+  assert_eq!(super::serialize(&header)?, hex!("c20107"));
+  assert_eq!(super::deserialize::<Header>(&hex!("c20107"))?, header);
+
+  // absent fields before a present one are written as their default
+  let header = Header {
+    number: 1,
+    base_fee: None,
+    blob_gas: Some(5),
+  };
+  assert_eq!(super::serialize(&header)?, hex!("c3018005"));
+  assert_eq!(
+    super::deserialize::<Header>(&hex!("c3018005"))?.base_fee,
+    Some(U256::zero())
+  );
+
+  // only the fields at the end of the enclosing struct are omitted
+  let block = Block {
+    header: Header {
+      number: 1,
+      ..Default::default()
+    },
+    number: 2,
+  };
+  assert_eq!(super::serialize(&block)?, hex!("c3c10102"));
+  assert_eq!(super::deserialize::<Block>(&hex!("c3c10102"))?, block);
+
+  Ok(())
+}
+
+#[test]
+fn with_raw_test() -> super::Result<()> {
+  use super::with::raw;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Message {
+    id: u64,
+    #[serde(with = "raw")]
+    payload: Vec<u8>,
+  }
+
+  let message = Message {
+    id: 1,
+    payload: hex!("c20102").to_vec(),
+  };
+  let encoded = super::serialize(&message)?;
+  assert_eq!(encoded, hex!("c401c20102"));
+  assert_eq!(super::deserialize::<Message>(&encoded)?, message);
+
+  // payloads are checked to be well formed when decoding
+  assert!(super::deserialize::<Message>(&hex!("c401c30102")).is_err());
+
+  Ok(())
+}
+
+#[test]
+fn with_encodable_test() -> super::Result<()> {
+  use super::with::encodable;
+  use ethereum_types::{Address, H256};
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Account {
+    #[serde(with = "encodable")]
+    balance: U256,
+    #[serde(with = "encodable")]
+    address: Address,
+    #[serde(with = "encodable")]
+    code_hash: H256,
+    #[serde(with = "encodable")]
+    code: Vec<u8>,
+    supply: u128,
+  }
+
+  let account = Account {
+    balance: U256::from(0x0400),
+    address: Address::repeat_byte(0x11),
+    code_hash: H256::repeat_byte(0x22),
+    code: vec![0x60],
+    supply: 1 << 100,
+  };
+  let encoded = super::serialize(&account)?;
+  assert_eq!(
+    encoded,
+    [
+      &hex!("f848820400")[..],
+      &hex!("94")[..],
+      &[0x11; 20][..],
+      &hex!("a0")[..],
+      &[0x22; 32][..],
+      &hex!("60")[..],
+      &hex!("8d10000000000000000000000000")[..],
+    ]
+    .concat()
+  );
+  assert_eq!(super::deserialize::<Account>(&encoded)?, account);
+
+  // u128 integers are compact, leading zeros are rejected
+  assert_eq!(super::serialize(&0x0100u128)?, hex!("820100"));
+  assert!(super::deserialize::<u128>(&hex!("83000100")).is_err());
+
+  Ok(())
+}
@@ -0,0 +1,135 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::err::ErrorKind;
+use super::header::{self, Header};
+use super::Result;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+
+/// A well formed rlp item borrowed from its encoding, decoded on demand.
+///
+/// Gives random access into lists without decoding the items that are
+/// not looked at, e.g. the transaction at some index of a block. As a
+/// struct field it captures the encoding of that field for later use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RlpView<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> RlpView<'a> {
+    /// Checks that `bytes` is exactly one canonically encoded item,
+    /// including all items nested within it.
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        super::deserialize::<IgnoredAny>(bytes)?;
+        Ok(RlpView { raw: bytes })
+    }
+
+    /// The complete encoding of the item
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.raw[0] >= 0xc0
+    }
+
+    /// The payload of a string item
+    pub fn data(&self) -> Result<&'a [u8]> {
+        let header = self.header()?;
+        match header.list {
+            true => Err(ErrorKind::RlpExpectedToBeData.into()),
+            false => Ok(&self.raw[header.len..]),
+        }
+    }
+
+    /// Number of items in a list, walks the item headers only
+    pub fn item_count(&self) -> Result<usize> {
+        self.items().map(Iterator::count)
+    }
+
+    /// The item at `index` of a list
+    pub fn at(&self, index: usize) -> Result<RlpView<'a>> {
+        self.items()?
+            .nth(index)
+            .ok_or_else(|| ErrorKind::RlpIsTooShort.into())
+    }
+
+    /// The items of a list, nothing for a string item
+    pub fn iter(&self) -> RlpViewIter<'a> {
+        RlpViewIter {
+            remaining: match self.header() {
+                Ok(header) if header.list => &self.raw[header.len..],
+                _ => &[],
+            },
+        }
+    }
+
+    /// Decodes the item, borrowing from its encoding where `T` allows it
+    pub fn decode<T: Deserialize<'a>>(&self) -> Result<T> {
+        super::deserialize(self.raw)
+    }
+
+    fn header(&self) -> Result<Header> {
+        Ok(header::parse(self.raw).map_err(|fault| fault.at(0, String::new()))?)
+    }
+
+    fn items(&self) -> Result<RlpViewIter<'a>> {
+        match self.is_list() {
+            true => Ok(self.iter()),
+            false => Err(ErrorKind::RlpExpectedToBeList.into()),
+        }
+    }
+}
+
+/// Iterator over the items of a list, see [RlpView::iter]
+pub struct RlpViewIter<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> Iterator for RlpViewIter<'a> {
+    type Item = RlpView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the items of a view have been validated on construction
+        let header = header::parse(self.remaining).ok()?;
+        let (raw, remaining) = self.remaining.split_at(header.total());
+        self.remaining = remaining;
+        Some(RlpView { raw })
+    }
+}
+
+/// Views are encoded verbatim
+impl<'a> Serialize for RlpView<'a> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.raw)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RlpView<'a> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = RlpView<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("rlp encoded bytes borrowed from the input")
+            }
+
+            fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                RlpView::new(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(Visitor)
+    }
+}
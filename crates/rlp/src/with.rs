@@ -0,0 +1,154 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Field attributes for `#[serde(with = "...")]`, for fields whose
+//! type has no serde implementation that maps onto rlp as needed:
+//!
+//! - [encodable]: types with a canonical rlp encoding of their own,
+//!   e.g. `U256`, `H256`, `Address`, `Bloom` or byte strings (`Vec<u8>`).
+//! - [raw]: already encoded rlp items that are embedded verbatim.
+//! - [trailing] and [trailing_encodable]: optional fields at the end of
+//!   a struct, e.g. header fields introduced by later forks. They have to
+//!   be declared with `#[serde(default)]` as well.
+//!
+//! Unsigned integers need no attribute, `u8` up to `u128` are encoded
+//! natively as compact big endian integers without leading zeros.
+
+use super::ser::Absent;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::marker::PhantomData;
+
+/// Fields of types that implement [rlp::Encodable] and [rlp::Decodable]
+pub mod encodable {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: rlp::Encodable,
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&rlp::encode(value))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: rlp::Decodable,
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+
+        impl<'de, T: rlp::Decodable> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = T;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a raw rlp item")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                rlp::decode(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(Visitor(PhantomData))
+    }
+}
+
+/// Fields holding the complete encoding of an rlp item, e.g. parts of a
+/// message that are relayed or hashed as they were received.
+pub mod raw {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]>,
+        S: Serializer,
+    {
+        serializer.serialize_bytes(value.as_ref())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a raw rlp item")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(v.to_vec())
+            }
+        }
+
+        deserializer.deserialize_bytes(Visitor)
+    }
+}
+
+/// Optional fields at the end of a struct. They are omitted when they and
+/// all fields after them are absent, absent fields that are followed by a
+/// present one are written with their default value.
+pub mod trailing {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + Default,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => value.serialize(serializer),
+            None => Absent(T::default()).serialize(serializer),
+        }
+    }
+
+    /// Fields past the end of the list are filled in by `#[serde(default)]`
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Some)
+    }
+}
+
+/// [trailing] fields of types that are [encodable]
+pub mod trailing_encodable {
+    use super::*;
+
+    struct Encodable<'a, T>(&'a T);
+
+    impl<'a, T: rlp::Encodable> Serialize for Encodable<'a, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            encodable::serialize(self.0, serializer)
+        }
+    }
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: rlp::Encodable + Default,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => encodable::serialize(value, serializer),
+            None => Absent(Encodable(&T::default())).serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: rlp::Decodable,
+        D: Deserializer<'de>,
+    {
+        encodable::deserialize(deserializer).map(Some)
+    }
+}
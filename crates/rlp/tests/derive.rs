@@ -0,0 +1,122 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use ethereum_types::{Address, U256};
+use hex_literal::hex;
+use oe4_rlp::{ErrorKind, RlpDecodable, RlpEncodable, RlpView};
+
+#[derive(Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+struct Header {
+  number: u64,
+  #[rlp(encodable)]
+  beneficiary: Address,
+  #[rlp(trailing, encodable)]
+  base_fee: Option<U256>,
+  #[rlp(trailing)]
+  blob_gas: Option<u64>,
+}
+
+#[test]
+fn struct_test() -> oe4_rlp::Result<()> {
+  let header = Header {
+    number: 1,
+    beneficiary: Address::repeat_byte(0x11),
+    base_fee: Some(7.into()),
+    blob_gas: Some(5),
+  };
+  let encoded = oe4_rlp::serialize(&header)?;
+  assert_eq!(
+    encoded,
+    [&hex!("d80194")[..], &[0x11; 20], &hex!("0705")].concat()
+  );
+  assert_eq!(oe4_rlp::deserialize::<Header>(&encoded)?, header);
+
+  Ok(())
+}
+
+#[test]
+fn trailing_test() -> oe4_rlp::Result<()> {
+  let header = Header {
+    number: 1,
+    ..Default::default()
+  };
+  let encoded = oe4_rlp::serialize(&header)?;
+  assert_eq!(encoded, [&hex!("d60194")[..], &[0; 20]].concat());
+  assert_eq!(oe4_rlp::deserialize::<Header>(&encoded)?, header);
+
+  // absent fields before a present one are written as their default
+  let header = Header {
+    blob_gas: Some(5),
+    ..header
+  };
+  let encoded = oe4_rlp::serialize(&header)?;
+  assert_eq!(
+    encoded,
+    [&hex!("d80194")[..], &[0; 20], &hex!("8005")].concat()
+  );
+  assert_eq!(
+    oe4_rlp::deserialize::<Header>(&encoded)?.base_fee,
+    Some(U256::zero())
+  );
+
+  Ok(())
+}
+
+#[test]
+fn length_test() {
+  // the mandatory fields are missing
+  assert!(oe4_rlp::deserialize::<Header>(&hex!("c101")).is_err());
+
+  // an item past the last field
+  let encoded = [&hex!("d90194")[..], &[0; 20], &hex!("800102")].concat();
+  assert_eq!(
+    *oe4_rlp::deserialize::<Header>(&encoded).unwrap_err(),
+    ErrorKind::RlpUnexpectedItems {
+      offset: 25,
+      path: String::new()
+    }
+  );
+}
+
+#[test]
+fn tuple_struct_test() -> oe4_rlp::Result<()> {
+  #[derive(Debug, PartialEq, RlpEncodable, RlpDecodable)]
+  struct Version(u8, String, #[rlp(trailing)] Option<u8>);
+
+  let version = Version(4, "oe".into(), None);
+  let encoded = oe4_rlp::serialize(&version)?;
+  assert_eq!(encoded, hex!("c404826f65"));
+  assert_eq!(oe4_rlp::deserialize::<Version>(&encoded)?, version);
+
+  let version = Version(4, "oe".into(), Some(2));
+  assert_eq!(
+    oe4_rlp::deserialize::<Version>(&oe4_rlp::serialize(&version)?)?,
+    version
+  );
+
+  Ok(())
+}
+
+#[test]
+fn generics_test() -> oe4_rlp::Result<()> {
+  // borrowed fields and type parameters, encoded through a module
+  #[derive(Debug, PartialEq, RlpEncodable, RlpDecodable)]
+  struct Message<'a, T> {
+    id: T,
+    #[rlp(raw)]
+    payload: Vec<u8>,
+    body: RlpView<'a>,
+    #[rlp(trailing, with = "oe4_rlp::with::trailing")]
+    tags: Option<Vec<T>>,
+  }
+
+  let encoded = hex!("c601c20102c103");
+  let message: Message<u64> = oe4_rlp::deserialize(&encoded)?;
+  assert_eq!(message.id, 1);
+  assert_eq!(message.payload, hex!("c20102"));
+  assert_eq!(message.body.as_raw(), hex!("c103"));
+  assert_eq!(message.tags, None);
+  assert_eq!(oe4_rlp::serialize(&message)?, encoded);
+
+  Ok(())
+}
@@ -0,0 +1,152 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Compares the serde based rlp codec against the `rlp` crate.
+
+use oe4_rlp::RlpView;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use rlp::{Rlp, RlpStream};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+/// Values that both codecs encode natively
+#[derive(Debug, Clone)]
+enum Value {
+  Integer(u64),
+  Text(String),
+  List(Vec<Value>),
+}
+
+impl Serialize for Value {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Value::Integer(v) => serializer.serialize_u64(*v),
+      Value::Text(v) => serializer.serialize_str(v),
+      Value::List(items) => {
+        let mut seq = serializer.serialize_seq(Some(items.len()))?;
+        for item in items {
+          seq.serialize_element(item)?;
+        }
+        seq.end()
+      }
+    }
+  }
+}
+
+impl rlp::Encodable for Value {
+  fn rlp_append(&self, s: &mut RlpStream) {
+    match self {
+      Value::Integer(v) => v.rlp_append(s),
+      Value::Text(v) => v.rlp_append(s),
+      Value::List(items) => {
+        s.append_list(items);
+      }
+    }
+  }
+}
+
+fn value() -> impl Strategy<Value = Value> {
+  let leaf = prop_oneof![
+    any::<u64>().prop_map(Value::Integer),
+    // long enough for both the short and the long string form
+    ".{0,70}".prop_map(Value::Text),
+  ];
+  leaf.prop_recursive(4, 64, 16, |inner| vec(inner, 0..16).prop_map(Value::List))
+}
+
+/// The structure of an item, as seen by either codec
+#[derive(Debug, PartialEq)]
+enum Item {
+  Data(Vec<u8>),
+  List(Vec<Item>),
+}
+
+fn ours(bytes: &[u8]) -> Option<Item> {
+  fn item(view: RlpView) -> Item {
+    match view.is_list() {
+      true => Item::List(view.iter().map(item).collect()),
+      false => Item::Data(view.data().unwrap().to_vec()),
+    }
+  }
+  RlpView::new(bytes).ok().map(item)
+}
+
+fn theirs(bytes: &[u8]) -> Option<Item> {
+  // item_count stops at the first malformed item of a list, so lists
+  // are also checked to be covered by their items. Single bytes
+  // wrapped in a string are only rejected when decoding values.
+  fn item(rlp: &Rlp) -> Result<Item, rlp::DecoderError> {
+    match rlp.is_list() {
+      true => {
+        let items = (0..rlp.item_count()?)
+          .map(|i| rlp.at(i))
+          .collect::<Result<Vec<_>, _>>()?;
+        let covered: usize = items.iter().map(|item| item.as_raw().len()).sum();
+        match covered == rlp.payload_info()?.value_len {
+          true => items
+            .iter()
+            .map(item)
+            .collect::<Result<_, _>>()
+            .map(Item::List),
+          false => Err(rlp::DecoderError::RlpInconsistentLengthAndData),
+        }
+      }
+      false => rlp
+        .decoder()
+        .decode_value(|data| Ok(Item::Data(data.to_vec()))),
+    }
+  }
+  let rlp = Rlp::new(bytes);
+  match rlp.payload_info().ok()?.total() == bytes.len() {
+    true => item(&rlp).ok(),
+    false => None,
+  }
+}
+
+/// Encodings of values with some bytes overwritten, removed or appended
+fn mutated() -> impl Strategy<Value = Vec<u8>> {
+  (
+    value(),
+    vec((any::<prop::sample::Index>(), any::<u8>()), 0..3),
+    0..3usize,
+    vec(any::<u8>(), 0..3),
+  )
+    .prop_map(|(value, writes, truncate, append)| {
+      let mut bytes = rlp::encode(&value).to_vec();
+      for (index, byte) in writes {
+        let index = index.index(bytes.len());
+        bytes[index] = byte;
+      }
+      bytes.truncate(bytes.len().saturating_sub(truncate));
+      bytes.extend(append);
+      bytes
+    })
+}
+
+proptest! {
+  #[test]
+  fn encodes_like_rlp_crate(value in value()) {
+    prop_assert_eq!(oe4_rlp::serialize(&value).unwrap(), rlp::encode(&value).to_vec());
+  }
+
+  #[test]
+  fn accepts_like_rlp_crate(bytes in mutated()) {
+    prop_assert_eq!(ours(&bytes), theirs(&bytes));
+  }
+
+  #[test]
+  fn decodes_integers_like_rlp_crate(bytes in vec(any::<u8>(), 0..10)) {
+    prop_assert_eq!(
+      oe4_rlp::deserialize::<u64>(&bytes).ok(),
+      rlp::decode::<u64>(&bytes).ok().filter(|_| theirs(&bytes).is_some())
+    );
+  }
+
+  #[test]
+  fn decodes_strings_like_rlp_crate(bytes in mutated()) {
+    prop_assert_eq!(
+      oe4_rlp::deserialize::<String>(&bytes).ok(),
+      rlp::decode::<String>(&bytes).ok().filter(|_| theirs(&bytes).is_some())
+    );
+  }
+}
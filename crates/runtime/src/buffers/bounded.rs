@@ -0,0 +1,284 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::{Error, Message, MessageStatus, Result, Source, Target};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use event_listener::Event;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  collections::VecDeque,
+  sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+/// What a [BoundedBuffer] does with messages offered to it while full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+  /// Wait for a consumer to make space, then store the message
+  /// and report it as [MessageStatus::Posponed].
+  Block,
+  /// Decline the offered message.
+  DropNewest,
+  /// Evict the oldest stored message to make space for the offered one.
+  DropOldest,
+}
+
+/// A buffer of messages of type T that holds at most `capacity`
+/// messages. Messages are forwarded in the order they were accepted.
+pub struct BoundedBuffer<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  /// the maximum number of messages stored at once
+  capacity: usize,
+  /// how offers are handled when the buffer is full
+  policy: OverflowPolicy,
+  /// messages that were not consumed yet by targets
+  pending: Mutex<VecDeque<Message<T>>>,
+  /// used to signal waiting consumers that a message was stored
+  not_empty: Event,
+  /// used to signal blocked producers that a message was consumed
+  not_full: Event,
+  /// messages that were declined or evicted because of overflow
+  dropped: AtomicU64,
+  /// no more messages are accepted once set, only changes under the lock
+  completed: AtomicBool,
+}
+
+impl<T> BoundedBuffer<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+    assert!(capacity > 0, "bounded buffer capacity has to be positive");
+    BoundedBuffer {
+      capacity,
+      policy,
+      pending: Mutex::new(VecDeque::with_capacity(capacity)),
+      not_empty: Event::new(),
+      not_full: Event::new(),
+      dropped: AtomicU64::new(0),
+      completed: AtomicBool::new(false),
+    }
+  }
+
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  pub fn policy(&self) -> OverflowPolicy {
+    self.policy
+  }
+
+  /// The number of messages lost to overflow since this buffer was
+  /// created, either declined or evicted depending on the policy.
+  pub fn dropped(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+
+  #[cfg(test)]
+  pub(crate) async fn count(&self) -> usize {
+    self.pending.lock().await.len()
+  }
+}
+
+#[async_trait]
+impl<T> Source<T> for BoundedBuffer<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<T>> {
+    let message = self.pending.try_lock()?.pop_front();
+    if message.is_some() {
+      self.not_full.notify_additional(1);
+    }
+    message
+  }
+
+  async fn consume(&self) -> Result<Message<T>> {
+    loop {
+      let mut pending = self.pending.lock().await;
+      if let Some(message) = pending.pop_front() {
+        self.not_full.notify_additional(1);
+        return Ok(message);
+      }
+      if self.completed.load(Ordering::SeqCst) {
+        return Err(Error::Completed);
+      }
+      // producers notify while holding the lock, so registering before
+      // releasing it can't miss a message
+      let listener = self.not_empty.listen();
+      drop(pending);
+      listener.await;
+    }
+  }
+}
+
+#[async_trait]
+impl<T> Target<T> for BoundedBuffer<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  async fn accept(&self, message: Message<T>) -> MessageStatus {
+    let mut status = MessageStatus::Accepted;
+    let mut pending = loop {
+      let mut pending = self.pending.lock().await;
+      if self.completed.load(Ordering::SeqCst) {
+        return MessageStatus::Declined;
+      }
+      if pending.len() < self.capacity {
+        break pending;
+      }
+      match self.policy {
+        OverflowPolicy::Block => {
+          let listener = self.not_full.listen();
+          drop(pending);
+          listener.await;
+          status = MessageStatus::Posponed;
+        }
+        OverflowPolicy::DropNewest => {
+          self.dropped.fetch_add(1, Ordering::Relaxed);
+          return MessageStatus::Declined;
+        }
+        OverflowPolicy::DropOldest => {
+          pending.pop_front();
+          self.dropped.fetch_add(1, Ordering::Relaxed);
+          break pending;
+        }
+      }
+    };
+    pending.push_back(message);
+    self.not_empty.notify_additional(1);
+    status
+  }
+
+  /// Wakes up blocked producers, their messages are declined.
+  async fn complete(&self) {
+    let _pending = self.pending.lock().await;
+    self.completed.store(true, Ordering::SeqCst);
+    self.not_empty.notify(usize::MAX);
+    self.not_full.notify(usize::MAX);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt};
+  use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+  };
+
+  async fn block_test() -> Result<()> {
+    let bbuf: Arc<BoundedBuffer<u64>> = Arc::new(BoundedBuffer::new(2, OverflowPolicy::Block));
+
+    assert_eq!(send(&*bbuf, 10).await, MessageStatus::Accepted);
+    assert_eq!(send(&*bbuf, 20).await, MessageStatus::Accepted);
+
+    let bbuf_t = bbuf.clone();
+    let t_consume = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_secs(1)).await;
+      assert_eq!(receive(&*bbuf_t).await.unwrap(), 10);
+    });
+
+    let start = Instant::now();
+    assert_eq!(send(&*bbuf, 30).await, MessageStatus::Posponed);
+    assert!(start.elapsed() >= Duration::from_millis(900));
+    t_consume.await;
+
+    assert_eq!(bbuf.count().await, 2);
+    assert_eq!(bbuf.dropped(), 0);
+    assert_eq!(receive(&*bbuf).await?, 20);
+    assert_eq!(receive(&*bbuf).await?, 30);
+    assert_eq!(bbuf.try_consume(), None);
+
+    Ok(())
+  }
+
+  async fn drop_newest_test() -> Result<()> {
+    let bbuf: BoundedBuffer<u64> = BoundedBuffer::new(2, OverflowPolicy::DropNewest);
+
+    assert_eq!(send(&bbuf, 10).await, MessageStatus::Accepted);
+    assert_eq!(send(&bbuf, 20).await, MessageStatus::Accepted);
+    assert_eq!(send(&bbuf, 30).await, MessageStatus::Declined);
+    assert_eq!(send(&bbuf, 40).await, MessageStatus::Declined);
+    assert_eq!(bbuf.dropped(), 2);
+
+    assert_eq!(receive(&bbuf).await?, 10);
+    assert_eq!(send(&bbuf, 50).await, MessageStatus::Accepted);
+    assert_eq!(receive(&bbuf).await?, 20);
+    assert_eq!(receive(&bbuf).await?, 50);
+
+    Ok(())
+  }
+
+  async fn drop_oldest_test() -> Result<()> {
+    let bbuf: BoundedBuffer<u64> = BoundedBuffer::new(2, OverflowPolicy::DropOldest);
+
+    for i in 1..=5 {
+      assert_eq!(send(&bbuf, i * 10).await, MessageStatus::Accepted);
+    }
+    assert_eq!(bbuf.count().await, 2);
+    assert_eq!(bbuf.dropped(), 3);
+
+    assert_eq!(receive(&bbuf).await?, 40);
+    assert_eq!(receive(&bbuf).await?, 50);
+    assert_eq!(bbuf.try_consume(), None);
+
+    Ok(())
+  }
+
+  async fn complete_test() -> Result<()> {
+    let bbuf: Arc<BoundedBuffer<u64>> = Arc::new(BoundedBuffer::new(1, OverflowPolicy::Block));
+    send(&*bbuf, 10).await;
+
+    // a producer blocked on a full buffer gives up on completion
+    let bbuf_t = bbuf.clone();
+    let t_send = test_rt::spawn(async move { send(&*bbuf_t, 20).await });
+    test_rt::sleep(Duration::from_millis(100)).await;
+    bbuf.complete().await;
+    assert_eq!(t_send.await, MessageStatus::Declined);
+
+    assert_eq!(receive(&*bbuf).await?, 10);
+    assert!(matches!(receive(&*bbuf).await, Err(Error::Completed)));
+
+    Ok(())
+  }
+
+  async fn mt_consume_test() {
+    let bbuf: Arc<BoundedBuffer<u64>> = Arc::new(BoundedBuffer::new(16, OverflowPolicy::Block));
+
+    let producers: Vec<_> = (0..2)
+      .map(|_| {
+        let bbuf = bbuf.clone();
+        test_rt::spawn(async move {
+          for i in 0..1000 {
+            send(&*bbuf, i).await;
+          }
+        })
+      })
+      .collect();
+
+    let mut sum = 0u64;
+    for _ in 0..2000 {
+      sum += receive(&*bbuf).await.unwrap();
+    }
+
+    for producer in producers {
+      producer.await;
+    }
+
+    assert_eq!(sum, 999_000);
+    assert_eq!(bbuf.dropped(), 0);
+  }
+
+  matrix_tests!(
+    block_test,
+    drop_newest_test,
+    drop_oldest_test,
+    complete_test,
+    mt_consume_test
+  );
+}
@@ -0,0 +1,74 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use event_listener::Event;
+use std::sync::{
+  atomic::{AtomicBool, Ordering},
+  Arc,
+};
+
+#[derive(Debug, Default)]
+struct State {
+  cancelled: AtomicBool,
+  /// wakes up everyone waiting on [CancellationToken::cancelled]
+  notify: Event,
+}
+
+/// Cancels operations that wait on buffers, such as [super::Source::consume_cancellable].
+/// Clones share their state, cancelling one of them cancels all of them.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+  state: Arc<State>,
+}
+
+impl CancellationToken {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn cancel(&self) {
+    self.state.cancelled.store(true, Ordering::SeqCst);
+    self.state.notify.notify(usize::MAX);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.state.cancelled.load(Ordering::SeqCst)
+  }
+
+  /// Resolves once the token is cancelled.
+  pub async fn cancelled(&self) {
+    let mut listener = None;
+    while !self.is_cancelled() {
+      match listener.take() {
+        Some(listener) => listener.await,
+        None => listener = Some(self.state.notify.listen()),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::test_rt;
+  use std::time::Duration;
+
+  async fn cancel_test() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+    assert!(!clone.is_cancelled());
+
+    let t_cancel = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_millis(100)).await;
+      token.cancel();
+    });
+
+    clone.cancelled().await;
+    assert!(clone.is_cancelled());
+    clone.cancelled().await;
+    t_cancel.await;
+  }
+
+  matrix_tests!(cancel_test);
+}
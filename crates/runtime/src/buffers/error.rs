@@ -4,7 +4,11 @@
 #[derive(Debug)]
 pub enum Error {
   Unknown,
-  Custom(String)
+  Custom(String),
+  /// The source was completed and all of its messages were consumed
+  Completed,
+  /// The operation was cancelled through a [super::CancellationToken]
+  Cancelled,
 }
 
 impl std::fmt::Display for Error {
@@ -0,0 +1,405 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::{CancellationToken, Error, MessageStatus, Source, Target};
+
+use futures_lite::future::{self, Boxed};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+  thread,
+};
+
+type Filter<T> = dyn Fn(&T) -> bool + Send + Sync;
+
+/// Starts the forwarding of a link in the background, usually by
+/// spawning it on the executor that runs the rest of the program.
+type Spawn = dyn Fn(Forward) + Send + Sync;
+
+/// Controls which messages a link forwards and when it ends.
+pub struct LinkOptions<T> {
+  /// Only messages that pass the filter are offered to the target,
+  /// the others are consumed from the source and dropped.
+  pub filter: Option<Box<Filter<T>>>,
+
+  /// The link ends after the target took this many messages.
+  pub max_messages: Option<u64>,
+
+  /// When the source stops producing messages, the target is
+  /// notified through [Target::complete].
+  pub propagate_completion: bool,
+
+  /// The link ends when the target declines a message, instead of
+  /// counting it as dropped and carrying on with the next one. Keeps a
+  /// completed target from draining the source into nothing.
+  pub unlink_on_decline: bool,
+
+  /// Starts the [Forward] future of the link, e.g.
+  /// `|forward| { tokio::spawn(forward); }`. Without it the link
+  /// forwards its messages on a thread of its own.
+  pub spawn: Option<Box<Spawn>>,
+}
+
+impl<T> Default for LinkOptions<T> {
+  fn default() -> Self {
+    LinkOptions {
+      filter: None,
+      max_messages: None,
+      propagate_completion: false,
+      unlink_on_decline: false,
+      spawn: None,
+    }
+  }
+}
+
+/// Forwards the messages of a link for as long as it is polled.
+pub type Forward = Boxed<()>;
+
+/// Controls a link between a source and a target. Dropping the handle
+/// leaves the link running until it ends on its own.
+pub struct LinkHandle {
+  stop: CancellationToken,
+  /// cancelled once the [Forward] future finished or was dropped
+  done: CancellationToken,
+  forwarded: Arc<AtomicU64>,
+  dropped: Arc<AtomicU64>,
+}
+
+impl LinkHandle {
+  /// The number of messages the target took so far.
+  pub fn forwarded(&self) -> u64 {
+    self.forwarded.load(Ordering::Relaxed)
+  }
+
+  /// The number of messages taken from the source that never reached the
+  /// target, because the filter rejected them or the target declined them.
+  pub fn dropped(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+
+  /// Stops forwarding messages and waits for the link to end. A message
+  /// that is being offered to the target when unlinking is still delivered.
+  /// Returns the number of messages the target took.
+  pub async fn unlink(self) -> u64 {
+    self.stop.cancel();
+    self.join().await
+  }
+
+  /// Waits until the link ends on its own, because the source stopped
+  /// producing messages, `max_messages` were forwarded or, with
+  /// `unlink_on_decline`, the target declined one. Returns the number of messages the target took.
+  pub async fn join(self) -> u64 {
+    self.done.cancelled().await;
+    self.forwarded.load(Ordering::Relaxed)
+  }
+}
+
+/// Marks the link as done when the forwarding future goes away,
+/// whether it finished or was dropped before that.
+struct Done(CancellationToken);
+
+impl Drop for Done {
+  fn drop(&mut self) {
+    self.0.cancel();
+  }
+}
+
+/// Connects a shared source to a target, so that its messages are
+/// forwarded in the background as they become available. The forwarding
+/// is started through [LinkOptions::spawn]:
+///
+/// ```ignore
+/// let link = source.link_to(target, LinkOptions {
+///   spawn: Some(Box::new(|forward| { tokio::spawn(forward); })),
+///   ..Default::default()
+/// });
+/// ```
+pub trait Link<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn link_to<D>(&self, target: Arc<D>, options: LinkOptions<T>) -> LinkHandle
+  where
+    D: Target<T> + ?Sized + 'static;
+}
+
+impl<T, S> Link<T> for Arc<S>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned + 'static,
+  S: Source<T> + ?Sized + 'static,
+{
+  fn link_to<D>(&self, target: Arc<D>, mut options: LinkOptions<T>) -> LinkHandle
+  where
+    D: Target<T> + ?Sized + 'static,
+  {
+    let source = self.clone();
+    let stop = CancellationToken::new();
+    let stopped = stop.clone();
+    let done = CancellationToken::new();
+    let finished = Done(done.clone());
+    let forwarded = Arc::new(AtomicU64::new(0));
+    let counter = forwarded.clone();
+    let dropped = Arc::new(AtomicU64::new(0));
+    let lost = dropped.clone();
+
+    let spawn = options.spawn.take();
+    let forward: Forward = Box::pin(async move {
+      let _finished = finished;
+      while !matches!(options.max_messages, Some(max) if counter.load(Ordering::Relaxed) >= max) {
+        let message = match source.consume_cancellable(&stopped).await {
+          Ok(message) => message,
+          Err(Error::Cancelled) => return,
+          Err(e) => {
+            log::debug!("link source stopped: {}", e);
+            if options.propagate_completion {
+              target.complete().await;
+            }
+            return;
+          }
+        };
+
+        if let Some(ref filter) = options.filter {
+          if !filter(&message) {
+            lost.fetch_add(1, Ordering::Relaxed);
+            continue;
+          }
+        }
+
+        match target.accept(message).await {
+          MessageStatus::Accepted | MessageStatus::Posponed => {
+            counter.fetch_add(1, Ordering::Relaxed);
+          }
+          MessageStatus::Declined | MessageStatus::Missed => {
+            lost.fetch_add(1, Ordering::Relaxed);
+            if options.unlink_on_decline {
+              log::debug!("link target declined a message, unlinking");
+              return;
+            }
+          }
+        }
+      }
+    });
+
+    match spawn {
+      Some(spawn) => spawn(forward),
+      None => {
+        thread::Builder::new()
+          .name("link".into())
+          .spawn(move || future::block_on(forward))
+          .expect("failed to spawn a link thread");
+      }
+    }
+
+    LinkHandle {
+      stop,
+      done,
+      forwarded,
+      dropped,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive, Message, Result, UnboundedBuffer};
+  use async_trait::async_trait;
+  use std::{sync::atomic::AtomicBool, time::Duration};
+
+  fn background(forward: Forward) {
+    drop(test_rt::spawn(forward));
+  }
+
+  async fn forward_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let options = LinkOptions {
+      spawn: Some(Box::new(background)),
+      ..Default::default()
+    };
+    let link = a.link_to(b.clone(), options);
+
+    let message = Message::new(10);
+    let id = message.id();
+    a.accept(message).await;
+    send(&*a, 20).await;
+
+    assert_eq!(b.consume().await?.id(), id);
+    assert_eq!(receive(&*b).await?, 20);
+    assert_eq!(link.unlink().await, 2);
+
+    Ok(())
+  }
+
+  async fn options_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+
+    for i in 1..=6 {
+      send(&*a, i).await;
+    }
+
+    let link = a.link_to(
+      b.clone(),
+      LinkOptions {
+        filter: Some(Box::new(|v| v % 2 == 0)),
+        max_messages: Some(2),
+        ..Default::default()
+      },
+    );
+    assert_eq!(link.join().await, 2);
+
+    assert_eq!(try_receive(&*b), Some(2));
+    assert_eq!(try_receive(&*b), Some(4));
+    assert_eq!(try_receive(&*b), None);
+
+    // messages after the last forwarded one stay in the source
+    assert_eq!(try_receive(&*a), Some(5));
+
+    Ok(())
+  }
+
+  async fn unlink_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+
+    let link = a.link_to(b.clone(), LinkOptions::default());
+    assert_eq!(link.unlink().await, 0);
+
+    send(&*a, 10).await;
+    assert_eq!(try_receive(&*a), Some(10));
+    assert_eq!(try_receive(&*b), None);
+
+    Ok(())
+  }
+
+  async fn declined_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    b.complete().await;
+
+    for i in 1..=3 {
+      send(&*a, i).await;
+    }
+
+    // the declined message is lost, the link ends instead of draining the rest
+    let options = LinkOptions {
+      unlink_on_decline: true,
+      ..Default::default()
+    };
+    let link = a.link_to(b.clone(), options);
+    test_rt::sleep(Duration::from_millis(100)).await;
+    assert_eq!(link.dropped(), 1);
+    assert_eq!(link.join().await, 0);
+    assert_eq!(try_receive(&*a), Some(2));
+    assert_eq!(try_receive(&*a), Some(3));
+
+    Ok(())
+  }
+
+  /// Declines odd messages and takes the even ones.
+  struct Picky(UnboundedBuffer<u64>);
+
+  #[async_trait]
+  impl Target<u64> for Picky {
+    async fn accept(&self, message: Message<u64>) -> MessageStatus {
+      match message.payload() % 2 {
+        0 => self.0.accept(message).await,
+        _ => MessageStatus::Declined,
+      }
+    }
+
+    async fn complete(&self) {
+      self.0.complete().await;
+    }
+  }
+
+  async fn forward_after_decline_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(Picky(UnboundedBuffer::new()));
+
+    for i in 1..=4 {
+      send(&*a, i).await;
+    }
+    a.complete().await;
+
+    // declined messages are counted, the ones after them still go through
+    let link = a.link_to(b.clone(), LinkOptions::default());
+    assert_eq!(receive(&b.0).await?, 2);
+    assert_eq!(receive(&b.0).await?, 4);
+    assert_eq!(link.dropped(), 2);
+    assert_eq!(link.join().await, 2);
+    assert_eq!(try_receive(&*a), None);
+
+    Ok(())
+  }
+
+  struct Failing;
+
+  #[async_trait]
+  impl Source<u64> for Failing {
+    fn try_consume(&self) -> Option<Message<u64>> {
+      None
+    }
+
+    async fn consume(&self) -> Result<Message<u64>> {
+      Err(Error::Unknown)
+    }
+  }
+
+  #[derive(Default)]
+  struct Completable(AtomicBool);
+
+  #[async_trait]
+  impl Target<u64> for Completable {
+    async fn accept(&self, _: Message<u64>) -> MessageStatus {
+      MessageStatus::Declined
+    }
+
+    async fn complete(&self) {
+      self.0.store(true, Ordering::SeqCst);
+    }
+  }
+
+  async fn completion_test() {
+    let target = Arc::new(Completable::default());
+    let link = Arc::new(Failing).link_to(target.clone(), LinkOptions::default());
+    link.join().await;
+    assert!(!target.0.load(Ordering::SeqCst));
+
+    let options = LinkOptions {
+      propagate_completion: true,
+      ..Default::default()
+    };
+    let link = Arc::new(Failing).link_to(target.clone(), options);
+    link.join().await;
+    assert!(target.0.load(Ordering::SeqCst));
+  }
+
+  async fn dropped_forward_test() {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+
+    // a link whose forwarding future is never polled ends right away
+    let options = LinkOptions {
+      spawn: Some(Box::new(drop)),
+      ..Default::default()
+    };
+    let link = a.link_to(b, options);
+    assert_eq!(link.join().await, 0);
+  }
+
+  matrix_tests!(
+    forward_test,
+    options_test,
+    unlink_test,
+    declined_test,
+    forward_after_decline_test,
+    completion_test,
+    dropped_forward_test
+  );
+}
@@ -7,7 +7,7 @@ use rand::RngCore;
 use serde::{de::DeserializeOwned, Serialize};
 
 /// A unique identifier of a message sent in the system
-type Id = u64;
+pub type Id = u64;
 
 /// The basic message envelope containing the data
 /// payload being passed between messaging blocks.
@@ -31,6 +31,12 @@ where
     }
   }
 
+  /// Wraps a payload that was derived from another message, so
+  /// it can be traced through the system under the same id.
+  pub(crate) fn with_id(payload: T, id: Id) -> Self {
+    Message { payload, id }
+  }
+
   pub fn payload(&self) -> &T {
     &self.payload
   }
@@ -73,7 +79,8 @@ pub enum Status {
   Accepted,
   /// The target did not accept the message.
   Declined,
-  /// The target posponed the message
+  /// The target could not take the message right away and
+  /// held on to it until it could.
   Posponed,
   /// The target tried to accept the message but it was no longer available.
   Missed,
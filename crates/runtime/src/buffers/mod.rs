@@ -1,20 +1,31 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
+mod bounded;
+mod cancellation;
 mod error;
+mod link;
 mod message;
 mod overwrite;
+mod priority;
+mod sync;
 mod transform;
 mod unbounded;
 mod write_once;
 
-pub use message::{Message, Status as MessageStatus};
+pub use bounded::{BoundedBuffer, OverflowPolicy};
+pub use cancellation::CancellationToken;
+pub use error::Error;
+pub use link::{Forward, Link, LinkHandle, LinkOptions};
+pub use message::{Id as MessageId, Message, Status as MessageStatus};
 pub use overwrite::OverwriteBuffer;
-pub use transform::TransformBuffer;
+pub use priority::PriorityBuffer;
+pub use transform::{TransformBuffer, Transformed};
 pub use unbounded::UnboundedBuffer;
 pub use write_once::WriteOnceBuffer;
 
 use async_trait::async_trait;
+use futures_lite::FutureExt;
 use serde::{de::DeserializeOwned, Serialize};
 
 pub type Result<T> = std::result::Result<T, error::Error>;
@@ -28,8 +39,22 @@ where
   /// Attempts to return a message if it immediately available
   fn try_consume(&self) -> Option<Message<T>>;
 
-  /// asynchronously blocks until a message is available
+  /// asynchronously blocks until a message is available, fails with
+  /// [Error::Completed] once the source is completed and drained
   async fn consume(&self) -> Result<Message<T>>;
+
+  /// Like [Source::consume], but gives up with [Error::Cancelled]
+  /// once the token is cancelled
+  async fn consume_cancellable(&self, token: &CancellationToken) -> Result<Message<T>> {
+    if token.is_cancelled() {
+      return Err(Error::Cancelled);
+    }
+    let cancelled = async {
+      token.cancelled().await;
+      Err(Error::Cancelled)
+    };
+    self.consume().or(cancelled).await
+  }
 }
 
 /// Implemented by all target blocks that consume messages offered to them
@@ -41,6 +66,12 @@ where
 {
   /// Asynchonously sends/writes a message on the target
   async fn accept(&self, message: Message<T>) -> MessageStatus;
+
+  /// Signals that no more messages will be offered to the target.
+  /// Buffers decline later messages and report [Error::Completed] to
+  /// consumers once the messages they hold are drained, targets that
+  /// don't track completion ignore it.
+  async fn complete(&self) {}
 }
 
 pub async fn send<T: Sized + Send + Clone + Serialize + DeserializeOwned>(
@@ -72,9 +103,8 @@ mod tests {
   };
 
   use super::*;
-  use futures_await_test::async_test;
+  use crate::test_rt;
 
-  #[async_test]
   async fn send_receive_unbounded() {
     let unbounded: Arc<UnboundedBuffer<u64>> = Arc::new(UnboundedBuffer::new());
 
@@ -85,8 +115,8 @@ mod tests {
     assert_eq!(receive(&*unbounded).await.unwrap(), 20);
 
     let ub_t = unbounded.clone();
-    let t_insert = async_std::task::spawn(async move {
-      std::thread::sleep(Duration::from_secs(3));
+    let t_insert = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_secs(3)).await;
       assert_eq!(ub_t.accept(Message::new(30)).await, MessageStatus::Accepted);
     });
 
@@ -95,4 +125,6 @@ mod tests {
     assert!(start.elapsed() >= Duration::from_secs(2));
     t_insert.await;
   }
+
+  matrix_tests!(send_receive_unbounded);
 }
@@ -1,10 +1,12 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use crate::{Message, MessageStatus, Result, Source, Target};
+use super::sync::{AtomicBool, Ordering};
+use crate::{Error, Message, MessageStatus, Result, Source, Target};
 
-use async_std::sync::{Condvar, Mutex, RwLock, RwLockUpgradableReadGuard};
+use async_lock::{RwLock, RwLockUpgradableReadGuard};
 use async_trait::async_trait;
+use event_listener::Event;
 use serde::{de::DeserializeOwned, Serialize};
 
 /// Stores one message that can be written to and read from multiple times.
@@ -16,7 +18,10 @@ where
   value: RwLock<Option<Message<T>>>,
 
   /// used to notify anyone attempting to receive before the value is set
-  notify: (Mutex<()>, Condvar),
+  notify: Event,
+
+  /// no more values are accepted once set
+  completed: AtomicBool,
 }
 
 impl<T> OverwriteBuffer<T>
@@ -26,7 +31,8 @@ where
   pub fn new() -> Self {
     OverwriteBuffer {
       value: RwLock::new(None),
-      notify: (Mutex::new(()), Condvar::new()),
+      notify: Event::new(),
+      completed: AtomicBool::new(false),
     }
   }
 }
@@ -41,12 +47,20 @@ where
   }
 
   async fn consume(&self) -> Result<Message<T>> {
-    if let Some(ref message) = self.try_consume() {
-      Ok(message.clone())
-    } else {
-      let lock = self.notify.0.lock().await;
-      self.notify.1.wait(lock).await;
-      self.consume().await
+    let mut listener = None;
+    loop {
+      if let Some(ref message) = *self.value.read().await {
+        return Ok(message.clone());
+      }
+      if self.completed.load(Ordering::SeqCst) {
+        return Err(Error::Completed);
+      }
+      // the value is checked once more after registering a listener,
+      // so a write that happens in between can't be missed
+      match listener.take() {
+        Some(listener) => listener.await,
+        None => listener = Some(self.notify.listen()),
+      }
     }
   }
 }
@@ -60,6 +74,9 @@ where
   /// id and alwyas signals changes by notifying any awaiting receives
   async fn accept(&self, message: Message<T>) -> MessageStatus {
     let access = self.value.upgradable_read().await;
+    if self.completed.load(Ordering::SeqCst) {
+      return MessageStatus::Declined;
+    }
     if let Some(ref existing) = *access {
       if existing.id() == message.id() {
         return MessageStatus::Declined;
@@ -67,35 +84,41 @@ where
     }
     let mut writer = RwLockUpgradableReadGuard::upgrade(access).await;
     (*writer).replace(message);
-    self.notify.1.notify_all();
+    self.notify.notify(usize::MAX);
     MessageStatus::Accepted
   }
+
+  /// Consumers keep reading the stored value, if there is one
+  async fn complete(&self) {
+    let _writer = self.value.write().await;
+    self.completed.store(true, Ordering::SeqCst);
+    self.notify.notify(usize::MAX);
+  }
 }
 
 #[cfg(test)]
 mod tests {
 
   use super::*;
-  use futures_await_test::async_test;
+  use crate::test_rt;
   use std::{
     sync::Arc,
     time::{Duration, Instant},
   };
 
-  #[async_test]
   async fn sanity() {
     let wobuf: Arc<OverwriteBuffer<u64>> = Arc::new(OverwriteBuffer::new());
 
     assert_eq!(wobuf.try_consume(), None);
 
     let wobuf_t = wobuf.clone();
-    let t_write = async_std::task::spawn(async move {
-      std::thread::sleep(Duration::from_secs(1));
+    let t_write = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_secs(1)).await;
       assert_eq!(
         wobuf_t.accept(Message::new(10)).await,
         MessageStatus::Accepted
       );
-      std::thread::sleep(Duration::from_secs(1));
+      test_rt::sleep(Duration::from_secs(1)).await;
       assert_eq!(
         wobuf_t.accept(Message::new(20)).await,
         MessageStatus::Accepted
@@ -104,10 +127,12 @@ mod tests {
 
     let start = Instant::now();
     assert_eq!(*wobuf.consume().await.unwrap().payload(), 10u64);
-    std::thread::sleep(Duration::from_secs(2));
+    test_rt::sleep(Duration::from_secs(2)).await;
     assert_eq!(*wobuf.consume().await.unwrap().payload(), 20u64); // double read
     assert_eq!(*wobuf.try_consume().unwrap().payload(), 20u64);
     assert!(start.elapsed() >= Duration::from_secs(2));
     t_write.await;
   }
+
+  matrix_tests!(sanity);
 }
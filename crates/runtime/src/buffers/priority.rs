@@ -0,0 +1,309 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::{Error, Message, MessageId, MessageStatus, Result, Source, Target};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use event_listener::Event;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  cmp::Reverse,
+  collections::{BTreeMap, HashMap},
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Messages ordered by priority, then by arrival for equal priorities.
+struct Queue<T, K>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  /// the last entry is the next message to be consumed
+  ordered: BTreeMap<(K, Reverse<u64>), Message<T>>,
+  /// where each stored message is in the ordered map
+  positions: HashMap<MessageId, (K, u64)>,
+  /// arrival counter, breaks ties between equal priorities
+  sequence: u64,
+  /// no more messages are accepted once set
+  completed: bool,
+}
+
+impl<T, K> Queue<T, K>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+  K: Ord + Clone,
+{
+  fn insert(&mut self, message: Message<T>, priority: K) {
+    self.sequence += 1;
+    self
+      .positions
+      .insert(message.id(), (priority.clone(), self.sequence));
+    self
+      .ordered
+      .insert((priority, Reverse(self.sequence)), message);
+  }
+
+  fn pop_highest(&mut self) -> Option<Message<T>> {
+    let key = self.ordered.keys().next_back()?.clone();
+    self.remove(key)
+  }
+
+  fn pop_lowest(&mut self) -> Option<Message<T>> {
+    let key = self.ordered.keys().next()?.clone();
+    self.remove(key)
+  }
+
+  fn lowest(&self) -> Option<&K> {
+    self.ordered.keys().next().map(|(priority, _)| priority)
+  }
+
+  fn remove(&mut self, key: (K, Reverse<u64>)) -> Option<Message<T>> {
+    let message = self.ordered.remove(&key)?;
+    self.positions.remove(&message.id());
+    Some(message)
+  }
+}
+
+/// A buffer of messages of type T that forwards the message with the
+/// highest priority first. Priorities are computed by a caller-supplied
+/// function when a message is accepted and can be changed afterwards
+/// by message id. Messages of equal priority are forwarded in the order
+/// they were accepted.
+///
+/// When created with a capacity, accepting a message into a full buffer
+/// evicts the message with the lowest priority, or declines the offered
+/// message if nothing stored has a lower priority than it.
+pub struct PriorityBuffer<T, K>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  /// computes the priority of accepted messages
+  priority: Box<dyn Fn(&T) -> K + Send + Sync>,
+  /// the maximum number of stored messages, if any
+  capacity: Option<usize>,
+  /// messages that were not consumed yet by targets
+  pending: Mutex<Queue<T, K>>,
+  /// used to signal waiting consumers that a message was stored
+  notify: Event,
+  /// messages that were declined or evicted because of capacity
+  dropped: AtomicU64,
+}
+
+impl<T, K> PriorityBuffer<T, K>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+  K: Ord + Clone + Send,
+{
+  pub fn new<F>(priority: F) -> Self
+  where
+    F: Fn(&T) -> K + Send + Sync + 'static,
+  {
+    PriorityBuffer {
+      priority: Box::new(priority),
+      capacity: None,
+      pending: Mutex::new(Queue {
+        ordered: BTreeMap::new(),
+        positions: HashMap::new(),
+        sequence: 0,
+        completed: false,
+      }),
+      notify: Event::new(),
+      dropped: AtomicU64::new(0),
+    }
+  }
+
+  /// Creates a buffer that stores at most `capacity` messages.
+  pub fn with_capacity<F>(capacity: usize, priority: F) -> Self
+  where
+    F: Fn(&T) -> K + Send + Sync + 'static,
+  {
+    assert!(capacity > 0, "priority buffer capacity has to be positive");
+    PriorityBuffer {
+      capacity: Some(capacity),
+      ..Self::new(priority)
+    }
+  }
+
+  pub fn capacity(&self) -> Option<usize> {
+    self.capacity
+  }
+
+  /// The number of messages lost to capacity since this buffer was
+  /// created, either declined or evicted.
+  pub fn dropped(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+
+  /// Changes the priority of a stored message. Returns false if no
+  /// message with this id is waiting to be consumed.
+  pub async fn reprioritize(&self, id: MessageId, priority: K) -> bool {
+    let mut pending = self.pending.lock().await;
+    let (previous, sequence) = match pending.positions.get(&id) {
+      Some(position) => position.clone(),
+      None => return false,
+    };
+    let message = pending
+      .ordered
+      .remove(&(previous, Reverse(sequence)))
+      .expect("positions and ordered messages are in sync");
+    pending.positions.insert(id, (priority.clone(), sequence));
+    pending
+      .ordered
+      .insert((priority, Reverse(sequence)), message);
+    true
+  }
+
+  #[cfg(test)]
+  pub(crate) async fn count(&self) -> usize {
+    self.pending.lock().await.ordered.len()
+  }
+}
+
+#[async_trait]
+impl<T, K> Source<T> for PriorityBuffer<T, K>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+  K: Ord + Clone + Send,
+{
+  fn try_consume(&self) -> Option<Message<T>> {
+    self.pending.try_lock()?.pop_highest()
+  }
+
+  async fn consume(&self) -> Result<Message<T>> {
+    loop {
+      let mut pending = self.pending.lock().await;
+      if let Some(message) = pending.pop_highest() {
+        return Ok(message);
+      }
+      if pending.completed {
+        return Err(Error::Completed);
+      }
+      let listener = self.notify.listen();
+      drop(pending);
+      listener.await;
+    }
+  }
+}
+
+#[async_trait]
+impl<T, K> Target<T> for PriorityBuffer<T, K>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+  K: Ord + Clone + Send,
+{
+  /// Declines messages with an id that is already stored
+  async fn accept(&self, message: Message<T>) -> MessageStatus {
+    let priority = (self.priority)(message.payload());
+    let mut pending = self.pending.lock().await;
+    if pending.completed || pending.positions.contains_key(&message.id()) {
+      return MessageStatus::Declined;
+    }
+
+    if let Some(capacity) = self.capacity {
+      if pending.ordered.len() >= capacity {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        match pending.lowest() {
+          Some(lowest) if *lowest < priority => pending.pop_lowest(),
+          _ => return MessageStatus::Declined,
+        };
+      }
+    }
+
+    pending.insert(message, priority);
+    self.notify.notify_additional(1);
+    MessageStatus::Accepted
+  }
+
+  async fn complete(&self) {
+    self.pending.lock().await.completed = true;
+    self.notify.notify(usize::MAX);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive};
+  use std::{sync::Arc, time::Duration};
+
+  async fn order_test() -> Result<()> {
+    let pbuf = PriorityBuffer::new(|v: &(u64, char)| v.0);
+
+    send(&pbuf, (1, 'a')).await;
+    send(&pbuf, (3, 'b')).await;
+    send(&pbuf, (2, 'c')).await;
+    send(&pbuf, (3, 'd')).await;
+
+    // equal priorities are consumed in the order they were accepted
+    assert_eq!(receive(&pbuf).await?, (3, 'b'));
+    assert_eq!(receive(&pbuf).await?, (3, 'd'));
+    assert_eq!(receive(&pbuf).await?, (2, 'c'));
+    assert_eq!(try_receive(&pbuf), Some((1, 'a')));
+    assert_eq!(try_receive(&pbuf), None);
+
+    Ok(())
+  }
+
+  async fn capacity_test() -> Result<()> {
+    let pbuf = PriorityBuffer::with_capacity(2, |v: &u64| *v);
+
+    assert_eq!(send(&pbuf, 20).await, MessageStatus::Accepted);
+    assert_eq!(send(&pbuf, 10).await, MessageStatus::Accepted);
+
+    // evicts 10, then the buffer holds nothing lower than 5 or 20
+    assert_eq!(send(&pbuf, 30).await, MessageStatus::Accepted);
+    assert_eq!(send(&pbuf, 5).await, MessageStatus::Declined);
+    assert_eq!(send(&pbuf, 20).await, MessageStatus::Declined);
+    assert_eq!(pbuf.count().await, 2);
+    assert_eq!(pbuf.dropped(), 3);
+
+    assert_eq!(receive(&pbuf).await?, 30);
+    assert_eq!(receive(&pbuf).await?, 20);
+
+    Ok(())
+  }
+
+  async fn reprioritize_test() -> Result<()> {
+    let pbuf = PriorityBuffer::new(|v: &u64| *v);
+
+    let message = Message::new(1);
+    let id = message.id();
+    pbuf.accept(message).await;
+    send(&pbuf, 2).await;
+    send(&pbuf, 3).await;
+
+    assert!(pbuf.reprioritize(id, 10).await);
+    assert_eq!(receive(&pbuf).await?, 1);
+    assert!(!pbuf.reprioritize(id, 10).await);
+    assert_eq!(receive(&pbuf).await?, 3);
+
+    // an id is only stored once
+    let message = Message::new(4);
+    assert_eq!(pbuf.accept(message.clone()).await, MessageStatus::Accepted);
+    assert_eq!(pbuf.accept(message).await, MessageStatus::Declined);
+
+    Ok(())
+  }
+
+  async fn consume_wait_test() -> Result<()> {
+    let pbuf = Arc::new(PriorityBuffer::new(|v: &u64| *v));
+
+    let pbuf_t = pbuf.clone();
+    let t_insert = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_millis(500)).await;
+      assert_eq!(send(&*pbuf_t, 10).await, MessageStatus::Accepted);
+    });
+
+    assert_eq!(receive(&*pbuf).await?, 10);
+    t_insert.await;
+
+    Ok(())
+  }
+
+  matrix_tests!(
+    order_test,
+    capacity_test,
+    reprioritize_test,
+    consume_wait_test
+  );
+}
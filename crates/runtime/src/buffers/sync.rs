@@ -0,0 +1,33 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Atomics and queues that loom can model when the crate is built
+//! with `--cfg loom`.
+
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(not(loom))]
+pub(crate) use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(not(loom))]
+pub(crate) use crossbeam_queue::SegQueue as Queue;
+
+/// Loom can't see into the atomics of a lock-free queue, so models
+/// run against a locked queue with the same interface instead.
+#[cfg(loom)]
+pub(crate) struct Queue<T>(loom::sync::Mutex<std::collections::VecDeque<T>>);
+
+#[cfg(loom)]
+impl<T> Queue<T> {
+  pub(crate) fn new() -> Self {
+    Queue(loom::sync::Mutex::new(std::collections::VecDeque::new()))
+  }
+
+  pub(crate) fn push(&self, value: T) {
+    self.0.lock().unwrap().push_back(value);
+  }
+
+  pub(crate) fn pop(&self) -> Option<T> {
+    self.0.lock().unwrap().pop_front()
+  }
+}
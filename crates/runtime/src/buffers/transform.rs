@@ -1,14 +1,332 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use std::marker::PhantomData;
+use super::{Message, MessageStatus, Result, Source, Target, UnboundedBuffer};
+
+use async_lock::Semaphore;
+use async_trait::async_trait;
+use futures_lite::future::Boxed;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  fmt::Display,
+  future::Future,
+  pin::Pin,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+};
+
+type Transform<In, Out> =
+  dyn Fn(In) -> Pin<Box<dyn Future<Output = Option<Out>> + Send>> + Send + Sync;
+
+/// Starts a transform in the background, usually by spawning it
+/// on the executor that runs the rest of the program.
+type Spawn = dyn Fn(Boxed<()>) + Send + Sync;
+
+/// The outcome of transforming one message. Messages that
+/// transform into [None] or an [Err] are dropped.
+pub trait Transformed<Out> {
+  fn into_output(self) -> Option<Out>;
+}
+
+impl<Out> Transformed<Out> for Option<Out> {
+  fn into_output(self) -> Option<Out> {
+    self
+  }
+}
+
+impl<Out, E: Display> Transformed<Out> for std::result::Result<Out, E> {
+  fn into_output(self) -> Option<Out> {
+    self
+      .map_err(|e| log::debug!("transform dropped a message: {}", e))
+      .ok()
+  }
+}
 
 /// Performs work when it receives data and sends the
 /// result of that work to another buffer.
 ///
 /// The [TransformBuffer] class can act on different
-/// input and output types.
-pub struct TransformBuffer<In, Out> {
-  _unused1: PhantomData<In>,
-  _unused2: PhantomData<Out>,
+/// input and output types. Each accepted message is passed
+/// through an async transform that runs in the background,
+/// and its result is buffered under the id of the input
+/// message, so it can be traced through all stages of a
+/// pipeline. Messages that don't transform into a value are
+/// dropped, which makes this buffer a filter as well.
+pub struct TransformBuffer<In, Out>
+where
+  In: Sized + Send + Clone + Serialize + DeserializeOwned,
+  Out: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  /// the work performed on every message
+  transform: Box<Transform<In, Out>>,
+  /// runs the transforms in the background
+  spawn: Box<Spawn>,
+  /// limits how many transforms may run at the same time,
+  /// every running transform holds one slot until it is buffered
+  slots: Arc<Semaphore>,
+  /// the number of slots, all of them are taken while completing
+  concurrency: usize,
+  /// no more messages are accepted once set
+  completed: AtomicBool,
+  /// transformed messages that were not consumed yet
+  output: Arc<UnboundedBuffer<Out>>,
+}
+
+impl<In, Out> TransformBuffer<In, Out>
+where
+  In: Sized + Send + Clone + Serialize + DeserializeOwned,
+  Out: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  /// Creates a buffer that runs at most `concurrency` transforms at
+  /// a time, offers beyond that wait until one of them completes.
+  /// Every transform is started through `spawn`:
+  ///
+  /// ```ignore
+  /// let hashes = TransformBuffer::new(4, |task| { tokio::spawn(task); }, hash);
+  /// ```
+  pub fn new<S, F, Fut>(concurrency: usize, spawn: S, transform: F) -> Self
+  where
+    S: Fn(Boxed<()>) + Send + Sync + 'static,
+    F: Fn(In) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Transformed<Out>,
+  {
+    assert!(concurrency > 0, "transform concurrency has to be positive");
+    TransformBuffer {
+      transform: Box::new(move |input| {
+        let output = transform(input);
+        Box::pin(async move { output.await.into_output() })
+      }),
+      spawn: Box::new(spawn),
+      slots: Arc::new(Semaphore::new(concurrency)),
+      concurrency,
+      completed: AtomicBool::new(false),
+      output: Arc::new(UnboundedBuffer::new()),
+    }
+  }
+}
+
+#[async_trait]
+impl<In, Out> Target<In> for TransformBuffer<In, Out>
+where
+  In: Sized + Send + Clone + Serialize + DeserializeOwned,
+  Out: Sized + Send + Clone + Serialize + DeserializeOwned + 'static,
+{
+  /// Waits for a free slot and starts transforming the message,
+  /// its result shows up in the output once the transform is done.
+  async fn accept(&self, message: Message<In>) -> MessageStatus {
+    let id = message.id();
+    let slot = self.slots.acquire_arc().await;
+    if self.completed.load(Ordering::SeqCst) {
+      return MessageStatus::Declined;
+    }
+    let transformed = (self.transform)(message.release());
+    let output = self.output.clone();
+    (self.spawn)(Box::pin(async move {
+      if let Some(value) = transformed.await {
+        output.accept(Message::with_id(value, id)).await;
+      }
+      drop(slot);
+    }));
+    MessageStatus::Accepted
+  }
+
+  /// Waits for transforms that are already running, their results
+  /// are still buffered, and declines messages offered afterwards.
+  async fn complete(&self) {
+    self.completed.store(true, Ordering::SeqCst);
+    let mut slots = Vec::with_capacity(self.concurrency);
+    for _ in 0..self.concurrency {
+      slots.push(self.slots.acquire().await);
+    }
+    self.output.complete().await;
+  }
+}
+
+#[async_trait]
+impl<In, Out> Source<Out> for TransformBuffer<In, Out>
+where
+  In: Sized + Send + Clone + Serialize + DeserializeOwned,
+  Out: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<Out>> {
+    self.output.try_consume()
+  }
+
+  async fn consume(&self) -> Result<Message<Out>> {
+    self.output.consume().await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, Error};
+  use std::{
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc,
+    },
+    time::Duration,
+  };
+
+  fn background(task: Boxed<()>) {
+    drop(test_rt::spawn(task));
+  }
+
+  /// Doubles values after a while and records how many
+  /// transforms ran at the same time in `peak`.
+  fn slow_doubler(concurrency: usize, peak: Arc<AtomicUsize>) -> Arc<TransformBuffer<u64, u64>> {
+    let running = Arc::new(AtomicUsize::new(0));
+    Arc::new(TransformBuffer::new(
+      concurrency,
+      background,
+      move |value: u64| {
+        let (running, peak) = (running.clone(), peak.clone());
+        async move {
+          let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+          peak.fetch_max(now, Ordering::SeqCst);
+          test_rt::sleep(Duration::from_millis(50)).await;
+          running.fetch_sub(1, Ordering::SeqCst);
+          Some(value * 2)
+        }
+      },
+    ))
+  }
+
+  async fn map_filter_test() -> Result<()> {
+    let even = TransformBuffer::new(1, background, |value: u64| async move {
+      match value % 2 {
+        0 => Some(value.to_string()),
+        _ => None,
+      }
+    });
+
+    let message = Message::new(10);
+    let id = message.id();
+    assert_eq!(even.accept(message).await, MessageStatus::Accepted);
+    assert_eq!(send(&even, 11).await, MessageStatus::Accepted);
+    assert_eq!(send(&even, 12).await, MessageStatus::Accepted);
+
+    let transformed = even.consume().await?;
+    assert_eq!(transformed.id(), id);
+    assert_eq!(transformed.payload(), "10");
+    assert_eq!(receive(&even).await?, "12");
+    assert_eq!(even.try_consume(), None);
+
+    Ok(())
+  }
+
+  async fn result_test() -> Result<()> {
+    let parse = TransformBuffer::new(1, background, |value: String| async move {
+      value.parse::<u64>()
+    });
+
+    assert_eq!(send(&parse, "x".into()).await, MessageStatus::Accepted);
+    assert_eq!(send(&parse, "7".into()).await, MessageStatus::Accepted);
+    assert_eq!(receive(&parse).await?, 7);
+    assert_eq!(parse.try_consume(), None);
+
+    Ok(())
+  }
+
+  async fn pipeline_test() -> Result<()> {
+    let decode = TransformBuffer::new(1, background, |bytes: Vec<u8>| async move {
+      String::from_utf8(bytes)
+    });
+    let validate = TransformBuffer::new(1, background, |text: String| async move {
+      Some(text).filter(|t| !t.is_empty())
+    });
+
+    let message = Message::new(b"tx".to_vec());
+    let id = message.id();
+    assert_eq!(decode.accept(message).await, MessageStatus::Accepted);
+    assert_eq!(
+      validate.accept(decode.consume().await?).await,
+      MessageStatus::Accepted
+    );
+    assert_eq!(validate.consume().await?.id(), id);
+
+    Ok(())
+  }
+
+  async fn concurrency_test() -> Result<()> {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let slow = slow_doubler(2, peak.clone());
+
+    let senders: Vec<_> = (0..6u64)
+      .map(|i| {
+        let slow = slow.clone();
+        test_rt::spawn(async move { send(&*slow, i).await })
+      })
+      .collect();
+
+    for sender in senders {
+      assert_eq!(sender.await, MessageStatus::Accepted);
+    }
+
+    let mut sum = 0;
+    for _ in 0..6 {
+      sum += receive(&*slow).await?;
+    }
+    assert_eq!(sum, 30);
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+    Ok(())
+  }
+
+  async fn sequential_producer_test() -> Result<()> {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let slow = slow_doubler(2, peak.clone());
+
+    // a single producer still keeps all slots busy
+    for i in 0..6u64 {
+      assert_eq!(send(&*slow, i).await, MessageStatus::Accepted);
+    }
+
+    let mut sum = 0;
+    for _ in 0..6 {
+      sum += receive(&*slow).await?;
+    }
+    assert_eq!(sum, 30);
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+    Ok(())
+  }
+
+  async fn complete_test() -> Result<()> {
+    let slow = Arc::new(TransformBuffer::new(
+      1,
+      background,
+      |value: u64| async move {
+        test_rt::sleep(Duration::from_millis(200)).await;
+        Some(value)
+      },
+    ));
+
+    let slow_t = slow.clone();
+    let t_send = test_rt::spawn(async move { send(&*slow_t, 1).await });
+    test_rt::sleep(Duration::from_millis(50)).await;
+
+    // the running transform finishes before completion
+    slow.complete().await;
+    assert_eq!(t_send.await, MessageStatus::Accepted);
+    assert_eq!(send(&*slow, 2).await, MessageStatus::Declined);
+    assert_eq!(receive(&*slow).await?, 1);
+    assert!(matches!(slow.consume().await, Err(Error::Completed)));
+
+    Ok(())
+  }
+
+  matrix_tests!(
+    map_filter_test,
+    result_test,
+    pipeline_test,
+    concurrency_test,
+    sequential_producer_test,
+    complete_test
+  );
 }
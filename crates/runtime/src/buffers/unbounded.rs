@@ -1,10 +1,12 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use super::{Message, MessageStatus, Result, Source, Target};
-use async_std::sync::{Condvar, Mutex};
+use super::{
+  sync::{AtomicUsize, Ordering, Queue},
+  Error, Message, MessageStatus, Result, Source, Target,
+};
 use async_trait::async_trait;
-use crossbeam_queue::SegQueue;
+use event_listener::Event;
 use serde::{de::DeserializeOwned, Serialize};
 
 /// An unbounded buffer of messages of type T.
@@ -14,19 +16,26 @@ where
   T: Sized + Send + Clone + Serialize + DeserializeOwned
 {
   /// messages that were not consumed yet by targets
-  pending: SegQueue<Message<T>>,
+  pending: Queue<Message<T>>,
   /// used to signal changes to the buffer for waiting consumers
-  notify: (Mutex<()>, Condvar),
+  notify: Event,
+  /// [COMPLETED] once no more messages are accepted, plus [IN_FLIGHT]
+  /// for every accept that passed that check and did not push yet
+  state: AtomicUsize,
 }
 
+const COMPLETED: usize = 1;
+const IN_FLIGHT: usize = 2;
+
 impl<T> UnboundedBuffer<T>
 where
   T: Sized + Send + Clone + Serialize + DeserializeOwned
 {
   pub fn new() -> Self {
     UnboundedBuffer {
-      pending: SegQueue::new(),
-      notify: (Mutex::new(()), Condvar::new()),
+      pending: Queue::new(),
+      notify: Event::new(),
+      state: AtomicUsize::new(0),
     }
   }
 
@@ -46,12 +55,22 @@ where
   }
 
   async fn consume(&self) -> Result<Message<T>> {
-    if let Some(value) = self.pending.pop() {
-      Ok(value)
-    } else {
-      let lock = self.notify.0.lock().await;
-      self.notify.1.wait(lock).await;
-      self.consume().await
+    let mut listener = None;
+    loop {
+      if let Some(value) = self.pending.pop() {
+        return Ok(value);
+      }
+      // once completed with no accept in flight nothing is pushed
+      // anymore, but a message may have landed right after the pop above
+      if self.state.load(Ordering::SeqCst) == COMPLETED {
+        return self.pending.pop().ok_or(Error::Completed);
+      }
+      // the queue is checked once more after registering a listener,
+      // so a message that arrives in between can't be missed
+      match listener.take() {
+        Some(listener) => listener.await,
+        None => listener = Some(self.notify.listen()),
+      }
     }
   }
 }
@@ -62,9 +81,27 @@ where
   T: Sized + Send + Clone + Serialize + DeserializeOwned
 {
   async fn accept(&self, message: Message<T>) -> MessageStatus {
-    self.pending.push(message);
-    self.notify.1.notify_one();
-    MessageStatus::Accepted
+    // marking the accept as in flight and checking for completion is one
+    // step, so a consumer can't see the buffer completed and drained
+    // while a message it would have to hand out is still being pushed
+    let accepted = self.state.fetch_add(IN_FLIGHT, Ordering::SeqCst) & COMPLETED == 0;
+    if accepted {
+      self.pending.push(message);
+      self.notify.notify_additional(1);
+    }
+    if self.state.fetch_sub(IN_FLIGHT, Ordering::SeqCst) == COMPLETED | IN_FLIGHT {
+      // consumers that saw completion wait for the last accept in flight
+      self.notify.notify(usize::MAX);
+    }
+    match accepted {
+      true => MessageStatus::Accepted,
+      false => MessageStatus::Declined,
+    }
+  }
+
+  async fn complete(&self) {
+    self.state.fetch_or(COMPLETED, Ordering::SeqCst);
+    self.notify.notify(usize::MAX);
   }
 }
 
@@ -72,11 +109,11 @@ where
 mod tests {
 
   use super::*;
+  use crate::CancellationToken;
   use std::sync::Arc;
 
-  use futures_await_test::async_test;
+  use crate::test_rt;
 
-  #[async_test]
   async fn sanity_test() {
     let ubuf: UnboundedBuffer<u64> = UnboundedBuffer::new();
     let status = ubuf.accept(Message::new(10)).await;
@@ -87,7 +124,40 @@ mod tests {
     assert_eq!(*dequed.unwrap(), 10);
   }
 
-  #[async_test]
+  async fn complete_test() {
+    let ubuf: Arc<UnboundedBuffer<u64>> = Arc::new(UnboundedBuffer::new());
+    ubuf.accept(Message::new(10)).await;
+
+    let ubuf_t = ubuf.clone();
+    let t_consume = test_rt::spawn(async move {
+      assert_eq!(*ubuf_t.consume().await.unwrap(), 10);
+      assert!(matches!(ubuf_t.consume().await, Err(Error::Completed)));
+    });
+
+    test_rt::sleep(std::time::Duration::from_millis(100)).await;
+    ubuf.complete().await;
+    assert_eq!(ubuf.accept(Message::new(20)).await, MessageStatus::Declined);
+    t_consume.await;
+  }
+
+  async fn cancel_test() {
+    let ubuf: UnboundedBuffer<u64> = UnboundedBuffer::new();
+    let token = CancellationToken::new();
+    ubuf.accept(Message::new(10)).await;
+    assert_eq!(*ubuf.consume_cancellable(&token).await.unwrap(), 10);
+
+    let token_t = token.clone();
+    let t_cancel = test_rt::spawn(async move {
+      test_rt::sleep(std::time::Duration::from_millis(100)).await;
+      token_t.cancel();
+    });
+    assert!(matches!(
+      ubuf.consume_cancellable(&token).await,
+      Err(Error::Cancelled)
+    ));
+    t_cancel.await;
+  }
+
   async fn mt_try_consume_test() {
     let ubuf: Arc<UnboundedBuffer<u64>> = Arc::new(UnboundedBuffer::new());
 
@@ -136,7 +206,6 @@ mod tests {
     assert_eq!(counter, 2000);
   }
 
-  #[async_test]
   async fn mt_consume_test() {
     let ubuf: Arc<UnboundedBuffer<u64>> = Arc::new(UnboundedBuffer::new());
 
@@ -185,4 +254,6 @@ mod tests {
     t2.join().expect("t2 paniced").await;
     t3.join().expect("t2 paniced").await;
   }
+
+  matrix_tests!(sanity_test, complete_test, cancel_test, mt_try_consume_test, mt_consume_test);
 }
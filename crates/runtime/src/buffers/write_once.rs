@@ -1,10 +1,14 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use super::{Message, MessageStatus, Result, Source, Target};
+use super::{
+  sync::{AtomicBool, Ordering},
+  Error, Message, MessageStatus, Result, Source, Target,
+};
 
-use async_std::sync::{Condvar, Mutex, RwLock, RwLockUpgradableReadGuard};
+use async_lock::{RwLock, RwLockUpgradableReadGuard};
 use async_trait::async_trait;
+use event_listener::Event;
 use serde::{de::DeserializeOwned, Serialize};
 
 /// Stores one message that can be written to one
@@ -17,7 +21,10 @@ where
   value: RwLock<Option<Message<T>>>,
 
   /// used to notify anyone attempting to receive before the value is set
-  notify: (Mutex<()>, Condvar),
+  notify: Event,
+
+  /// no more values are accepted once set
+  completed: AtomicBool,
 }
 
 impl<T> WriteOnceBuffer<T>
@@ -27,7 +34,8 @@ where
   pub fn new() -> Self {
     WriteOnceBuffer {
       value: RwLock::new(None),
-      notify: (Mutex::new(()), Condvar::new()),
+      notify: Event::new(),
+      completed: AtomicBool::new(false),
     }
   }
 }
@@ -42,12 +50,21 @@ where
   }
 
   async fn consume(&self) -> Result<Message<T>> {
-    if let Some(ref message) = self.try_consume() {
-      Ok(message.clone())
-    } else {
-      let lock = self.notify.0.lock().await;
-      self.notify.1.wait(lock).await;
-      self.consume().await
+    let mut listener = None;
+    loop {
+      if let Some(ref message) = *self.value.read().await {
+        return Ok(message.clone());
+      }
+      if self.completed.load(Ordering::SeqCst) {
+        // a value written right before completion is still handed out
+        return self.value.read().await.clone().ok_or(Error::Completed);
+      }
+      // the value is checked once more after registering a listener,
+      // so a write that happens in between can't be missed
+      match listener.take() {
+        Some(listener) => listener.await,
+        None => listener = Some(self.notify.listen()),
+      }
     }
   }
 }
@@ -61,37 +78,46 @@ where
   /// afterwards
   async fn accept(&self, message: Message<T>) -> MessageStatus {
     let access = self.value.upgradable_read().await;
+    if self.completed.load(Ordering::SeqCst) {
+      return MessageStatus::Declined;
+    }
     match *access {
       Some(_) => MessageStatus::Declined,
       None => {
         let mut writer = RwLockUpgradableReadGuard::upgrade(access).await;
         *writer = Some(message);
-        self.notify.1.notify_all();
+        self.notify.notify(usize::MAX);
         MessageStatus::Accepted
       }
     }
   }
+
+  /// Consumers keep reading the stored value, if there is one
+  async fn complete(&self) {
+    let _writer = self.value.write().await;
+    self.completed.store(true, Ordering::SeqCst);
+    self.notify.notify(usize::MAX);
+  }
 }
 
 #[cfg(test)]
 mod tests {
 
   use super::*;
-  use futures_await_test::async_test;
+  use crate::test_rt;
   use std::{
     sync::Arc,
     time::{Duration, Instant},
   };
 
-  #[async_test]
   async fn sanity_test() {
     let wobuf: Arc<WriteOnceBuffer<u64>> = Arc::new(WriteOnceBuffer::new());
 
     assert_eq!(wobuf.try_consume(), None);
 
     let wobuf_t = wobuf.clone();
-    let t_write = async_std::task::spawn(async move {
-      std::thread::sleep(Duration::from_secs(3));
+    let t_write = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_secs(3)).await;
       assert_eq!(
         wobuf_t.accept(Message::new(10)).await,
         MessageStatus::Accepted
@@ -110,4 +136,6 @@ mod tests {
     assert!(start.elapsed() >= Duration::from_secs(2));
     t_write.await;
   }
+
+  matrix_tests!(sanity_test);
 }
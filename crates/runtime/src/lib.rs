@@ -1,6 +1,10 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
+#[cfg(test)]
+#[macro_use]
+mod test_rt;
+
 pub mod agent;
 pub mod nodes;
 pub mod buffers;
@@ -0,0 +1,235 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Error, Message, MessageStatus, Result, Source, Target};
+use async_io::Timer;
+use async_lock::Mutex;
+use async_trait::async_trait;
+use event_listener::Event;
+use futures_lite::FutureExt;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  collections::VecDeque,
+  mem,
+  time::{Duration, Instant},
+};
+
+struct Batches<T> {
+  /// the batch that is currently being filled
+  current: Vec<T>,
+  /// when the first item of the current batch arrived
+  started: Option<Instant>,
+  /// batches that are complete and were not consumed yet
+  ready: VecDeque<Vec<T>>,
+  /// no more items are accepted once set
+  completed: bool,
+}
+
+impl<T> Batches<T> {
+  fn deadline(&self, timeout: Duration) -> Option<Instant> {
+    self.started.map(|started| started + timeout)
+  }
+
+  fn cut(&mut self) {
+    self.started = None;
+    let batch = mem::take(&mut self.current);
+    self.ready.push_back(batch);
+  }
+
+  /// Completes the current batch if its timeout elapsed and returns
+  /// the oldest complete batch.
+  fn pop(&mut self, timeout: Duration) -> Option<Vec<T>> {
+    if matches!(self.deadline(timeout), Some(d) if d <= Instant::now()) {
+      self.cut();
+    }
+    self.ready.pop_front()
+  }
+}
+
+/// A single-input, single-output node that groups incoming messages into
+/// batches. A batch is emitted when it holds `size` items or when `timeout`
+/// elapsed since its first item arrived, whichever happens first.
+pub struct BatchNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  size: usize,
+  timeout: Duration,
+  batches: Mutex<Batches<T>>,
+  /// used to signal waiting consumers that a batch was started or completed
+  notify: Event,
+}
+
+impl<T> BatchNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  pub fn new(size: usize, timeout: Duration) -> Self {
+    assert!(size > 0, "batch size has to be positive");
+    BatchNode {
+      size,
+      timeout,
+      batches: Mutex::new(Batches {
+        current: Vec::with_capacity(size),
+        started: None,
+        ready: VecDeque::new(),
+        completed: false,
+      }),
+      notify: Event::new(),
+    }
+  }
+}
+
+#[async_trait]
+impl<T> Target<T> for BatchNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  async fn accept(&self, message: Message<T>) -> MessageStatus {
+    let mut batches = self.batches.lock().await;
+    if batches.completed {
+      return MessageStatus::Declined;
+    }
+
+    // a batch that timed out before anyone consumed it is not extended
+    if matches!(batches.deadline(self.timeout), Some(d) if d <= Instant::now()) {
+      batches.cut();
+    }
+
+    if batches.started.is_none() {
+      batches.started = Some(Instant::now());
+    }
+    batches.current.push(message.release());
+    if batches.current.len() == self.size {
+      batches.cut();
+    }
+
+    self.notify.notify(usize::MAX);
+    MessageStatus::Accepted
+  }
+
+  /// Emits the partial batch right away, if there is one.
+  async fn complete(&self) {
+    let mut batches = self.batches.lock().await;
+    if batches.started.is_some() {
+      batches.cut();
+    }
+    batches.completed = true;
+    self.notify.notify(usize::MAX);
+  }
+}
+
+#[async_trait]
+impl<T> Source<Vec<T>> for BatchNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<Vec<T>>> {
+    let batch = self.batches.try_lock()?.pop(self.timeout)?;
+    Some(Message::new(batch))
+  }
+
+  async fn consume(&self) -> Result<Message<Vec<T>>> {
+    loop {
+      let mut batches = self.batches.lock().await;
+      if let Some(batch) = batches.pop(self.timeout) {
+        return Ok(Message::new(batch));
+      }
+      if batches.completed {
+        return Err(Error::Completed);
+      }
+      let deadline = batches.deadline(self.timeout);
+      let listener = self.notify.listen();
+      drop(batches);
+      match deadline {
+        Some(deadline) => {
+          let timeout = async {
+            Timer::at(deadline).await;
+          };
+          listener.or(timeout).await
+        }
+        None => listener.await,
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive};
+  use std::sync::Arc;
+
+  async fn size_test() -> Result<()> {
+    let batch = BatchNode::new(3, Duration::from_secs(60));
+
+    for i in 0..7 {
+      assert_eq!(send(&batch, i).await, MessageStatus::Accepted);
+    }
+
+    assert_eq!(receive(&batch).await?, [0, 1, 2]);
+    assert_eq!(receive(&batch).await?, [3, 4, 5]);
+    assert_eq!(try_receive(&batch), None);
+
+    Ok(())
+  }
+
+  async fn timeout_test() -> Result<()> {
+    let batch = Arc::new(BatchNode::new(3, Duration::from_millis(500)));
+
+    let start = Instant::now();
+    send(&*batch, 1).await;
+    send(&*batch, 2).await;
+    assert_eq!(receive(&*batch).await?, [1, 2]);
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    // the timeout starts with the first item of a batch
+    let batch_t = batch.clone();
+    let t_send = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_millis(300)).await;
+      send(&*batch_t, 3).await;
+    });
+
+    let start = Instant::now();
+    assert_eq!(receive(&*batch).await?, [3]);
+    assert!(start.elapsed() >= Duration::from_millis(800));
+    t_send.await;
+
+    Ok(())
+  }
+
+  async fn expired_test() -> Result<()> {
+    let batch = BatchNode::new(2, Duration::from_millis(100));
+
+    send(&batch, 1).await;
+    test_rt::sleep(Duration::from_millis(200)).await;
+    send(&batch, 2).await;
+
+    // items that arrive after a timeout start the next batch
+    assert_eq!(try_receive(&batch), Some(vec![1]));
+    assert_eq!(try_receive(&batch), None);
+    send(&batch, 3).await;
+    assert_eq!(try_receive(&batch), Some(vec![2, 3]));
+
+    Ok(())
+  }
+
+  async fn complete_test() -> Result<()> {
+    let batch = BatchNode::new(3, Duration::from_secs(60));
+
+    for i in 0..4 {
+      send(&batch, i).await;
+    }
+    batch.complete().await;
+
+    assert_eq!(send(&batch, 4).await, MessageStatus::Declined);
+    assert_eq!(receive(&batch).await?, [0, 1, 2]);
+    assert_eq!(receive(&batch).await?, [3]);
+    assert!(matches!(batch.consume().await, Err(Error::Completed)));
+
+    Ok(())
+  }
+
+  matrix_tests!(size_test, timeout_test, expired_test, complete_test);
+}
@@ -2,20 +2,21 @@
 // Licensed under the Apache License, Version 2.0.
 
 use crate::{MessageStatus, Target};
-use async_std::sync::RwLock;
+use async_lock::RwLock;
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
 
 /// A single-input, single-output node that broadcasts each message received to all successors.
 /// Its input and output are of the same generic type. It does not buffer messages.
-pub struct BroadcastNode<'a, T>
+pub struct BroadcastNode<T>
 where
   T: Sized + Send + Clone + Serialize + DeserializeOwned,
 {
-  outputs: RwLock<Vec<&'a dyn Target<T>>>,
+  outputs: RwLock<Vec<Arc<dyn Target<T>>>>,
 }
 
-impl<'a, T> BroadcastNode<'a, T>
+impl<T> BroadcastNode<T>
 where
   T: Sized + Send + Clone + Serialize + DeserializeOwned,
 {
@@ -30,21 +31,21 @@ where
 
   /// Initialize a new broadcast node with a predefined list of targets.
   /// New targets could still be appended to this node after init.
-  pub fn new(outputs: Vec<&'a dyn Target<T>>) -> Self {
+  pub fn new(outputs: Vec<Arc<dyn Target<T>>>) -> Self {
     BroadcastNode {
       outputs: RwLock::new(outputs),
     }
   }
 
   /// Adds a new target to the list of outputs post init.
-  pub async fn add_target(&self, target: &'a dyn Target<T>) {
+  pub async fn add_target(&self, target: Arc<dyn Target<T>>) {
     let mut outputs_access = self.outputs.write().await;
     outputs_access.push(target);
   }
 }
 
 #[async_trait]
-impl<'a, T> Target<T> for BroadcastNode<'a, T>
+impl<T> Target<T> for BroadcastNode<T>
 where
   T: Sized + Send + Clone + Serialize + DeserializeOwned,
 {
@@ -60,6 +61,13 @@ where
       MessageStatus::Accepted
     }
   }
+
+  /// Completes all outputs.
+  async fn complete(&self) {
+    for out in self.outputs.read().await.iter() {
+      out.complete().await;
+    }
+  }
 }
 
 #[cfg(test)]
@@ -67,51 +75,49 @@ mod tests {
 
   use super::*;
   use crate::buffers::*;
-  use futures_await_test::async_test;
 
-  #[async_test]
   async fn const_init() -> Result<()> {
-    let b = UnboundedBuffer::<u64>::new();
-    let c = UnboundedBuffer::<u64>::new();
-    let a = BroadcastNode::new(vec![&b, &c]);
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let c = Arc::new(UnboundedBuffer::<u64>::new());
+    let a = BroadcastNode::new(vec![b.clone(), c.clone()]);
 
     assert_eq!(send(&a, 10u64).await, MessageStatus::Accepted);
     assert_eq!(send(&a, 20u64).await, MessageStatus::Accepted);
 
-    assert_eq!(receive(&b).await?, 10);
-    assert_eq!(receive(&b).await?, 20);
+    assert_eq!(receive(&*b).await?, 10);
+    assert_eq!(receive(&*b).await?, 20);
 
-    assert_eq!(receive(&c).await?, 10);
-    assert_eq!(receive(&c).await?, 20);
+    assert_eq!(receive(&*c).await?, 10);
+    assert_eq!(receive(&*c).await?, 20);
 
     Ok(())
   }
 
-  #[async_test]
   async fn dynamic_add() -> Result<()> {
-    let b = UnboundedBuffer::<u64>::new();
-    let c = UnboundedBuffer::<u64>::new();
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let c = Arc::new(UnboundedBuffer::<u64>::new());
 
     let a = BroadcastNode::empty();
 
-    a.add_target(&b).await;
-    a.add_target(&c).await;
+    a.add_target(b.clone()).await;
+    a.add_target(c.clone()).await;
 
     assert_eq!(send(&a, 10u64).await, MessageStatus::Accepted);
     assert_eq!(send(&a, 20u64).await, MessageStatus::Accepted);
 
-    assert_eq!(receive(&b).await?, 10);
-    assert_eq!(receive(&b).await?, 20);
+    assert_eq!(receive(&*b).await?, 10);
+    assert_eq!(receive(&*b).await?, 20);
 
-    assert_eq!(receive(&c).await?, 10);
-    assert_eq!(receive(&c).await?, 20);
+    assert_eq!(receive(&*c).await?, 10);
+    assert_eq!(receive(&*c).await?, 20);
 
     Ok(())
   }
 
-  #[async_test]
   async fn reject_when_no_outputs() {
     let a = BroadcastNode::empty();
     assert_eq!(send(&a, 10).await, MessageStatus::Declined);
   }
+
+  matrix_tests!(const_init, dynamic_add, reject_when_no_outputs);
 }
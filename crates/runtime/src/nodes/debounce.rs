@@ -0,0 +1,178 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Error, Message, MessageStatus, Result, Source, Target};
+use async_io::Timer;
+use async_lock::Mutex;
+use async_trait::async_trait;
+use event_listener::Event;
+use futures_lite::FutureExt;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  sync::atomic::{AtomicBool, Ordering},
+  time::{Duration, Instant},
+};
+
+/// A single-input, single-output node that forwards a message only once
+/// `delay` passed without a newer message arriving. Each new message
+/// replaces the one that is waiting, so bursts collapse into their
+/// last message.
+pub struct DebounceNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  delay: Duration,
+  /// the latest message and when it arrived
+  latest: Mutex<Option<(Message<T>, Instant)>>,
+  /// once set, no more messages are accepted and the latest one is
+  /// forwarded without waiting for the delay, only changes under the lock
+  completed: AtomicBool,
+  /// used to signal waiting consumers that the latest message changed
+  notify: Event,
+}
+
+impl<T> DebounceNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  pub fn new(delay: Duration) -> Self {
+    DebounceNode {
+      delay,
+      latest: Mutex::new(None),
+      completed: AtomicBool::new(false),
+      notify: Event::new(),
+    }
+  }
+
+  /// How long the latest message has been waiting, completion ends the wait.
+  fn quiet(&self, arrived: Instant) -> Duration {
+    match self.completed.load(Ordering::SeqCst) {
+      true => self.delay,
+      false => arrived.elapsed(),
+    }
+  }
+}
+
+#[async_trait]
+impl<T> Target<T> for DebounceNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  async fn accept(&self, message: Message<T>) -> MessageStatus {
+    let mut latest = self.latest.lock().await;
+    if self.completed.load(Ordering::SeqCst) {
+      return MessageStatus::Declined;
+    }
+    latest.replace((message, Instant::now()));
+    self.notify.notify(usize::MAX);
+    MessageStatus::Accepted
+  }
+
+  async fn complete(&self) {
+    let _latest = self.latest.lock().await;
+    self.completed.store(true, Ordering::SeqCst);
+    self.notify.notify(usize::MAX);
+  }
+}
+
+#[async_trait]
+impl<T> Source<T> for DebounceNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<T>> {
+    let mut latest = self.latest.try_lock()?;
+    match *latest {
+      Some((_, arrived)) if self.quiet(arrived) >= self.delay => latest.take().map(|(m, _)| m),
+      _ => None,
+    }
+  }
+
+  async fn consume(&self) -> Result<Message<T>> {
+    loop {
+      let mut latest = self.latest.lock().await;
+      let remaining = match *latest {
+        Some((_, arrived)) => {
+          let quiet = self.quiet(arrived);
+          if quiet >= self.delay {
+            let (message, _) = latest.take().expect("checked above");
+            return Ok(message);
+          }
+          Some(self.delay - quiet)
+        }
+        None if self.completed.load(Ordering::SeqCst) => return Err(Error::Completed),
+        None => None,
+      };
+      let listener = self.notify.listen();
+      drop(latest);
+      match remaining {
+        Some(remaining) => {
+          let timeout = async {
+            Timer::after(remaining).await;
+          };
+          listener.or(timeout).await
+        }
+        None => listener.await,
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive};
+  use std::sync::Arc;
+
+  async fn burst_test() -> Result<()> {
+    let debounce = Arc::new(DebounceNode::new(Duration::from_millis(300)));
+
+    let debounce_t = debounce.clone();
+    let t_send = test_rt::spawn(async move {
+      for i in 0..5 {
+        send(&*debounce_t, i).await;
+        test_rt::sleep(Duration::from_millis(100)).await;
+      }
+    });
+
+    let start = Instant::now();
+    assert_eq!(receive(&*debounce).await?, 4);
+    assert!(start.elapsed() >= Duration::from_millis(700));
+    assert_eq!(try_receive(&*debounce), None);
+    t_send.await;
+
+    Ok(())
+  }
+
+  async fn quiet_test() -> Result<()> {
+    let debounce = DebounceNode::new(Duration::from_millis(100));
+
+    let message = Message::new(1);
+    let id = message.id();
+    debounce.accept(message).await;
+    assert_eq!(try_receive(&debounce), None);
+
+    test_rt::sleep(Duration::from_millis(150)).await;
+    assert_eq!(debounce.try_consume().map(|m| m.id()), Some(id));
+    assert_eq!(try_receive(&debounce), None);
+
+    Ok(())
+  }
+
+  async fn complete_test() -> Result<()> {
+    let debounce = DebounceNode::new(Duration::from_secs(60));
+
+    send(&debounce, 1).await;
+    debounce.complete().await;
+
+    // the pending message is forwarded without waiting for the delay
+    assert_eq!(send(&debounce, 2).await, MessageStatus::Declined);
+    assert_eq!(try_receive(&debounce), Some(1));
+    assert!(matches!(debounce.consume().await, Err(Error::Completed)));
+
+    Ok(())
+  }
+
+  matrix_tests!(burst_test, quiet_test, complete_test);
+}
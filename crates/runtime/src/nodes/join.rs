@@ -0,0 +1,169 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Message, Result, Source};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+/// A tuple of sources that can be joined by a [JoinNode]. Implemented
+/// for tuples of two to four `Arc<dyn Source<T>>` of any message types.
+#[async_trait]
+pub trait JoinSources: Send + Sync {
+  /// A tuple with one payload from every source
+  type Output: Sized + Send + Clone + Serialize + DeserializeOwned;
+
+  /// Payloads that were received ahead of the rest of their tuple
+  type Slots: Default + Send;
+
+  /// Fills empty slots with messages that are immediately available,
+  /// returns true when all slots are filled.
+  fn try_fill(&self, slots: &mut Self::Slots) -> bool;
+
+  /// Waits for a message from every source with an empty slot.
+  async fn fill(&self, slots: &mut Self::Slots) -> Result<()>;
+
+  /// Empties filled slots into one output tuple.
+  fn take(slots: &mut Self::Slots) -> Self::Output;
+}
+
+macro_rules! join_sources {
+  ($($T:ident $i:tt),+) => {
+    #[async_trait]
+    impl<$($T),+> JoinSources for ($(Arc<dyn Source<$T>>,)+)
+    where
+      $($T: Sized + Send + Clone + Serialize + DeserializeOwned,)+
+    {
+      type Output = ($($T,)+);
+      type Slots = ($(Option<$T>,)+);
+
+      fn try_fill(&self, slots: &mut Self::Slots) -> bool {
+        $(
+          if slots.$i.is_none() {
+            slots.$i = self.$i.try_consume().map(Message::release);
+          }
+        )+
+        $(slots.$i.is_some())&&+
+      }
+
+      async fn fill(&self, slots: &mut Self::Slots) -> Result<()> {
+        $(
+          if slots.$i.is_none() {
+            slots.$i = Some(self.$i.consume().await?.release());
+          }
+        )+
+        Ok(())
+      }
+
+      fn take(slots: &mut Self::Slots) -> Self::Output {
+        ($(slots.$i.take().expect("all slots are filled"),)+)
+      }
+    }
+  };
+}
+
+join_sources!(A 0, B 1);
+join_sources!(A 0, B 1, C 2);
+join_sources!(A 0, B 1, C 2, D 3);
+
+/// A multi-input, single-output node that waits for one message from
+/// each of its sources and forwards their payloads together as a tuple.
+///
+/// Messages taken from some of the sources are held by the node until
+/// the others have one available, so they are not lost when a consumer
+/// stops waiting.
+pub struct JoinNode<S>
+where
+  S: JoinSources,
+{
+  sources: S,
+  slots: Mutex<S::Slots>,
+}
+
+impl<S> JoinNode<S>
+where
+  S: JoinSources,
+{
+  pub fn new(sources: S) -> Self {
+    JoinNode {
+      sources,
+      slots: Mutex::new(S::Slots::default()),
+    }
+  }
+}
+
+#[async_trait]
+impl<S> Source<S::Output> for JoinNode<S>
+where
+  S: JoinSources,
+{
+  fn try_consume(&self) -> Option<Message<S::Output>> {
+    let mut slots = self.slots.try_lock()?;
+    match self.sources.try_fill(&mut slots) {
+      true => Some(Message::new(S::take(&mut slots))),
+      false => None,
+    }
+  }
+
+  async fn consume(&self) -> Result<Message<S::Output>> {
+    let mut slots = self.slots.lock().await;
+    self.sources.fill(&mut slots).await?;
+    Ok(Message::new(S::take(&mut slots)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive, UnboundedBuffer};
+  use std::time::Duration;
+
+  async fn pair_test() -> Result<()> {
+    let headers = Arc::new(UnboundedBuffer::<u64>::new());
+    let proposals = Arc::new(UnboundedBuffer::<Vec<String>>::new());
+    type Sources = (Arc<dyn Source<u64>>, Arc<dyn Source<Vec<String>>>);
+    let join: JoinNode<Sources> = JoinNode::new((headers.clone(), proposals.clone()));
+
+    send(&*proposals, vec!["tx".into()]).await;
+    assert_eq!(try_receive(&join), None);
+
+    let headers_t = headers.clone();
+    let t_send = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_millis(300)).await;
+      send(&*headers_t, 1).await;
+    });
+
+    // the proposal taken by try_receive is still part of the next tuple
+    assert_eq!(receive(&join).await?, (1, vec!["tx".to_string()]));
+    t_send.await;
+
+    Ok(())
+  }
+
+  async fn triple_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<bool>::new());
+    let c = Arc::new(UnboundedBuffer::<String>::new());
+    type Sources = (
+      Arc<dyn Source<u64>>,
+      Arc<dyn Source<bool>>,
+      Arc<dyn Source<String>>,
+    );
+    let join: JoinNode<Sources> = JoinNode::new((a.clone(), b.clone(), c.clone()));
+
+    send(&*a, 1).await;
+    send(&*a, 2).await;
+    send(&*b, true).await;
+    send(&*c, "x".into()).await;
+
+    assert_eq!(try_receive(&join), Some((1, true, "x".to_string())));
+    assert_eq!(try_receive(&join), None);
+    assert_eq!(try_receive(&*a), None);
+
+    Ok(())
+  }
+
+  matrix_tests!(pair_test, triple_test);
+}
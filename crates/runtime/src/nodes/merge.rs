@@ -0,0 +1,108 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use super::select::{SelectNode, SelectPolicy};
+use crate::{Message, Result, Source};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+/// A multi-input, single-output node that interleaves the messages of
+/// several sources of the same type. Sources that have messages available
+/// take turns, so a busy source can't starve the others. Forwarded messages
+/// keep their ids.
+pub struct MergeNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  select: SelectNode<T>,
+}
+
+impl<T> MergeNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  pub fn new(sources: Vec<Arc<dyn Source<T>>>) -> Self {
+    MergeNode {
+      select: SelectNode::new(sources, SelectPolicy::RoundRobin),
+    }
+  }
+}
+
+fn merged<T>(selected: Message<(usize, T)>) -> Message<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  let id = selected.id();
+  let (_, payload) = selected.release();
+  Message::with_id(payload, id)
+}
+
+#[async_trait]
+impl<T> Source<T> for MergeNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<T>> {
+    self.select.try_consume().map(merged)
+  }
+
+  async fn consume(&self) -> Result<Message<T>> {
+    self.select.consume().await.map(merged)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, try_receive, Error, Link, LinkOptions, Target, UnboundedBuffer};
+
+  async fn interleave_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let c = Arc::new(UnboundedBuffer::<u64>::new());
+    let merge = MergeNode::new(vec![a.clone(), b.clone(), c.clone()]);
+
+    for i in 0..3 {
+      send(&*a, i).await;
+    }
+    send(&*b, 10).await;
+    send(&*c, 20).await;
+
+    let mut merged = vec![];
+    for _ in 0..5 {
+      merged.push(receive(&merge).await?);
+    }
+    assert_eq!(merged, [0, 10, 20, 1, 2]);
+    assert_eq!(try_receive(&merge), None);
+
+    Ok(())
+  }
+
+  async fn link_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let merged = Arc::new(UnboundedBuffer::<u64>::new());
+    let merge = Arc::new(MergeNode::new(vec![a.clone(), b.clone()]));
+
+    let options = LinkOptions {
+      propagate_completion: true,
+      ..Default::default()
+    };
+    let link = merge.link_to(merged.clone(), options);
+
+    send(&*a, 1).await;
+    send(&*b, 2).await;
+    a.complete().await;
+    b.complete().await;
+    assert_eq!(link.join().await, 2);
+
+    assert_eq!(receive(&*merged).await? + receive(&*merged).await?, 3);
+    assert!(matches!(merged.consume().await, Err(Error::Completed)));
+
+    Ok(())
+  }
+
+  matrix_tests!(interleave_test, link_test);
+}
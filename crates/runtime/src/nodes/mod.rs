@@ -2,4 +2,13 @@
 // Licensed under the Apache License, Version 2.0.
 
 pub mod proxy;
-pub mod broadcast;
\ No newline at end of file
+pub mod broadcast;
+// these need a timer, which loom can't model
+#[cfg(not(loom))]
+pub mod batch;
+#[cfg(not(loom))]
+pub mod debounce;
+pub mod throttle;
+pub mod join;
+pub mod merge;
+pub mod select;
\ No newline at end of file
@@ -2,7 +2,6 @@
 // Licensed under the Apache License, Version 2.0.
 
 use crate::Target;
-use async_std::task::{self, JoinHandle};
 use serde::{de::DeserializeOwned, Serialize};
 
 pub struct ProxyNode<'a, T>
@@ -10,7 +9,6 @@ where
   T: Sized + Send + Clone + Serialize + DeserializeOwned + Sync,
 {
   target: &'a dyn Target<T>,
-  worker: JoinHandle<()>,
 }
 
 impl<'a, T> ProxyNode<'a, T>
@@ -18,14 +16,10 @@ where
   T: Sized + Send + Clone + Serialize + DeserializeOwned + Sync,
 {
   pub fn new(target: &'a dyn Target<T>) -> Self {
-    ProxyNode {
-      target,
-      worker: task::spawn(async {}),
-    }
+    ProxyNode { target }
   }
 }
 
-
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -0,0 +1,206 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Error, Message, Result, Source};
+use async_trait::async_trait;
+use futures_lite::future::poll_fn;
+use rand::Rng;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+  },
+  task::Poll,
+};
+
+/// Decides which source wins when several have a message available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectPolicy {
+  /// Sources earlier in the list always win, later sources may starve.
+  Biased,
+  /// The source after the last winner is checked first.
+  RoundRobin,
+  /// A random source is checked first.
+  Random,
+}
+
+/// A multi-input, single-output node that races its sources and forwards
+/// the first message that becomes available, together with the index of
+/// the source it came from. Forwarded messages keep their ids.
+///
+/// Sources that report [Error::Completed] are skipped from then on, the
+/// node itself reports completion once all of its sources are completed.
+pub struct SelectNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  sources: Vec<Arc<dyn Source<T>>>,
+  policy: SelectPolicy,
+  /// the index of the source that is checked first under round robin
+  next: AtomicUsize,
+  /// which sources were completed and drained
+  completed: Vec<AtomicBool>,
+}
+
+impl<T> SelectNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  pub fn new(sources: Vec<Arc<dyn Source<T>>>, policy: SelectPolicy) -> Self {
+    assert!(!sources.is_empty(), "select needs at least one source");
+    SelectNode {
+      completed: sources.iter().map(|_| AtomicBool::new(false)).collect(),
+      sources,
+      policy,
+      next: AtomicUsize::new(0),
+    }
+  }
+
+  /// The source indices in the order they are checked for the next message.
+  fn order(&self) -> impl Iterator<Item = usize> {
+    let count = self.sources.len();
+    let first = match self.policy {
+      SelectPolicy::Biased => 0,
+      SelectPolicy::RoundRobin => self.next.load(Ordering::Relaxed) % count,
+      SelectPolicy::Random => rand::thread_rng().gen_range(0..count),
+    };
+    (first..count).chain(0..first)
+  }
+
+  fn selected(&self, index: usize, message: Message<T>) -> Message<(usize, T)> {
+    self.next.store(index + 1, Ordering::Relaxed);
+    let id = message.id();
+    Message::with_id((index, message.release()), id)
+  }
+}
+
+#[async_trait]
+impl<T> Source<(usize, T)> for SelectNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<(usize, T)>> {
+    self.order().find_map(|index| {
+      let message = self.sources[index].try_consume()?;
+      Some(self.selected(index, message))
+    })
+  }
+
+  async fn consume(&self) -> Result<Message<(usize, T)>> {
+    let order: Vec<_> = self.order().collect();
+    let mut pending: Vec<_> = order
+      .iter()
+      .filter(|&&index| !self.completed[index].load(Ordering::Relaxed))
+      .map(|&index| (index, self.sources[index].consume()))
+      .collect();
+
+    // polls in policy order and stops at the first ready source,
+    // so no more than one message is taken out of the sources
+    let (index, message) = poll_fn(|cx| {
+      let mut i = 0;
+      while i < pending.len() {
+        let index = pending[i].0;
+        match pending[i].1.as_mut().poll(cx) {
+          Poll::Ready(Err(Error::Completed)) => {
+            self.completed[index].store(true, Ordering::Relaxed);
+            drop(pending.remove(i));
+          }
+          Poll::Ready(message) => return Poll::Ready(Some((index, message))),
+          Poll::Pending => i += 1,
+        }
+      }
+      match pending.is_empty() {
+        true => Poll::Ready(None),
+        false => Poll::Pending,
+      }
+    })
+    .await
+    .ok_or(Error::Completed)?;
+
+    Ok(self.selected(index, message?))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive, Target, UnboundedBuffer};
+  use std::time::Duration;
+
+  async fn biased_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let select = SelectNode::new(vec![a.clone(), b.clone()], SelectPolicy::Biased);
+
+    send(&*a, 1).await;
+    send(&*a, 2).await;
+    send(&*b, 3).await;
+
+    assert_eq!(receive(&select).await?, (0, 1));
+    assert_eq!(receive(&select).await?, (0, 2));
+    assert_eq!(receive(&select).await?, (1, 3));
+    assert_eq!(try_receive(&select), None);
+
+    Ok(())
+  }
+
+  async fn round_robin_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let select = SelectNode::new(vec![a.clone(), b.clone()], SelectPolicy::RoundRobin);
+
+    for i in 0..3 {
+      send(&*a, i).await;
+      send(&*b, 10 + i).await;
+    }
+
+    let mut sources = vec![];
+    for _ in 0..6 {
+      sources.push(receive(&select).await?.0);
+    }
+    assert_eq!(sources, [0, 1, 0, 1, 0, 1]);
+
+    Ok(())
+  }
+
+  async fn race_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let select = SelectNode::new(vec![a.clone(), b.clone()], SelectPolicy::Random);
+
+    let a_t = a.clone();
+    let t_send = test_rt::spawn(async move {
+      test_rt::sleep(Duration::from_millis(300)).await;
+      let message = Message::new(7);
+      let id = message.id();
+      a_t.accept(message).await;
+      id
+    });
+
+    let selected = select.consume().await?;
+    assert_eq!(*selected, (0, 7));
+    assert_eq!(selected.id(), t_send.await);
+    assert_eq!(try_receive(&*b), None);
+
+    Ok(())
+  }
+
+  async fn complete_test() -> Result<()> {
+    let a = Arc::new(UnboundedBuffer::<u64>::new());
+    let b = Arc::new(UnboundedBuffer::<u64>::new());
+    let select = SelectNode::new(vec![a.clone(), b.clone()], SelectPolicy::Biased);
+
+    send(&*b, 1).await;
+    a.complete().await;
+    assert_eq!(receive(&select).await?, (1, 1));
+
+    b.complete().await;
+    assert!(matches!(select.consume().await, Err(Error::Completed)));
+
+    Ok(())
+  }
+
+  matrix_tests!(biased_test, round_robin_test, race_test, complete_test);
+}
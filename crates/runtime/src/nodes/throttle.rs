@@ -0,0 +1,117 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{Message, MessageStatus, Result, Source, Target, UnboundedBuffer};
+use async_lock::Mutex;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+  collections::VecDeque,
+  sync::atomic::{AtomicU64, Ordering},
+  time::{Duration, Instant},
+};
+
+/// A single-input, single-output node that accepts at most `limit`
+/// messages within any window of `interval` and declines the rest.
+/// Accepted messages are forwarded in the order they arrived.
+pub struct ThrottleNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  limit: usize,
+  interval: Duration,
+  /// arrival times of the messages accepted within the last interval
+  accepted: Mutex<VecDeque<Instant>>,
+  /// accepted messages that were not consumed yet
+  output: UnboundedBuffer<T>,
+  /// messages that were declined because of the rate limit
+  dropped: AtomicU64,
+}
+
+impl<T> ThrottleNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  pub fn new(limit: usize, interval: Duration) -> Self {
+    assert!(limit > 0, "throttle limit has to be positive");
+    ThrottleNode {
+      limit,
+      interval,
+      accepted: Mutex::new(VecDeque::with_capacity(limit)),
+      output: UnboundedBuffer::new(),
+      dropped: AtomicU64::new(0),
+    }
+  }
+
+  /// The number of messages declined since this node was created.
+  pub fn dropped(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+}
+
+#[async_trait]
+impl<T> Target<T> for ThrottleNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  async fn accept(&self, message: Message<T>) -> MessageStatus {
+    let mut accepted = self.accepted.lock().await;
+    let now = Instant::now();
+    while matches!(accepted.front(), Some(t) if now.duration_since(*t) >= self.interval) {
+      accepted.pop_front();
+    }
+
+    if accepted.len() >= self.limit {
+      self.dropped.fetch_add(1, Ordering::Relaxed);
+      return MessageStatus::Declined;
+    }
+    accepted.push_back(now);
+    self.output.accept(message).await
+  }
+
+  async fn complete(&self) {
+    self.output.complete().await;
+  }
+}
+
+#[async_trait]
+impl<T> Source<T> for ThrottleNode<T>
+where
+  T: Sized + Send + Clone + Serialize + DeserializeOwned,
+{
+  fn try_consume(&self) -> Option<Message<T>> {
+    self.output.try_consume()
+  }
+
+  async fn consume(&self) -> Result<Message<T>> {
+    self.output.consume().await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::{receive, send, test_rt, try_receive};
+
+  async fn limit_test() -> Result<()> {
+    let throttle = ThrottleNode::new(2, Duration::from_millis(300));
+
+    assert_eq!(send(&throttle, 1).await, MessageStatus::Accepted);
+    assert_eq!(send(&throttle, 2).await, MessageStatus::Accepted);
+    assert_eq!(send(&throttle, 3).await, MessageStatus::Declined);
+    assert_eq!(throttle.dropped(), 1);
+
+    test_rt::sleep(Duration::from_millis(350)).await;
+    assert_eq!(send(&throttle, 4).await, MessageStatus::Accepted);
+
+    assert_eq!(receive(&throttle).await?, 1);
+    assert_eq!(receive(&throttle).await?, 2);
+    assert_eq!(receive(&throttle).await?, 4);
+    assert_eq!(try_receive(&throttle), None);
+
+    Ok(())
+  }
+
+  matrix_tests!(limit_test);
+}
@@ -0,0 +1,66 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Runs tests once under async-std and once under tokio, to make sure
+//! buffers behave the same on both executors.
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+/// Generates one `#[test]` per executor for each of the listed async
+/// test functions of the enclosing module.
+macro_rules! matrix_tests {
+  ($($name:ident),+ $(,)?) => {
+    mod async_std_rt {
+      $(
+        #[test]
+        fn $name() -> impl std::process::Termination {
+          async_std::task::block_on(super::$name())
+        }
+      )+
+    }
+
+    mod tokio_rt {
+      $(
+        #[test]
+        fn $name() -> impl std::process::Termination {
+          $crate::test_rt::block_on_tokio(super::$name())
+        }
+      )+
+    }
+  };
+}
+
+pub fn block_on_tokio<F: Future>(future: F) -> F::Output {
+  tokio::runtime::Builder::new_multi_thread()
+    .enable_all()
+    .build()
+    .expect("tokio runtime")
+    .block_on(future)
+}
+
+fn on_tokio() -> bool {
+  tokio::runtime::Handle::try_current().is_ok()
+}
+
+/// Spawns a task on the executor that runs the current test.
+pub fn spawn<F>(future: F) -> Pin<Box<dyn Future<Output = F::Output> + Send>>
+where
+  F: Future + Send + 'static,
+  F::Output: Send + 'static,
+{
+  match on_tokio() {
+    true => {
+      let handle = tokio::spawn(future);
+      Box::pin(async move { handle.await.expect("tokio task panicked") })
+    }
+    false => Box::pin(async_std::task::spawn(future)),
+  }
+}
+
+/// Sleeps on the timer of the executor that runs the current test.
+pub async fn sleep(duration: Duration) {
+  match on_tokio() {
+    true => tokio::time::sleep(duration).await,
+    false => async_std::task::sleep(duration).await,
+  }
+}
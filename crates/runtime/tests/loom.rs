@@ -0,0 +1,173 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+//! Model checks the wakeups of buffers whose consumers wait without
+//! holding a lock. Every interleaving of producers and consumers is
+//! explored, a consumer that misses a notification shows up as a
+//! deadlock. Run with:
+//!
+//!   RUSTFLAGS="--cfg loom" cargo test -p oe4-runtime --release --test loom
+//!
+//! The models of a single consumer waiting on an [UnboundedBuffer]
+//! explore every interleaving. The other models also step through the
+//! instrumented internals of `async-lock`, their state space grows too
+//! fast for an exhaustive search, so they are bounded to 3 preemptions,
+//! or 2 with two consumers, unless `LOOM_MAX_PREEMPTIONS` says otherwise.
+
+#![cfg(loom)]
+
+use loom::{future::block_on, sync::Arc, thread};
+use oe4_runtime::{
+  receive, send, Error, MessageStatus, OverwriteBuffer, Target, UnboundedBuffer, WriteOnceBuffer,
+};
+
+fn model<F>(f: F)
+where
+  F: Fn() + Sync + Send + 'static,
+{
+  loom::model::Builder::new().check(f);
+}
+
+fn bounded_model<F>(preemptions: usize, f: F)
+where
+  F: Fn() + Sync + Send + 'static,
+{
+  let mut builder = loom::model::Builder::new();
+  if builder.preemption_bound.is_none() {
+    builder.preemption_bound = Some(preemptions);
+  }
+  builder.check(f);
+}
+
+#[test]
+fn unbounded_accept_wakes_consumer() {
+  model(|| {
+    let ubuf = Arc::new(UnboundedBuffer::<u64>::new());
+
+    let ubuf_t = ubuf.clone();
+    let producer = thread::spawn(move || block_on(send(&*ubuf_t, 10)));
+
+    assert_eq!(block_on(receive(&*ubuf)).unwrap(), 10);
+    assert_eq!(producer.join().unwrap(), MessageStatus::Accepted);
+  });
+}
+
+#[test]
+fn unbounded_accepts_wake_all_consumers() {
+  bounded_model(2, || {
+    let ubuf = Arc::new(UnboundedBuffer::<u64>::new());
+
+    let consumers: Vec<_> = (0..2)
+      .map(|_| {
+        let ubuf = ubuf.clone();
+        thread::spawn(move || block_on(receive(&*ubuf)).unwrap())
+      })
+      .collect();
+
+    block_on(send(&*ubuf, 1));
+    block_on(send(&*ubuf, 2));
+
+    let sum: u64 = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+    assert_eq!(sum, 3);
+  });
+}
+
+#[test]
+fn unbounded_complete_wakes_consumer() {
+  model(|| {
+    let ubuf = Arc::new(UnboundedBuffer::<u64>::new());
+
+    let ubuf_t = ubuf.clone();
+    let consumer = thread::spawn(move || block_on(receive(&*ubuf_t)));
+
+    block_on(ubuf.complete());
+    assert!(matches!(consumer.join().unwrap(), Err(Error::Completed)));
+  });
+}
+
+#[test]
+fn unbounded_accept_races_complete() {
+  bounded_model(3, || {
+    let ubuf = Arc::new(UnboundedBuffer::<u64>::new());
+
+    let ubuf_t = ubuf.clone();
+    let producer = thread::spawn(move || block_on(send(&*ubuf_t, 10)));
+    let ubuf_t = ubuf.clone();
+    let consumer = thread::spawn(move || block_on(receive(&*ubuf_t)));
+
+    block_on(ubuf.complete());
+
+    // an accepted message is handed out before completion is reported
+    match producer.join().unwrap() {
+      MessageStatus::Accepted => assert_eq!(consumer.join().unwrap().unwrap(), 10),
+      _ => assert!(matches!(consumer.join().unwrap(), Err(Error::Completed))),
+    }
+  });
+}
+
+#[test]
+fn write_once_accept_wakes_consumers() {
+  bounded_model(2, || {
+    let wobuf = Arc::new(WriteOnceBuffer::<u64>::new());
+
+    let consumers: Vec<_> = (0..2)
+      .map(|_| {
+        let wobuf = wobuf.clone();
+        thread::spawn(move || block_on(receive(&*wobuf)).unwrap())
+      })
+      .collect();
+
+    assert_eq!(block_on(send(&*wobuf, 10)), MessageStatus::Accepted);
+    for consumer in consumers {
+      assert_eq!(consumer.join().unwrap(), 10);
+    }
+  });
+}
+
+#[test]
+fn write_once_complete_wakes_consumer() {
+  bounded_model(3, || {
+    let wobuf = Arc::new(WriteOnceBuffer::<u64>::new());
+
+    let wobuf_t = wobuf.clone();
+    let consumer = thread::spawn(move || block_on(receive(&*wobuf_t)));
+
+    block_on(wobuf.complete());
+    assert!(matches!(consumer.join().unwrap(), Err(Error::Completed)));
+  });
+}
+
+#[test]
+fn write_once_accept_races_complete() {
+  bounded_model(3, || {
+    let wobuf = Arc::new(WriteOnceBuffer::<u64>::new());
+
+    let wobuf_t = wobuf.clone();
+    let producer = thread::spawn(move || block_on(send(&*wobuf_t, 10)));
+    let wobuf_t = wobuf.clone();
+    let consumer = thread::spawn(move || block_on(receive(&*wobuf_t)));
+
+    block_on(wobuf.complete());
+
+    match producer.join().unwrap() {
+      MessageStatus::Accepted => assert_eq!(consumer.join().unwrap().unwrap(), 10),
+      _ => assert!(matches!(consumer.join().unwrap(), Err(Error::Completed))),
+    }
+  });
+}
+
+#[test]
+fn overwrite_accept_wakes_consumer() {
+  bounded_model(3, || {
+    let obuf = Arc::new(OverwriteBuffer::<u64>::new());
+
+    let obuf_t = obuf.clone();
+    let consumer = thread::spawn(move || block_on(receive(&*obuf_t)).unwrap());
+
+    block_on(send(&*obuf, 10));
+    block_on(send(&*obuf, 20));
+
+    // the consumer sees whichever value was stored when it woke up
+    assert!(matches!(consumer.join().unwrap(), 10 | 20));
+  });
+}
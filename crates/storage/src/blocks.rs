@@ -0,0 +1,156 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use ethereum::{Block, Keccak};
+use oe4_runtime::{Message, MessageStatus, Target};
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+  },
+};
+
+/// The blocks the node imported, one RLP file per block named after the
+/// block hash. Accepted blocks are kept in memory until they are flushed.
+///
+/// Completing the store makes it decline new blocks, [BlockStore::close]
+/// does that and also writes out the blocks it still holds.
+pub struct BlockStore {
+  dir: PathBuf,
+  pending: Mutex<Vec<Block>>,
+  closed: AtomicBool,
+}
+
+impl BlockStore {
+  /// Opens a block directory, creating it if it doesn't exist yet.
+  pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+    let dir = dir.into();
+    fs::create_dir_all(&dir)?;
+    Ok(BlockStore {
+      dir,
+      pending: Mutex::new(Vec::new()),
+      closed: AtomicBool::new(false),
+    })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.dir
+  }
+
+  fn block_path(&self, hash: &Keccak) -> PathBuf {
+    self.dir.join(format!("{:x}.rlp", hash))
+  }
+
+  /// Looks a block up by its hash, whether it was flushed already or not.
+  pub fn get(&self, hash: &Keccak) -> io::Result<Option<Block>> {
+    let pending = self.pending.lock().unwrap();
+    if let Some(block) = pending.iter().find(|b| b.header.hash() == *hash) {
+      return Ok(Some(block.clone()));
+    }
+
+    match fs::read(self.block_path(hash)) {
+      Ok(bytes) => ethereum::rlp_deserialize(&bytes)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(e),
+    }
+  }
+
+  /// Writes all accepted blocks to disk. Blocks that could not be written
+  /// stay in memory for the next flush.
+  pub fn flush(&self) -> io::Result<()> {
+    let mut pending = self.pending.lock().unwrap();
+    let mut written = 0;
+    let result = pending.iter().try_for_each(|block| {
+      let bytes = ethereum::rlp_serialize(block)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+      // written next to the block and renamed, so a block file is
+      // never left half written
+      let path = self.block_path(&block.header.hash());
+      let partial = path.with_extension("partial");
+      fs::write(&partial, bytes)?;
+      fs::rename(&partial, &path)?;
+      written += 1;
+      Ok(())
+    });
+    pending.drain(..written);
+    result
+  }
+
+  /// Declines all blocks from now on and flushes the ones accepted so far.
+  pub fn close(&self) -> io::Result<()> {
+    self.closed.store(true, Ordering::SeqCst);
+    self.flush()
+  }
+}
+
+#[async_trait]
+impl Target<Block> for BlockStore {
+  async fn accept(&self, message: Message<Block>) -> MessageStatus {
+    let mut pending = self.pending.lock().unwrap();
+    if self.closed.load(Ordering::SeqCst) {
+      return MessageStatus::Declined;
+    }
+    pending.push(message.release());
+    MessageStatus::Accepted
+  }
+
+  async fn complete(&self) {
+    self.closed.store(true, Ordering::SeqCst);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures_await_test::async_test;
+  use oe4_runtime::send;
+
+  fn temp_store(name: &str) -> BlockStore {
+    let dir = std::env::temp_dir().join(format!("oe-blocks-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    BlockStore::open(dir).unwrap()
+  }
+
+  fn block(fixture: &str) -> Block {
+    ethereum::rlp_deserialize(&hex::decode(fixture.trim()).unwrap()).unwrap()
+  }
+
+  #[async_test]
+  async fn flush_and_reopen() -> io::Result<()> {
+    let store = temp_store("flush");
+    let genesis = block(include_str!("../../core/res/mainnet/block_0.hex"));
+    let first = block(include_str!("../../core/res/mainnet/block_1.hex"));
+
+    assert_eq!(send(&store, genesis.clone()).await, MessageStatus::Accepted);
+    assert_eq!(store.get(&genesis.header.hash())?, Some(genesis.clone()));
+    store.flush()?;
+    assert_eq!(send(&store, first.clone()).await, MessageStatus::Accepted);
+
+    // the first block is only written out when the store is closed
+    let reopened = BlockStore::open(store.path())?;
+    assert_eq!(reopened.get(&genesis.header.hash())?, Some(genesis));
+    assert_eq!(reopened.get(&first.header.hash())?, None);
+
+    store.close()?;
+    assert_eq!(reopened.get(&first.header.hash())?, Some(first.clone()));
+    assert_eq!(send(&store, first).await, MessageStatus::Declined);
+
+    fs::remove_dir_all(store.path())?;
+    Ok(())
+  }
+
+  #[async_test]
+  async fn declines_after_complete() {
+    let store = temp_store("complete");
+    store.complete().await;
+    let genesis = block(include_str!("../../core/res/mainnet/block_0.hex"));
+    assert_eq!(send(&store, genesis).await, MessageStatus::Declined);
+    fs::remove_dir_all(store.path()).unwrap();
+  }
+}
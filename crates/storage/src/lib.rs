@@ -1,8 +1,11 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
+mod blocks;
 mod trie;
 
+pub use blocks::BlockStore;
+
 #[cfg(test)]
 mod tests {
   #[test]
@@ -1,7 +1,9 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
+mod message;
 mod send;
+mod signer;
 
 use clap::Clap;
 use std::error::Error;
@@ -24,6 +26,8 @@ struct Opts {
 #[derive(Clap, Debug)]
 enum Command {
   Send(send::SendOptions),
+  Sign(message::SignOptions),
+  Verify(message::VerifyOptions),
 }
 
 #[tokio::main]
@@ -31,5 +35,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
   let opts = Opts::parse();
   match opts.command {
     Command::Send(send_opts) => send::run(&send_opts, &opts.global).await,
+    Command::Sign(sign_opts) => message::sign(&sign_opts, &opts.global),
+    Command::Verify(verify_opts) => message::verify(&verify_opts, &opts.global),
   }
 }
@@ -0,0 +1,84 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use crate::{signer::SignerOptions, CommonOptions};
+use clap::Clap;
+use ethereum::{hash_message, Address, Keccak, Signature, Signer, TypedData};
+use std::{error::Error, path::PathBuf};
+
+#[derive(Clap, Debug)]
+struct MessageOptions {
+  #[clap(
+    long,
+    about = "Message signed EIP-191 style, as personal_sign does",
+    required_unless_present = "typed-data"
+  )]
+  message: Option<String>,
+
+  #[clap(long, about = "Treat --message as hex encoded bytes instead of text")]
+  hex: bool,
+
+  #[clap(
+    long,
+    about = "JSON file with EIP-712 typed data, as passed to eth_signTypedData_v4",
+    conflicts_with = "message"
+  )]
+  typed_data: Option<PathBuf>,
+}
+
+impl MessageOptions {
+  /// The hash that is signed for the given message or typed data
+  fn hash(&self) -> Result<Keccak, Box<dyn Error>> {
+    if let Some(path) = &self.typed_data {
+      let data: TypedData = serde_json::from_slice(&std::fs::read(path)?)?;
+      return Ok(data.signing_hash()?);
+    }
+    let message = self.message.as_deref().unwrap_or_default();
+    Ok(match self.hex {
+      true => hash_message(hex::decode(message.trim_start_matches("0x"))?),
+      false => hash_message(message),
+    })
+  }
+}
+
+#[derive(Clap, Debug)]
+pub(crate) struct SignOptions {
+  #[clap(flatten)]
+  message: MessageOptions,
+
+  #[clap(flatten)]
+  signer: SignerOptions,
+}
+
+#[derive(Clap, Debug)]
+pub(crate) struct VerifyOptions {
+  #[clap(flatten)]
+  message: MessageOptions,
+
+  #[clap(long, about = "65 byte r || s || v signature, hex encoded")]
+  signature: String,
+
+  #[clap(long, about = "Fail unless the message was signed by this account")]
+  address: Option<Address>,
+}
+
+pub(crate) fn sign(opts: &SignOptions, _: &CommonOptions) -> Result<(), Box<dyn Error>> {
+  let signer = opts.signer.signer()?;
+  let signature = signer.sign_hash(&opts.message.hash()?)?.to_legacy(None)?;
+  println!("signer: {:?}", signer.address());
+  println!("signature: 0x{}", hex::encode(&signature.to_bytes()?[..]));
+  Ok(())
+}
+
+pub(crate) fn verify(opts: &VerifyOptions, _: &CommonOptions) -> Result<(), Box<dyn Error>> {
+  let bytes = hex::decode(opts.signature.trim_start_matches("0x"))?;
+  let signature = Signature::from_bytes(&bytes)?;
+  let signer = signature.recover(&opts.message.hash()?)?;
+  println!("signer: {:?}", signer);
+  match opts.address {
+    Some(expected) if expected != signer => {
+      Err(format!("signature is not from {:?}", expected).into())
+    }
+    _ => Ok(()),
+  }
+}
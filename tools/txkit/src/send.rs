@@ -1,9 +1,9 @@
 // Copyright 2021 The OpenEthereum Authors.
 // Licensed under the Apache License, Version 2.0.
 
-use crate::CommonOptions;
+use crate::{signer::SignerOptions, CommonOptions};
 use clap::Clap;
-use ethereum::{Address, H256, U256};
+use ethereum::{Address, Json, TransactionKind, U256};
 use std::{
   error::Error,
   net::{SocketAddr, ToSocketAddrs},
@@ -18,7 +18,17 @@ pub(crate) struct SendOptions {
   gas_limit: U256, // default transfer price 21000 wei
 
   #[clap(long, default_value = "3B9ACA00")]
-  gas_price: U256, // default is 1 Gwei
+  gas_price: U256, // default is 1 Gwei, max fee per gas for dynamic-fee transactions
+
+  #[clap(long, default_value = "3B9ACA00")]
+  max_priority_fee: U256, // default is 1 Gwei, used only by dynamic-fee transactions
+
+  #[clap(
+    long = "type",
+    about = "transaction type: legacy, access-list (EIP-2930) or dynamic-fee (EIP-1559)",
+    default_value = "legacy"
+  )]
+  tx_type: String,
 
   #[clap(long, default_value = "DE0B6B3A7640000")]
   value: U256, // default is 1 Ether
@@ -36,33 +46,50 @@ pub(crate) struct SendOptions {
     default_value = "localhost:8545")]
   target: SocketAddr,
 
-  #[clap(
-    long,
-    about = "Secret 256-bit key used as x in an ECDSA signature",
-    default_value = "0000000000000000000000000000000000000000000000000000000000000001"
-  )]
-  secret: H256, // this default gives sender addr: 0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf
+  #[clap(flatten)]
+  signer: SignerOptions,
 }
 
 pub(crate) async fn run(opts: &SendOptions, _: &CommonOptions) -> Result<(), Box<dyn Error>> {
+  let signer = opts.signer.signer()?;
+
   let tx = ethereum::Transaction::new(
+    transaction_kind(opts)?,
     opts.nonce,
-    opts.gas_price,
     opts.gas_limit,
-    opts.to,
+    Some(opts.to),
     opts.value,
     Vec::new(),
     opts.chain,
-    opts.secret,
+    &signer,
   )?;
-  println!("about to send transaction: {:#?}", &tx);
   println!(
-    "serialized form: {}",
-    hex::encode(ethereum::rlp_serialize(&tx)?)
+    "about to send transaction: {}",
+    serde_json::to_string_pretty(&Json(&tx))?
   );
+  println!("transaction hash: {:?}", tx.hash());
+  println!("serialized form: {}", hex::encode(tx.encode()));
   Ok(())
 }
 
+fn transaction_kind(opts: &SendOptions) -> Result<TransactionKind, Box<dyn Error>> {
+  Ok(match opts.tx_type.as_str() {
+    "legacy" => TransactionKind::Legacy {
+      gas_price: opts.gas_price,
+    },
+    "access-list" => TransactionKind::AccessList {
+      gas_price: opts.gas_price,
+      access_list: vec![],
+    },
+    "dynamic-fee" => TransactionKind::DynamicFee {
+      max_priority_fee_per_gas: opts.max_priority_fee,
+      max_fee_per_gas: opts.gas_price,
+      access_list: vec![],
+    },
+    other => return Err(format!("unknown transaction type: {}", other).into()),
+  })
+}
+
 fn parse_target_addr(s: &str) -> Result<SocketAddr, std::io::Error> {
   Ok(
     s.to_socket_addrs()?
@@ -0,0 +1,58 @@
+// Copyright 2021 The OpenEthereum Authors.
+// Licensed under the Apache License, Version 2.0.
+
+use clap::{ArgGroup, Clap};
+use ethereum::{Address, SecretKey};
+use keystore::Keystore;
+use std::{error::Error, path::PathBuf};
+
+#[derive(Clap, Debug)]
+#[clap(group = ArgGroup::new("signer").required(true))]
+pub(crate) struct SignerOptions {
+  #[clap(
+    long,
+    group = "signer",
+    about = "Secret 256-bit key used as x in an ECDSA signature"
+  )]
+  secret: Option<SecretKey>,
+
+  #[clap(
+    long,
+    group = "signer",
+    about = "Sign with this account from the keystore instead of --secret"
+  )]
+  from: Option<Address>,
+
+  #[clap(
+    long,
+    about = "Directory with encrypted V3 key files, required by --from"
+  )]
+  keystore: Option<PathBuf>,
+
+  #[clap(
+    long,
+    about = "File containing the password of the --from account, prompted for if missing"
+  )]
+  password_file: Option<PathBuf>,
+}
+
+impl SignerOptions {
+  /// The --secret key, or the --from account unlocked from the keystore.
+  pub(crate) fn signer(&self) -> Result<SecretKey, Box<dyn Error>> {
+    match (&self.secret, self.from) {
+      (Some(secret), _) => Ok(secret.clone()),
+      (None, Some(address)) => self.unlock(&address),
+      (None, None) => Err("either --secret or --from is required".into()),
+    }
+  }
+
+  fn unlock(&self, address: &Address) -> Result<SecretKey, Box<dyn Error>> {
+    let dir = self.keystore.as_ref().ok_or("--from requires --keystore")?;
+    let password = match &self.password_file {
+      Some(path) => std::fs::read_to_string(path)?,
+      None => rpassword::prompt_password(format!("password for {:?}: ", address))?,
+    };
+    let password = password.trim_end_matches(&['\r', '\n'][..]);
+    Ok(Keystore::open(dir)?.unlock(address, password.as_bytes())?)
+  }
+}